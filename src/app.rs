@@ -1,31 +1,65 @@
 pub mod base_component;
+pub mod command_history;
 pub mod component;
 pub mod display_focus;
+pub mod history;
+pub mod keymap;
+pub mod register;
 
-use std::{ffi::OsStr, path::Path};
+use std::{collections::BTreeMap, ffi::OsStr, path::Path};
 
 use color_eyre::eyre::Result;
+use command_history::CommandHistory;
 use display_focus::DisplayFocus;
 use eyre::{bail, OptionExt};
+use history::History;
+use keymap::Keymap;
+use register::Registers;
 
 use crate::error::StrataError;
 
 use component::{
+    column_stats::ColumnStatsPanel,
     command::CommandPopup,
     error_popup::ErrorPopup,
     file_view::FileView,
     table_selector::{TableName, TableSelector, INITIAL_TABLE_NAME},
-    table_view::TableView,
+    table_view::{Replacement, TableView},
 };
 
+/// Reversible change to the list of tables
+pub enum TableListEdit {
+    Insert {
+        index: usize,
+        name: TableName,
+        table: Box<TableView>,
+    },
+    Remove {
+        index: usize,
+    },
+    Rename {
+        index: usize,
+        name: TableName,
+    },
+}
+
 #[derive(Default)]
 pub struct App {
     display_focus: DisplayFocus,
     table_selector: TableSelector,
     table_view_list: Vec<TableView>,
+    table_list_history: History<TableListEdit>,
     file_view: Option<FileView>,
+    /// Statistics of the selected column, shown beside the table when set
+    column_stats: Option<ColumnStatsPanel>,
     command: Option<CommandPopup>,
+    command_history: CommandHistory,
     error_popup: ErrorPopup,
+    keymap: Keymap,
+    registers: Registers,
+    /// Text to put in the system clipboard once the frame is drawn
+    clipboard_output: Option<String>,
+    should_quit: bool,
 }
 
 impl App {
@@ -66,6 +100,68 @@ impl App {
             .ok_or_eyre(StrataError::TableNotFound(index.to_string()))
     }
 
+    pub fn is_table_modified(&self, index: usize) -> bool {
+        self.table_view_list
+            .get(index)
+            .is_some_and(TableView::is_modified)
+    }
+
+    /// Indices of the tables that have been edited since they were loaded or saved
+    pub fn unsaved_table_indices(&self) -> Vec<usize> {
+        (0..self.table_view_list.len())
+            .filter(|&index| self.is_table_modified(index))
+            .collect()
+    }
+
+    pub fn unsaved_table_names(&self) -> Vec<&TableName> {
+        self.unsaved_table_indices()
+            .into_iter()
+            .filter_map(|index| self.table_selector.table_name(index))
+            .collect()
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.table_view_list.iter().any(TableView::is_modified)
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Let the main loop stop after the current message
+    pub fn quit(&mut self) -> &mut Self {
+        self.should_quit = true;
+        self
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    pub fn copy_to_clipboard(&mut self, text: String) -> &mut Self {
+        self.clipboard_output = Some(text);
+        self
+    }
+
+    /// Text waiting to be written to the system clipboard
+    pub fn take_clipboard_output(&mut self) -> Option<String> {
+        self.clipboard_output.take()
+    }
+
+    pub fn table_views(&self) -> &[TableView] {
+        &self.table_view_list
+    }
+
+    pub fn table_view_mut(&mut self, index: usize) -> Result<&mut TableView> {
+        self.table_view_list
+            .get_mut(index)
+            .ok_or_eyre(StrataError::TableNotFound(index.to_string()))
+    }
+
     pub fn command(&self) -> Option<&CommandPopup> {
         self.command.as_ref()
     }
@@ -86,6 +182,39 @@ impl App {
         &mut self.error_popup
     }
 
+    pub fn column_stats(&self) -> Option<&ColumnStatsPanel> {
+        self.column_stats.as_ref()
+    }
+
+    /// Show the statistics of the selected column beside the table, or hide them
+    pub fn toggle_column_stats(&mut self) -> Result<&mut Self> {
+        self.column_stats = match self.column_stats {
+            Some(_) => None,
+            None => {
+                let index = self
+                    .table_selector
+                    .selected_index()
+                    .ok_or_eyre(StrataError::NoTableSelected)?;
+                Some(ColumnStatsPanel::new(index, self.selected_table_view()?))
+            }
+        };
+        Ok(self)
+    }
+
+    /// Read the next rows for the column statistics, and tell whether rows are left
+    pub fn update_column_stats(&mut self) -> bool {
+        let (Some(panel), Some(index)) = (
+            self.column_stats.as_mut(),
+            self.table_selector.selected_index(),
+        ) else {
+            return false;
+        };
+        match self.table_view_list.get(index) {
+            Some(tv) => panel.update(index, tv),
+            None => false,
+        }
+    }
+
     pub fn file_view(&self) -> Option<&FileView> {
         self.file_view.as_ref()
     }
@@ -168,9 +297,7 @@ impl App {
     pub fn add_table(&mut self, table_name_str: &str) -> Result<&mut Self> {
         let table_name = TableName::from(table_name_str)?;
 
-        self.table_selector.push_table(table_name)?;
-        self.table_view_list.push(TableView::new());
-        Ok(self)
+        self.push_table(table_name, TableView::new())
     }
 
     pub fn open_table(&mut self, file_path: &Path, has_header: bool) -> Result<&mut Self> {
@@ -180,8 +307,26 @@ impl App {
             .map_or(TableName::from(INITIAL_TABLE_NAME), TableName::from)?;
         let new_table = TableView::from_csv(file_path, has_header)?;
 
-        self.table_selector.push_table(table_name)?;
-        self.table_view_list.push(new_table);
+        self.push_table(table_name, new_table)
+    }
+
+    /// Add the table to the end of the list and select it
+    pub fn push_table(&mut self, table_name: TableName, table: TableView) -> Result<&mut Self> {
+        self.commit_table_list_edit(TableListEdit::Insert {
+            index: self.table_view_list.len(),
+            name: table_name,
+            table: Box::new(table),
+        })
+    }
+
+    /// Add the table to the end of the list without recording it in the history, as the
+    /// files opened at startup are
+    pub fn load_table(&mut self, table_name: TableName, table: TableView) -> Result<&mut Self> {
+        self.apply_table_list_edit(TableListEdit::Insert {
+            index: self.table_view_list.len(),
+            name: table_name,
+            table: Box::new(table),
+        })?;
         Ok(self)
     }
 
@@ -195,18 +340,218 @@ impl App {
             .selected_index()
             .ok_or_eyre(StrataError::NoTableSelected)?;
 
-        self.table_selector.remove_table(index)?;
-        self.table_view_list.remove(index);
+        self.commit_table_list_edit(TableListEdit::Remove { index })?;
         self.display_focus = DisplayFocus::TableSelector;
         Ok(self)
     }
 
-    /// Execute the command and discard it
+    pub fn rename_selected_table(&mut self, table_name: TableName) -> Result<&mut Self> {
+        let index = self
+            .table_selector
+            .selected_index()
+            .ok_or_eyre(StrataError::NoTableSelected)?;
+
+        self.commit_table_list_edit(TableListEdit::Rename {
+            index,
+            name: table_name,
+        })
+    }
+
+    /// Revert the last change to the list of tables
+    pub fn undo_table_list(&mut self) -> Result<&mut Self> {
+        let edit = self
+            .table_list_history
+            .take_undo()
+            .ok_or_eyre(StrataError::NothingToUndo)?;
+        let inverse = self.apply_table_list_edit(edit)?;
+        self.table_list_history.push_undone(inverse);
+        Ok(self)
+    }
+
+    /// Re-apply the last undone change to the list of tables
+    pub fn redo_table_list(&mut self) -> Result<&mut Self> {
+        let edit = self
+            .table_list_history
+            .take_redo()
+            .ok_or_eyre(StrataError::NothingToRedo)?;
+        let inverse = self.apply_table_list_edit(edit)?;
+        self.table_list_history.push_redone(inverse);
+        Ok(self)
+    }
+
+    /// Apply the edit and record its inverse in the history
+    fn commit_table_list_edit(&mut self, edit: TableListEdit) -> Result<&mut Self> {
+        let inverse = self.apply_table_list_edit(edit)?;
+        self.table_list_history.record(inverse);
+        Ok(self)
+    }
+
+    /// Apply the edit, select the affected table and return the edit that reverts it
+    fn apply_table_list_edit(&mut self, edit: TableListEdit) -> Result<TableListEdit> {
+        match edit {
+            TableListEdit::Insert { index, name, table } => {
+                self.table_selector.insert_table(index, name)?;
+                self.table_view_list.insert(index, *table);
+                Ok(TableListEdit::Remove { index })
+            }
+            TableListEdit::Remove { index } => {
+                let name = self
+                    .table_selector
+                    .table_name(index)
+                    .cloned()
+                    .ok_or_eyre(StrataError::TableNotFound(index.to_string()))?;
+                self.table_selector.remove_table(index)?;
+                let table = self.table_view_list.remove(index);
+                Ok(TableListEdit::Insert {
+                    index,
+                    name,
+                    table: Box::new(table),
+                })
+            }
+            TableListEdit::Rename { index, name } => {
+                let old_name = self
+                    .table_selector
+                    .table_name(index)
+                    .cloned()
+                    .ok_or_eyre(StrataError::TableNotFound(index.to_string()))?;
+                self.table_selector.update_table(index, name)?;
+                self.table_selector.select_index(index)?;
+                Ok(TableListEdit::Rename {
+                    index,
+                    name: old_name,
+                })
+            }
+        }
+    }
+
+    /// Rewrite cells of several tables, given with the index of their table
+    ///
+    /// Nothing is changed when one of the tables is readonly. Each table records the
+    /// replacements as a single edit.
+    pub fn replace_in_tables(
+        &mut self,
+        replacements: &[(usize, Replacement)],
+    ) -> Result<&mut Self> {
+        let mut by_table: BTreeMap<usize, Vec<Replacement>> = BTreeMap::new();
+        for (index, replacement) in replacements {
+            by_table
+                .entry(*index)
+                .or_default()
+                .push(replacement.clone());
+        }
+        for &index in by_table.keys() {
+            let tv = self
+                .table_view_list
+                .get(index)
+                .ok_or_eyre(StrataError::TableNotFound(index.to_string()))?;
+            if tv.readonly {
+                bail!(StrataError::ReadOnlyTable);
+            }
+        }
+
+        for (index, replacements) in by_table {
+            self.table_view_mut(index)?
+                .apply_replacements(&replacements)?;
+        }
+        Ok(self)
+    }
+
+    /// Go back to where the searches being previewed started
+    pub fn cancel_search_preview(&mut self) -> &mut Self {
+        for tv in self.table_view_list.iter_mut() {
+            tv.cancel_search_preview();
+        }
+        self
+    }
+
+    /// Execute the command and discard it, recording its input in the history
     pub fn execute_command(&mut self) -> Result<&mut Self> {
-        self.command
+        let command = self
+            .command
             .take()
-            .ok_or_eyre(StrataError::CommandNotFound)?
-            .execute(self)?;
+            .ok_or_eyre(StrataError::CommandNotFound)?;
+        if let Some(key) = command.history_key() {
+            self.command_history.push(key, command.value());
+        }
+        command.execute(self)?;
+        Ok(self)
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// Use these bindings from now on, e.g. ones loaded from the keymap file
+    pub fn set_keymap(&mut self, keymap: Keymap) -> &mut Self {
+        self.keymap = keymap;
+        self
+    }
+
+    pub fn command_history(&self) -> &CommandHistory {
+        &self.command_history
+    }
+
+    /// Use this history from now on, e.g. one loaded from a file
+    pub fn set_command_history(&mut self, command_history: CommandHistory) -> &mut Self {
+        self.command_history = command_history;
+        self
+    }
+
+    /// Replace the command input with an older or newer entry of its history
+    pub fn recall_command_history(&mut self, older: bool) -> Result<&mut Self> {
+        let command = self
+            .command
+            .as_mut()
+            .ok_or_eyre(StrataError::CommandNotFound)?;
+        let entries = command
+            .history_key()
+            .and_then(|key| self.command_history.entries(key));
+        if let Some(entries) = entries {
+            if older {
+                command.recall_older(entries);
+            } else {
+                command.recall_newer(entries);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Complete the command input, or cycle through the candidates already listed
+    pub fn complete_command(&mut self, backward: bool) -> Result<&mut Self> {
+        let command = self
+            .command
+            .as_ref()
+            .ok_or_eyre(StrataError::CommandNotFound)?;
+        let candidates = match command.complete_hook() {
+            Some(complete) if command.completion().is_none() => {
+                Some(complete(command.value(), self))
+            }
+            _ => None,
+        };
+        let command = self
+            .command
+            .as_mut()
+            .ok_or_eyre(StrataError::CommandNotFound)?;
+        match candidates {
+            Some(candidates) => command.set_candidates(candidates),
+            None => command.cycle_candidates(backward),
+        };
+        Ok(self)
+    }
+
+    /// Notify the command that its input has changed
+    pub fn command_input_changed(&mut self) -> Result<&mut Self> {
+        let Some(command) = self.command.as_ref() else {
+            return Ok(self);
+        };
+        if let Some(on_change) = command.on_change_hook() {
+            let input = command.value().to_string();
+            on_change(&input, self)?;
+        }
         Ok(self)
     }
 
@@ -218,6 +563,8 @@ impl App {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
     use crate::test_util::setup_sample_app;
 
     use super::*;
@@ -233,11 +580,11 @@ mod tests {
         assert!(app
             .table_selector
             .table_list()
-            .contains(&&TableName::from("table1").unwrap()));
+            .contains(&TableName::from("table1").unwrap()));
         assert!(app
             .table_selector
             .table_list()
-            .contains(&&TableName::from("table2").unwrap()));
+            .contains(&TableName::from("table2").unwrap()));
         assert_eq!(app.table_view_list.len(), 2);
     }
 
@@ -250,7 +597,62 @@ mod tests {
         assert!(app
             .table_selector
             .table_list()
-            .contains(&&TableName::from("table2").unwrap()));
+            .contains(&TableName::from("table2").unwrap()));
+    }
+
+    #[test]
+    fn test_undo_remove_table() {
+        let mut app = setup_sample_app();
+
+        app.remove_table().unwrap();
+        app.undo_table_list().unwrap();
+        assert_eq!(app.table_selector.table_list().len(), 2);
+        assert_eq!(
+            app.table_selector.selected_table_name(),
+            Some(&TableName::from("table1").unwrap())
+        );
+        assert_eq!(
+            app.selected_table_view().unwrap().cell_value(0, 0).unwrap(),
+            "cell 0-0"
+        );
+
+        app.redo_table_list().unwrap();
+        assert_eq!(app.table_selector.table_list().len(), 1);
+        assert_eq!(app.table_view_list.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_rename_table() {
+        let mut app = setup_sample_app();
+
+        app.rename_selected_table(TableName::from("renamed").unwrap())
+            .unwrap();
+        app.undo_table_list().unwrap();
+        assert_eq!(
+            app.table_selector.selected_table_name(),
+            Some(&TableName::from("table1").unwrap())
+        );
+
+        app.redo_table_list().unwrap();
+        assert_eq!(
+            app.table_selector.selected_table_name(),
+            Some(&TableName::from("renamed").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_unsaved_table_names() {
+        let mut app = setup_sample_app();
+        app.open_table(Path::new("tests/data/fluits.csv"), true)
+            .unwrap();
+        assert!(app.has_unsaved_changes());
+        assert_eq!(
+            app.unsaved_table_names(),
+            vec![
+                &TableName::from("table1").unwrap(),
+                &TableName::from("table2").unwrap()
+            ]
+        );
     }
 
     #[test]
@@ -279,4 +681,26 @@ mod tests {
         );
         assert!(app.execute_command().is_ok());
     }
+
+    #[test]
+    fn test_command_history_per_prompt() {
+        let mut app = setup_sample_app();
+        for input in ["a", "b"] {
+            app.focus_command(CommandPopup::new("Jump", input, Box::new(|_, _| Ok(()))));
+            app.execute_command().unwrap();
+        }
+        app.focus_command(CommandPopup::new("Other", "x", Box::new(|_, _| Ok(()))));
+        app.execute_command().unwrap();
+
+        app.focus_command(CommandPopup::new("Jump", "", Box::new(|_, _| Ok(()))));
+        app.recall_command_history(true).unwrap();
+        assert_eq!(app.command().unwrap().value(), "b");
+        app.recall_command_history(true).unwrap();
+        app.recall_command_history(true).unwrap();
+        assert_eq!(app.command().unwrap().value(), "a");
+        assert_eq!(
+            app.command_history().entries("Other"),
+            Some(&VecDeque::from(["x".to_string()]))
+        );
+    }
 }
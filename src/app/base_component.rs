@@ -1,2 +1,2 @@
+pub mod line_editor;
 pub mod popup;
-
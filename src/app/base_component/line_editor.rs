@@ -0,0 +1,211 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Cursor motion or deletion in a `LineEditor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEdit {
+    Left,
+    Right,
+    Home,
+    End,
+    /// Start of the previous word
+    WordLeft,
+    /// End of the next word
+    WordRight,
+    /// Character under the cursor
+    Delete,
+    /// Whitespace separated word before the cursor
+    DeleteWord,
+    /// Everything before the cursor
+    DeleteToStart,
+}
+
+/// Single line of text with a cursor
+///
+/// The cursor is a byte index that always lies on a character boundary.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    /// Editor holding the text with the cursor at its end
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.len(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Display width of the text before the cursor, i.e. the column of the caret
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.cursor].width()
+    }
+
+    pub fn insert(&mut self, c: char) -> &mut Self {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self
+    }
+
+    /// Remove the character before the cursor
+    pub fn backspace(&mut self) -> &mut Self {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.text.clear();
+        self.cursor = 0;
+        self
+    }
+
+    pub fn edit(&mut self, edit: LineEdit) -> &mut Self {
+        match edit {
+            LineEdit::Left => self.cursor = self.prev_boundary(),
+            LineEdit::Right => self.cursor = self.next_boundary(),
+            LineEdit::Home => self.cursor = 0,
+            LineEdit::End => self.cursor = self.text.len(),
+            LineEdit::WordLeft => {
+                self.cursor = self.word_start_before(|c| !c.is_alphanumeric());
+            }
+            LineEdit::WordRight => {
+                let after_separators = self.text[self.cursor..]
+                    .find(char::is_alphanumeric)
+                    .map_or(self.text.len(), |i| self.cursor + i);
+                self.cursor = self.text[after_separators..]
+                    .find(|c: char| !c.is_alphanumeric())
+                    .map_or(self.text.len(), |i| after_separators + i);
+            }
+            LineEdit::Delete => {
+                if self.cursor < self.text.len() {
+                    self.text.remove(self.cursor);
+                }
+            }
+            LineEdit::DeleteWord => {
+                let start = self.word_start_before(char::is_whitespace);
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            LineEdit::DeleteToStart => {
+                self.text.replace_range(..self.cursor, "");
+                self.cursor = 0;
+            }
+        }
+        self
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .chars()
+            .next_back()
+            .map_or(0, |c| self.cursor - c.len_utf8())
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// Start of the word before the cursor, skipping the separators next to the cursor
+    fn word_start_before(&self, is_separator: impl Fn(char) -> bool) -> usize {
+        let before = &self.text[..self.cursor];
+        let word_end = before.rfind(|c: char| !is_separator(c)).map_or(0, |i| {
+            i + before[i..].chars().next().map_or(0, char::len_utf8)
+        });
+        before[..word_end].rfind(&is_separator).map_or(0, |i| {
+            i + before[i..].chars().next().map_or(0, char::len_utf8)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_in_the_middle() {
+        let mut editor = LineEditor::new("helo");
+        editor.edit(LineEdit::Left).edit(LineEdit::Left).insert('l');
+        assert_eq!(editor.value(), "hello");
+        assert_eq!(editor.cursor(), 3);
+
+        editor.edit(LineEdit::Home).edit(LineEdit::Delete);
+        assert_eq!(editor.value(), "ello");
+        editor.edit(LineEdit::End).backspace();
+        assert_eq!(editor.value(), "ell");
+
+        // the cursor stays within the text
+        editor.edit(LineEdit::Right).edit(LineEdit::Delete);
+        assert_eq!(editor.value(), "ell");
+        editor.edit(LineEdit::Home).edit(LineEdit::Left).backspace();
+        assert_eq!(editor.value(), "ell");
+    }
+
+    #[test]
+    fn test_multi_byte_characters() {
+        let mut editor = LineEditor::new("aé日本");
+        assert_eq!(editor.cursor_width(), 1 + 1 + 2 + 2);
+
+        editor.edit(LineEdit::Left);
+        assert_eq!(editor.cursor_width(), 4);
+        editor.insert('語');
+        assert_eq!(editor.value(), "aé日語本");
+        editor.edit(LineEdit::Left).edit(LineEdit::Left).backspace();
+        assert_eq!(editor.value(), "a日語本");
+        assert_eq!(editor.cursor_width(), 1);
+        editor.edit(LineEdit::Delete);
+        assert_eq!(editor.value(), "a語本");
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let mut editor = LineEditor::new("foo bar-baz  qux");
+        editor.edit(LineEdit::WordLeft);
+        assert_eq!(editor.cursor(), 13);
+        editor.edit(LineEdit::WordLeft);
+        assert_eq!(editor.cursor(), 8);
+        editor.edit(LineEdit::WordLeft).edit(LineEdit::WordLeft);
+        assert_eq!(editor.cursor(), 0);
+        editor.edit(LineEdit::WordLeft);
+        assert_eq!(editor.cursor(), 0);
+
+        editor.edit(LineEdit::WordRight);
+        assert_eq!(editor.cursor(), 3);
+        editor.edit(LineEdit::WordRight);
+        assert_eq!(editor.cursor(), 7);
+        editor.edit(LineEdit::WordRight).edit(LineEdit::WordRight);
+        assert_eq!(editor.cursor(), 16);
+    }
+
+    #[test]
+    fn test_deletions() {
+        let mut editor = LineEditor::new("foo bar-baz  qux");
+        editor.edit(LineEdit::WordLeft).edit(LineEdit::DeleteWord);
+        assert_eq!(editor.value(), "foo qux");
+        assert_eq!(editor.cursor(), 4);
+
+        editor.edit(LineEdit::DeleteToStart);
+        assert_eq!(editor.value(), "qux");
+        assert_eq!(editor.cursor(), 0);
+
+        let mut editor = LineEditor::new("日本 語");
+        editor.edit(LineEdit::DeleteWord);
+        assert_eq!(editor.value(), "日本 ");
+        editor.edit(LineEdit::DeleteWord);
+        assert_eq!(editor.value(), "");
+    }
+}
@@ -1,7 +1,7 @@
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::Style,
+    layout::{Position, Rect},
+    style::{Modifier, Style},
     text::{Line, Text},
     widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
 };
@@ -13,6 +13,9 @@ pub struct Popup<'a> {
     pub border_style: Style,
     pub title_style: Style,
     pub style: Style,
+    /// Column of the caret in a single line content, which is then scrolled to keep the
+    /// caret visible
+    pub cursor: Option<usize>,
 }
 
 impl Widget for Popup<'_> {
@@ -24,10 +27,21 @@ impl Widget for Popup<'_> {
             .title_style(self.title_style)
             .borders(Borders::ALL)
             .border_style(self.border_style);
-        Paragraph::new(self.content)
-            .wrap(Wrap { trim: true })
-            .style(self.style)
-            .block(block)
-            .render(area, buf);
+        let inner = block.inner(area);
+        let paragraph = Paragraph::new(self.content).style(self.style).block(block);
+
+        let Some(cursor) = self.cursor else {
+            paragraph.wrap(Wrap { trim: true }).render(area, buf);
+            return;
+        };
+        let width = inner.width as usize;
+        let scroll = (cursor + 1).saturating_sub(width);
+        paragraph.scroll((0, scroll as u16)).render(area, buf);
+        if width > 0 && inner.height > 0 {
+            let caret = Position::new(inner.x + (cursor - scroll) as u16, inner.y);
+            if let Some(cell) = buf.cell_mut(caret) {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+        }
     }
 }
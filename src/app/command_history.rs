@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::Result;
+
+/// Entries kept for each prompt, the oldest ones being dropped first
+pub const COMMAND_HISTORY_LIMIT: usize = 100;
+
+/// Inputs submitted to the command prompts, grouped by prompt
+#[derive(Debug, Default)]
+pub struct CommandHistory {
+    entries: HashMap<String, VecDeque<String>>,
+    /// File the history is loaded from and saved to, if it is persisted
+    file_path: Option<PathBuf>,
+}
+
+impl CommandHistory {
+    /// Entries of the prompt, from the oldest to the newest
+    pub fn entries(&self, key: &str) -> Option<&VecDeque<String>> {
+        self.entries.get(key)
+    }
+
+    /// Record the input as the newest entry of the prompt, moving it there if it was
+    /// already recorded
+    pub fn push(&mut self, key: &str, input: &str) -> &mut Self {
+        if input.is_empty() {
+            return self;
+        }
+        let entries = self.entries.entry(key.to_string()).or_default();
+        entries.retain(|entry| entry != input);
+        if entries.len() == COMMAND_HISTORY_LIMIT {
+            entries.pop_front();
+        }
+        entries.push_back(input.to_string());
+        self
+    }
+
+    /// Default history file, `$XDG_STATE_HOME/strata/history` or
+    /// `~/.local/state/strata/history`
+    pub fn default_file_path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+            })?;
+        Some(state_home.join("strata").join("history"))
+    }
+
+    /// Empty history saved to the file from now on
+    pub fn persisted(file_path: &Path) -> Self {
+        Self {
+            entries: HashMap::new(),
+            file_path: Some(file_path.to_path_buf()),
+        }
+    }
+
+    /// Load the history from the file and save it there from now on
+    ///
+    /// A missing file is not an error: it is created on the first save.
+    pub fn load(file_path: &Path) -> Result<Self> {
+        let mut history = Self::persisted(file_path);
+        if !file_path.exists() {
+            return Ok(history);
+        }
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .from_path(file_path)?;
+        for record in reader.records() {
+            let record = record?;
+            if let (Some(key), Some(input)) = (record.get(0), record.get(1)) {
+                history.push(key, input);
+            }
+        }
+        Ok(history)
+    }
+
+    /// Write the history to its file, if it is persisted
+    pub fn save(&self) -> Result<()> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(file_path)?;
+        let mut keys: Vec<&String> = self.entries.keys().collect();
+        keys.sort();
+        for key in keys {
+            for input in &self.entries[key] {
+                writer.write_record([key, input])?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut history = CommandHistory::default();
+        history
+            .push("Jump", "1 1")
+            .push("Jump", "2 2")
+            .push("Jump", "1 1");
+        history.push("Jump", "").push("Open", "a.csv");
+
+        assert_eq!(
+            history.entries("Jump"),
+            Some(&VecDeque::from(vec!["2 2".to_string(), "1 1".to_string()]))
+        );
+        assert_eq!(history.entries("Open").map(VecDeque::len), Some(1));
+        assert!(history.entries("Save").is_none());
+
+        for i in 0..COMMAND_HISTORY_LIMIT {
+            history.push("Jump", &i.to_string());
+        }
+        let entries = history.entries("Jump").unwrap();
+        assert_eq!(entries.len(), COMMAND_HISTORY_LIMIT);
+        assert_eq!(entries.front().map(String::as_str), Some("0"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join("strata_test_command_history")
+            .join("history");
+        let _ = fs::remove_file(&path);
+
+        let mut history = CommandHistory::load(&path).unwrap();
+        assert!(history.entries("Jump").is_none());
+        history
+            .push("Jump", "1 1")
+            .push("Search", "tab\there \"quoted\"")
+            .push("Jump", "2 2");
+        history.save().unwrap();
+
+        let loaded = CommandHistory::load(&path).unwrap();
+        assert_eq!(loaded.entries("Jump"), history.entries("Jump"));
+        assert_eq!(loaded.entries("Search"), history.entries("Search"));
+
+        // nothing is written when the history is not persisted
+        assert!(CommandHistory::default().save().is_ok());
+    }
+
+    #[test]
+    fn test_load_malformed() {
+        let path = std::env::temp_dir()
+            .join("strata_test_command_history_malformed")
+            .join("history");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "Jump\t1 1\nJump\t2 2\textra\n").unwrap();
+        assert!(CommandHistory::load(&path).is_err());
+
+        let mut history = CommandHistory::persisted(&path);
+        history.push("Jump", "3 3");
+        history.save().unwrap();
+        let loaded = CommandHistory::load(&path).unwrap();
+        assert_eq!(loaded.entries("Jump"), history.entries("Jump"));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod column_stats;
 pub mod command;
 pub mod error_popup;
 pub mod file_view;
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::component::{
+    component_style,
+    table_view::{column_name, natural_cmp, ColumnType, TableView},
+    StrataComponent,
+};
+
+/// Rows read at each update, so that keys are still handled while a large table is read
+pub const STATS_CHUNK_SIZE: usize = 50_000;
+
+/// Number of most frequent values shown
+const TOP_VALUE_COUNT: usize = 5;
+
+/// Mean, median and standard deviation of a column holding only numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericSummary {
+    pub mean: f64,
+    pub median: f64,
+    /// Sample standard deviation, zero for a single value
+    pub stddev: f64,
+}
+
+/// Statistics of the values of a column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub count: usize,
+    pub empty: usize,
+    pub distinct: usize,
+    /// Smallest and largest non-empty values, numbers being compared by value
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Only when every non-empty value is a number
+    pub numeric: Option<NumericSummary>,
+    /// Most frequent non-empty values with their count, the most frequent first
+    pub top_values: Vec<(String, usize)>,
+}
+
+impl ColumnStats {
+    pub fn from_values<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut builder = StatsBuilder::default();
+        values.into_iter().for_each(|value| builder.add(value));
+        builder.finish()
+    }
+}
+
+/// Statistics being gathered one value at a time, from which values can be taken back
+#[derive(Debug, Clone, Default)]
+struct StatsBuilder {
+    count: usize,
+    empty: usize,
+    counts: HashMap<String, usize>,
+    /// Values read so far that are numbers
+    numbers: Vec<f64>,
+    /// Non-empty values read so far that are not numbers
+    non_numbers: usize,
+    /// Running mean of the numbers and sum of squared differences from it
+    mean: f64,
+    squares: f64,
+}
+
+/// Value read as a number, if it is one
+fn parse_number(value: &str) -> Option<f64> {
+    ColumnType::Decimal
+        .accepts(value)
+        .then(|| value.parse::<f64>().ok())
+        .flatten()
+}
+
+impl StatsBuilder {
+    fn add(&mut self, value: &str) {
+        self.count += 1;
+        if value.is_empty() {
+            self.empty += 1;
+            return;
+        }
+        match self.counts.get_mut(value) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(value.to_string(), 1);
+            }
+        }
+
+        match parse_number(value) {
+            Some(number) => {
+                self.numbers.push(number);
+                let delta = number - self.mean;
+                self.mean += delta / self.numbers.len() as f64;
+                self.squares += delta * (number - self.mean);
+            }
+            None => self.non_numbers += 1,
+        }
+    }
+
+    /// Take back a value that was added
+    fn remove(&mut self, value: &str) {
+        self.count -= 1;
+        if value.is_empty() {
+            self.empty -= 1;
+            return;
+        }
+        if let Some(count) = self.counts.get_mut(value) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(value);
+            }
+        }
+
+        let Some(number) = parse_number(value) else {
+            self.non_numbers -= 1;
+            return;
+        };
+        if let Some(index) = self
+            .numbers
+            .iter()
+            .position(|n| n.to_bits() == number.to_bits())
+        {
+            self.numbers.swap_remove(index);
+        }
+        match self.numbers.len() {
+            0 => (self.mean, self.squares) = (0.0, 0.0),
+            len => {
+                let mean = self.mean;
+                self.mean -= (number - mean) / len as f64;
+                self.squares = (self.squares - (number - mean) * (number - self.mean)).max(0.0);
+            }
+        }
+    }
+
+    fn finish(&self) -> ColumnStats {
+        let numeric = Some(self.numbers.clone())
+            .filter(|numbers| !numbers.is_empty() && self.non_numbers == 0)
+            .map(|mut numbers| {
+                let len = numbers.len();
+                let mid = len / 2;
+                let (lower, &mut upper, _) = numbers.select_nth_unstable_by(mid, f64::total_cmp);
+                let median = if len % 2 == 0 {
+                    let lower = lower.iter().copied().fold(f64::MIN, f64::max);
+                    (lower + upper) / 2.0
+                } else {
+                    upper
+                };
+                NumericSummary {
+                    mean: self.mean,
+                    median,
+                    stddev: match len {
+                        1 => 0.0,
+                        _ => (self.squares / (len - 1) as f64).sqrt(),
+                    },
+                }
+            });
+
+        let min = self.counts.keys().min_by(|a, b| natural_cmp(a, b)).cloned();
+        let max = self.counts.keys().max_by(|a, b| natural_cmp(a, b)).cloned();
+        let distinct = self.counts.len();
+        let mut top_values: Vec<(String, usize)> = self
+            .counts
+            .iter()
+            .map(|(value, count)| (value.clone(), *count))
+            .collect();
+        top_values.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| natural_cmp(a, b))
+        });
+        top_values.truncate(TOP_VALUE_COUNT);
+
+        ColumnStats {
+            count: self.count,
+            empty: self.empty,
+            distinct,
+            min,
+            max,
+            numeric,
+            top_values,
+        }
+    }
+}
+
+/// Side panel with the statistics of the selected column
+///
+/// The rows are read a chunk at a time by `update`, and read again from the start when
+/// another column is selected or the table is edited other than by changing values. New
+/// values of the rows already read replace the old ones in the statistics.
+#[derive(Debug, Clone)]
+pub struct ColumnStatsPanel {
+    table: usize,
+    col: usize,
+    revision: u64,
+    header: String,
+    column_type: ColumnType,
+    /// Row to read next
+    next_row: usize,
+    row_count: usize,
+    /// Values of the rows read so far, to take back from the statistics when they change
+    values: Vec<String>,
+    builder: StatsBuilder,
+    stats: Option<ColumnStats>,
+}
+
+impl ColumnStatsPanel {
+    pub fn new(table: usize, tv: &TableView) -> Self {
+        let (_, col) = tv.selected_index().unwrap_or((0, 0));
+        Self {
+            table,
+            col,
+            revision: tv.revision(),
+            header: tv.headers().get(col).cloned().unwrap_or_default(),
+            column_type: tv.column_type(col).clone(),
+            next_row: 0,
+            row_count: tv.rows.len(),
+            values: Vec::new(),
+            builder: StatsBuilder::default(),
+            stats: None,
+        }
+    }
+
+    /// Statistics of the column, once every row has been read
+    pub fn stats(&self) -> Option<&ColumnStats> {
+        self.stats.as_ref()
+    }
+
+    /// Read the next chunk of rows of the selected column of the table, and tell whether
+    /// rows are left to read
+    pub fn update(&mut self, table: usize, tv: &TableView) -> bool {
+        let col = tv.selected_index().map_or(0, |(_, col)| col);
+        if (self.table, self.col) != (table, col) {
+            *self = Self::new(table, tv);
+        } else if self.revision != tv.revision() {
+            match tv.changed_cells_since(self.revision) {
+                Some(cells) => self.apply_changes(tv, &cells),
+                None => *self = Self::new(table, tv),
+            }
+        }
+        if self.stats.is_some() {
+            return false;
+        }
+
+        let end = (self.next_row + STATS_CHUNK_SIZE).min(tv.rows.len());
+        for row in self.next_row..end {
+            let value = tv.computed_value(row, self.col).into_owned();
+            self.builder.add(&value);
+            self.values.push(value);
+        }
+        self.next_row = end;
+        if end < tv.rows.len() {
+            return true;
+        }
+        self.stats = Some(self.builder.finish());
+        false
+    }
+
+    /// Replace the old values of the changed cells already read with their new values
+    fn apply_changes(&mut self, tv: &TableView, cells: &[(usize, usize)]) {
+        for &(row, col) in cells {
+            if col != self.col || row >= self.next_row {
+                continue;
+            }
+            let value = tv.computed_value(row, col);
+            if value != self.values[row] {
+                self.builder.remove(&self.values[row]);
+                self.builder.add(&value);
+                self.values[row] = value.into_owned();
+            }
+        }
+        self.revision = tv.revision();
+        if self.stats.is_some() {
+            self.stats = Some(self.builder.finish());
+        }
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        let label_style = Style::default().fg(Color::DarkGray);
+        let entry = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{:<9}", label), label_style),
+                value.into(),
+            ])
+        };
+
+        let mut lines = vec![
+            Line::from(format!("{} ({})", self.header, column_name(self.col))),
+            entry("type", self.column_type.to_string()),
+        ];
+        let Some(stats) = &self.stats else {
+            lines.push(entry(
+                "reading",
+                format!("{}/{} rows", self.next_row, self.row_count),
+            ));
+            return lines;
+        };
+
+        lines.extend([
+            entry("rows", stats.count.to_string()),
+            entry("empty", stats.empty.to_string()),
+            entry("distinct", stats.distinct.to_string()),
+        ]);
+        if let (Some(min), Some(max)) = (&stats.min, &stats.max) {
+            lines.push(entry("min", min.clone()));
+            lines.push(entry("max", max.clone()));
+        }
+        if let Some(numeric) = stats.numeric {
+            lines.push(entry("mean", format_number(numeric.mean)));
+            lines.push(entry("median", format_number(numeric.median)));
+            lines.push(entry("stddev", format_number(numeric.stddev)));
+        }
+        if !stats.top_values.is_empty() {
+            lines.push(Line::styled("top values", label_style));
+            lines.extend(
+                stats
+                    .top_values
+                    .iter()
+                    .map(|(value, count)| Line::from(format!("{:>6}  {}", count, value))),
+            );
+        }
+        lines
+    }
+}
+
+/// Number rounded to four decimal places, without trailing zeros
+fn format_number(number: f64) -> String {
+    let formatted = format!("{:.4}", number);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+impl StrataComponent for ColumnStatsPanel {
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let paragraph = Paragraph::new(self.lines())
+            .block(Block::default().title("Column").borders(Borders::ALL))
+            .style(component_style(is_focused));
+        frame.render_widget(paragraph, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = ColumnStats::from_values(["3", "", "1", "10", "3", "-2.5", ""]);
+        assert_eq!(stats.count, 7);
+        assert_eq!(stats.empty, 2);
+        assert_eq!(stats.distinct, 4);
+        assert_eq!(stats.min.as_deref(), Some("-2.5"));
+        assert_eq!(stats.max.as_deref(), Some("10"));
+        assert_eq!(stats.top_values[0], ("3".to_string(), 2));
+        assert_eq!(stats.top_values[1], ("-2.5".to_string(), 1));
+
+        let numeric = stats.numeric.unwrap();
+        assert_eq!(format_number(numeric.mean), "2.9");
+        assert_eq!(numeric.median, 3.0);
+        assert_eq!(format_number(numeric.stddev), "4.5607");
+
+        let stats = ColumnStats::from_values(["1", "4", "item10", "item9"]);
+        assert_eq!(stats.numeric, None);
+        assert_eq!(stats.max.as_deref(), Some("item10"));
+        assert_eq!(
+            ColumnStats::from_values(["2", "4"]).numeric.unwrap().median,
+            3.0
+        );
+        assert_eq!(ColumnStats::from_values([""]).min, None);
+    }
+
+    #[test]
+    fn test_stats_ties() {
+        // equal numbers and equal counts still give a single order, whatever the input order
+        let values = [
+            "1.7x", "1.10", "1.0", "1", "01", "1.5", "1.7x", "1.5", "b", "a",
+        ];
+        let stats = ColumnStats::from_values(values);
+        assert_eq!(stats.min.as_deref(), Some("01"));
+        assert_eq!(stats.max.as_deref(), Some("b"));
+        assert_eq!(
+            stats.top_values,
+            vec![
+                ("1.5".to_string(), 2),
+                ("1.7x".to_string(), 2),
+                ("01".to_string(), 1),
+                ("1".to_string(), 1),
+                ("1.0".to_string(), 1),
+            ]
+        );
+        let reversed = ColumnStats::from_values(values.iter().rev().copied());
+        assert_eq!(reversed, stats);
+    }
+
+    #[test]
+    fn test_panel_update() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        for _ in 0..STATS_CHUNK_SIZE {
+            tv.rows.push(vec!["kiwi".to_string(), "50".to_string()]);
+        }
+        tv.select_cell(0, 1).unwrap();
+
+        let mut panel = ColumnStatsPanel::new(0, &tv);
+        assert!(panel.update(0, &tv));
+        assert!(panel.stats().is_none());
+        assert!(!panel.update(0, &tv));
+        let stats = panel.stats().unwrap();
+        assert_eq!(stats.count, STATS_CHUNK_SIZE + 3);
+        assert_eq!(stats.top_values[0], ("50".to_string(), STATS_CHUNK_SIZE));
+
+        // new values are counted without reading the rows again
+        tv.update_cell(0, 1, "").unwrap();
+        tv.update_cell(1, 1, "50").unwrap();
+        assert!(!panel.update(0, &tv));
+        let stats = panel.stats().unwrap();
+        assert_eq!(stats.empty, 1);
+        assert_eq!(
+            stats.top_values[0],
+            ("50".to_string(), STATS_CHUNK_SIZE + 1)
+        );
+        assert_eq!(stats.distinct, 2);
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        panel.update(0, &tv);
+        assert_eq!(
+            panel.stats().unwrap(),
+            &ColumnStats::from_values(tv.rows.iter().map(|row| row[1].as_str()))
+        );
+
+        // as are the formulas computed again
+        tv.update_cell(2, 1, "=B0*2").unwrap();
+        panel.update(0, &tv);
+        tv.update_cell(0, 1, "75").unwrap();
+        panel.update(0, &tv);
+        let stats = panel.stats().unwrap();
+        assert_eq!(stats.max.as_deref(), Some("200"));
+        assert_eq!(stats.numeric.unwrap().median, 50.0);
+
+        // other edits start over
+        tv.insert_row(0).unwrap();
+        panel.update(0, &tv);
+        assert!(panel.stats().is_none());
+        while panel.update(0, &tv) {}
+        assert_eq!(panel.stats().unwrap().empty, 1);
+
+        tv.select_cell(0, 0).unwrap();
+        while panel.update(0, &tv) {}
+        assert_eq!(panel.stats().unwrap().distinct, 4);
+        assert_eq!(panel.lines()[0].to_string(), "fluits (A)");
+    }
+}
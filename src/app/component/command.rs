@@ -1,28 +1,66 @@
+mod completion;
+
+use std::{collections::VecDeque, rc::Rc};
+
 use eyre::Result;
 use ratatui::{
     layout::Rect,
     style::{Style, Stylize},
+    text::{Line, Text},
     Frame,
 };
 
-use crate::app::{base_component::popup::Popup, App};
+use crate::app::{
+    base_component::{
+        line_editor::{LineEdit, LineEditor},
+        popup::Popup,
+    },
+    App,
+};
 
 use super::{component_style, StrataPopup};
 
+pub use completion::{
+    common_prefix, complete_header, complete_path, complete_table_name, Completion,
+};
+
 type Command = Box<dyn FnOnce(&str, &mut App) -> Result<()>>;
+type OnChange = Rc<dyn Fn(&str, &mut App) -> Result<()>>;
+type Complete = Rc<dyn Fn(&str, &App) -> Vec<String>>;
+
+/// Number of candidates listed under the popup at once
+const COMPLETION_LIST_HEIGHT: usize = 8;
 
 pub struct CommandPopup {
     title: String,
-    input: String,
+    input: LineEditor,
     command: Command,
+    /// Called with the input every time it changes
+    on_change: Option<OnChange>,
+    /// Prompts sharing this key share their history, none is kept without it
+    history_key: Option<String>,
+    /// Entry of the history shown in the input, counted from the newest one
+    history_index: Option<usize>,
+    /// Input typed before going through the history
+    draft: String,
+    /// Gives the candidates for the input when Tab is pressed
+    complete: Option<Complete>,
+    /// Candidates listed under the popup until the input is edited
+    completion: Option<Completion>,
 }
 
 impl Default for CommandPopup {
     fn default() -> Self {
         Self {
             title: String::new(),
-            input: String::new(),
+            input: LineEditor::default(),
             command: Box::new(|_, _| Ok(())),
+            on_change: None,
+            history_key: None,
+            history_index: None,
+            draft: String::new(),
+            complete: None,
+            completion: None,
         }
     }
 }
@@ -31,31 +69,160 @@ impl CommandPopup {
     pub fn new(command_name: &str, input: &str, function: Command) -> CommandPopup {
         CommandPopup {
             title: command_name.to_string(),
-            input: input.to_string(),
+            input: LineEditor::new(input),
             command: function,
+            on_change: None,
+            history_key: Some(command_name.to_string()),
+            history_index: None,
+            draft: String::new(),
+            complete: None,
+            completion: None,
+        }
+    }
+
+    pub fn history_key(&self) -> Option<&str> {
+        self.history_key.as_deref()
+    }
+
+    /// Share the history with the other prompts using this key instead of the title,
+    /// e.g. when the title shows a count
+    pub fn with_history_key(mut self, key: &str) -> Self {
+        self.history_key = Some(key.to_string());
+        self
+    }
+
+    /// Do not record the input, e.g. for confirmations
+    pub fn without_history(mut self) -> Self {
+        self.history_key = None;
+        self
+    }
+
+    /// Complete the input with the candidates given by `complete` when Tab is pressed
+    pub fn with_completion(
+        mut self,
+        complete: impl Fn(&str, &App) -> Vec<String> + 'static,
+    ) -> Self {
+        self.complete = Some(Rc::new(complete));
+        self
+    }
+
+    pub(crate) fn complete_hook(&self) -> Option<Complete> {
+        self.complete.clone()
+    }
+
+    pub fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    /// Complete the input with the candidates found for it
+    ///
+    /// A single candidate replaces the input. Otherwise the input is extended to the
+    /// prefix the candidates share and they are listed to be cycled through.
+    pub fn set_candidates(&mut self, candidates: Vec<String>) -> &mut Self {
+        self.completion = None;
+        match candidates.as_slice() {
+            [] => {}
+            [candidate] => self.input = LineEditor::new(candidate),
+            _ => {
+                let prefix = common_prefix(&candidates);
+                if prefix.len() > self.input.value().len() {
+                    self.input = LineEditor::new(prefix);
+                }
+                self.completion = Some(Completion::new(candidates));
+            }
+        }
+        self
+    }
+
+    /// Show the next (or previous) listed candidate in the input
+    pub fn cycle_candidates(&mut self, backward: bool) -> &mut Self {
+        if let Some(candidate) = self
+            .completion
+            .as_mut()
+            .and_then(|completion| completion.cycle(backward))
+        {
+            self.input = LineEditor::new(candidate);
         }
+        self
+    }
+
+    /// Show the entry before the one shown, keeping what was typed to come back to it
+    pub fn recall_older(&mut self, entries: &VecDeque<String>) -> &mut Self {
+        let index = self.history_index.map_or(0, |index| index + 1);
+        if index >= entries.len() {
+            return self;
+        }
+        if self.history_index.is_none() {
+            self.draft = self.input.value().to_string();
+        }
+        self.history_index = Some(index);
+        self.input = LineEditor::new(&entries[entries.len() - 1 - index]);
+        self.completion = None;
+        self
+    }
+
+    /// Show the entry after the one shown, or what was typed after the newest one
+    pub fn recall_newer(&mut self, entries: &VecDeque<String>) -> &mut Self {
+        match self.history_index {
+            None => {}
+            Some(0) => {
+                self.history_index = None;
+                self.input = LineEditor::new(&self.draft);
+            }
+            Some(index) => {
+                let index = (index - 1).min(entries.len().saturating_sub(1));
+                self.history_index = Some(index);
+                self.input = LineEditor::new(&entries[entries.len() - 1 - index]);
+            }
+        }
+        self.completion = None;
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Fn(&str, &mut App) -> Result<()> + 'static) -> Self {
+        self.on_change = Some(Rc::new(on_change));
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value()
     }
 
     pub fn command_name(&self) -> &str {
         &self.title
     }
 
+    pub(crate) fn on_change_hook(&self) -> Option<OnChange> {
+        self.on_change.clone()
+    }
+
     pub fn execute(self, app: &mut App) -> Result<()> {
-        (self.command)(&self.input, app)
+        (self.command)(self.input.value(), app)
     }
 
+    /// Insert the character at the cursor
     pub fn input(&mut self, c: char) -> &mut Self {
-        self.input.push(c);
+        self.input.insert(c);
+        self.completion = None;
         self
     }
 
+    /// Remove the character before the cursor
     pub fn pop(&mut self) -> &mut Self {
-        self.input.pop();
+        self.input.backspace();
+        self.completion = None;
+        self
+    }
+
+    pub fn edit(&mut self, edit: LineEdit) -> &mut Self {
+        self.input.edit(edit);
+        self.completion = None;
         self
     }
 
     pub fn clear(&mut self) -> &mut Self {
         self.input.clear();
+        self.completion = None;
         self
     }
 }
@@ -70,11 +237,136 @@ impl StrataPopup for CommandPopup {
         };
         let popup = Popup {
             title: self.title.clone().into(),
-            content: self.input.clone().into(),
+            content: self.input.value().to_string().into(),
             style: component_style(true),
             title_style: Style::new().white().bold(),
             border_style: Style::default(),
+            cursor: Some(self.input.cursor_width()),
         };
         frame.render_widget(popup, area);
+
+        if let Some(completion) = &self.completion {
+            render_completion(frame, completion, area);
+        }
+    }
+}
+
+/// List the candidates under the popup, scrolled to keep the selected one visible
+fn render_completion(frame: &mut Frame, completion: &Completion, popup_area: Rect) {
+    let candidates = completion.candidates();
+    let height = candidates.len().min(COMPLETION_LIST_HEIGHT);
+    let area = Rect {
+        y: popup_area.bottom(),
+        height: height as u16 + 2,
+        ..popup_area
+    }
+    .intersection(frame.area());
+    let offset = completion
+        .selected()
+        .map_or(0, |index| (index + 1).saturating_sub(height));
+    let lines: Vec<Line> = candidates
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(height)
+        .map(|(index, candidate)| {
+            let line = Line::from(candidate.as_str());
+            if completion.selected() == Some(index) {
+                line.reversed()
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let popup = Popup {
+        title: format!("{} candidates", candidates.len()).into(),
+        content: Text::from(lines),
+        style: component_style(true),
+        title_style: Style::new().white(),
+        border_style: Style::default(),
+        cursor: None,
+    };
+    frame.render_widget(popup, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, style::Modifier, Terminal};
+
+    use super::*;
+
+    fn render(command: &CommandPopup) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(40, 9)).unwrap();
+        terminal.draw(|f| command.render(f)).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn test_recall_history() {
+        let entries = VecDeque::from(["first".to_string(), "second".to_string()]);
+        let mut command = CommandPopup::new("test", "draft", Box::new(|_, _| Ok(())));
+        command.recall_older(&entries);
+        assert_eq!(command.value(), "second");
+        command.recall_older(&entries).recall_older(&entries);
+        assert_eq!(command.value(), "first");
+        command.recall_newer(&entries);
+        assert_eq!(command.value(), "second");
+        command.recall_newer(&entries).recall_newer(&entries);
+        assert_eq!(command.value(), "draft");
+    }
+
+    #[test]
+    fn test_edit_in_the_middle() {
+        let mut command = CommandPopup::new("test", "hllo", Box::new(|_, _| Ok(())));
+        command
+            .edit(LineEdit::Home)
+            .edit(LineEdit::Right)
+            .input('e');
+        assert_eq!(command.value(), "hello");
+        command.edit(LineEdit::End).pop();
+        assert_eq!(command.value(), "hell");
+    }
+
+    #[test]
+    fn test_render_caret() {
+        let mut command = CommandPopup::new("test", "日本語", Box::new(|_, _| Ok(())));
+        command.edit(LineEdit::Left);
+
+        // the popup starts at x 10 and its content at x 11
+        let terminal = render(&command);
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(15, 4)].symbol(), "語");
+        assert!(buffer[(15, 4)].modifier.contains(Modifier::REVERSED));
+        assert!(!buffer[(13, 4)].modifier.contains(Modifier::REVERSED));
+
+        // a long input is scrolled to keep the caret visible
+        let mut command = CommandPopup::new("test", &"a".repeat(30), Box::new(|_, _| Ok(())));
+        command.input('b');
+        let terminal = render(&command);
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(27, 4)].symbol(), "b");
+        assert!(buffer[(28, 4)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_render_candidates() {
+        let mut command = CommandPopup::new("test", "ta", Box::new(|_, _| Ok(())));
+        command.set_candidates(vec!["table1".to_string(), "table2".to_string()]);
+        assert_eq!(command.value(), "table");
+        command.cycle_candidates(true);
+        assert_eq!(command.value(), "table2");
+
+        // the list is drawn under the popup, which ends at y 7
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|f| command.render(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(11, 8)].symbol(), "t");
+        assert_eq!(buffer[(16, 9)].symbol(), "2");
+        assert!(buffer[(11, 9)].modifier.contains(Modifier::REVERSED));
+        assert!(!buffer[(11, 8)].modifier.contains(Modifier::REVERSED));
+
+        command.input('x');
+        assert!(command.completion().is_none());
     }
 }
@@ -0,0 +1,166 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::app::App;
+
+/// Candidates offered for the input of a prompt
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Completion {
+    candidates: Vec<String>,
+    /// Candidate shown in the input
+    selected: Option<usize>,
+}
+
+impl Completion {
+    pub fn new(candidates: Vec<String>) -> Self {
+        Self {
+            candidates,
+            selected: None,
+        }
+    }
+
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Select the next (or previous) candidate, wrapping around the list
+    pub fn cycle(&mut self, backward: bool) -> Option<&str> {
+        let len = self.candidates.len();
+        if len == 0 {
+            return None;
+        }
+        let index = match (self.selected, backward) {
+            (None, false) => 0,
+            (None, true) => len - 1,
+            (Some(index), false) => (index + 1) % len,
+            (Some(index), true) => (index + len - 1) % len,
+        };
+        self.selected = Some(index);
+        Some(&self.candidates[index])
+    }
+}
+
+/// Longest prefix shared by all the candidates
+pub fn common_prefix(candidates: &[String]) -> &str {
+    let Some((first, rest)) = candidates.split_first() else {
+        return "";
+    };
+    let len = rest.iter().fold(first.len(), |len, candidate| {
+        first[..len]
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(candidate.len()), |((i, _), _)| i)
+    });
+    &first[..len]
+}
+
+/// Files and directories whose path starts with the input
+///
+/// Directories end with a `/` so that completing again goes into them. Hidden entries
+/// are only offered when the input asks for them.
+pub fn complete_path(input: &str, _app: &App) -> Vec<String> {
+    let (dir, prefix) = match input.rfind('/') {
+        Some(index) => input.split_at(index + 1),
+        None => ("", input),
+    };
+    let dir_path = if dir.is_empty() { "." } else { dir };
+    let Ok(entries) = fs::read_dir(Path::new(dir_path)) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{dir}{name}{suffix}"))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+/// Names of the open tables starting with the input
+pub fn complete_table_name(input: &str, app: &App) -> Vec<String> {
+    (0..)
+        .map_while(|index| app.table_selector().table_name(index))
+        .map(|name| name.as_str())
+        .filter(|name| name.starts_with(input))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Headers of all the open tables starting with the input
+pub fn complete_header(input: &str, app: &App) -> Vec<String> {
+    app.table_views()
+        .iter()
+        .flat_map(|tv| tv.headers())
+        .filter(|header| !header.is_empty() && header.starts_with(input))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::setup_sample_app;
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(common_prefix(&strings(&["table1", "table2", "tab"])), "tab");
+        assert_eq!(common_prefix(&strings(&["日本", "日本語"])), "日本");
+        assert_eq!(common_prefix(&strings(&["a", "b"])), "");
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut completion = Completion::new(strings(&["a", "b", "c"]));
+        assert_eq!(completion.cycle(true), Some("c"));
+        assert_eq!(completion.cycle(false), Some("a"));
+        assert_eq!(completion.cycle(false), Some("b"));
+        assert_eq!(completion.selected(), Some(1));
+        assert_eq!(Completion::new(Vec::new()).cycle(false), None);
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let app = setup_sample_app();
+        assert_eq!(complete_path("tests/da", &app), strings(&["tests/data/"]));
+        assert_eq!(
+            complete_path("tests/data/flu", &app),
+            strings(&["tests/data/fluits.csv", "tests/data/fluits.tsv"])
+        );
+        assert!(complete_path("no/such/dir/", &app).is_empty());
+    }
+
+    #[test]
+    fn test_complete_table_name_and_header() {
+        let mut app = setup_sample_app();
+        assert_eq!(
+            complete_table_name("tab", &app),
+            strings(&["table1", "table2"])
+        );
+        assert!(complete_table_name("x", &app).is_empty());
+
+        app.selected_table_view_mut()
+            .unwrap()
+            .update_header(0, "price")
+            .unwrap();
+        assert_eq!(complete_header("pr", &app), strings(&["price"]));
+    }
+}
@@ -55,6 +55,7 @@ impl StrataPopup for ErrorPopup {
             style: component_style(true),
             title_style: Style::new().white().bold(),
             border_style: Style::default(),
+            cursor: None,
         };
         frame.render_widget(popup, area);
     }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use eyre::{bail, Result};
 use ratatui::{
     style::{Style, Stylize},
@@ -31,9 +33,9 @@ impl TableName {
     }
 }
 
-impl ToString for TableName {
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl fmt::Display for TableName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -67,6 +69,10 @@ impl TableSelector {
         self.selected.map(|i| &self.table_list[i])
     }
 
+    pub fn table_name(&self, index: usize) -> Option<&TableName> {
+        self.table_list.get(index)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.table_list.is_empty()
     }
@@ -83,6 +89,24 @@ impl TableSelector {
         Ok(self)
     }
 
+    /// Insert table at the given position and select it
+    pub fn insert_table(&mut self, index: usize, table: TableName) -> Result<&mut Self> {
+        if index > self.table_list.len() {
+            bail!(StrataError::IndexOutOfBounds {
+                max: self.table_list.len(),
+                requested: index,
+            });
+        }
+        if self.table_list.contains(&table) {
+            bail!(StrataError::TableNameDuplicate(table.to_string()));
+        }
+
+        self.table_list.insert(index, table);
+        self.selected = Some(index);
+
+        Ok(self)
+    }
+
     pub fn remove_table(&mut self, remove_index: usize) -> Result<&mut Self> {
         if remove_index > self.table_list.len() {
             bail!(StrataError::IndexOutOfBounds {
@@ -136,6 +160,18 @@ impl TableSelector {
         self
     }
 
+    pub fn select_index(&mut self, index: usize) -> Result<&mut Self> {
+        if index >= self.table_list.len() {
+            bail!(StrataError::IndexOutOfBounds {
+                max: self.table_list.len(),
+                requested: index,
+            });
+        }
+
+        self.selected = Some(index);
+        Ok(self)
+    }
+
     pub fn select_by_name(&mut self, table_name: &TableName) -> Result<&mut Self> {
         self.selected = self.selected_index_by_name(table_name);
 
@@ -147,15 +183,29 @@ impl TableSelector {
     }
 }
 
-impl StrataComponent for TableSelector {
-    fn render(&self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, is_focused: bool) {
+impl TableSelector {
+    /// Render the list with a `*` marker next to the tables for which `is_modified` holds
+    pub fn render_with_modified(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::prelude::Rect,
+        is_focused: bool,
+        is_modified: impl Fn(usize) -> bool,
+    ) {
         let item_style = selectable_item_style_factory(is_focused);
 
         let list_items: Vec<ListItem> = self
             .table_list
             .iter()
             .enumerate()
-            .map(|(i, t)| ListItem::new(t.to_string()).style(item_style(Some(i) == self.selected)))
+            .map(|(i, t)| {
+                let name = if is_modified(i) {
+                    format!("{} *", t)
+                } else {
+                    t.to_string()
+                };
+                ListItem::new(name).style(item_style(Some(i) == self.selected))
+            })
             .collect();
         let list = List::new(list_items)
             .block(Block::bordered().title("List"))
@@ -169,6 +219,12 @@ impl StrataComponent for TableSelector {
     }
 }
 
+impl StrataComponent for TableSelector {
+    fn render(&self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect, is_focused: bool) {
+        self.render_with_modified(frame, area, is_focused, |_| false);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +289,23 @@ mod tests {
         assert_eq!(sl.selected_index(), Some(3));
     }
 
+    #[test]
+    fn test_insert_table() {
+        let mut sl = setup();
+
+        sl.insert_table(1, TableName::from("table3").unwrap())
+            .unwrap();
+        assert_eq!(sl.table_name(1), Some(&TableName::from("table3").unwrap()));
+        assert_eq!(sl.selected_index(), Some(1));
+
+        assert!(sl
+            .insert_table(0, TableName::from("table1").unwrap())
+            .is_err());
+        assert!(sl
+            .insert_table(5, TableName::from("table5").unwrap())
+            .is_err());
+    }
+
     #[test]
     fn test_remove_table() {
         let mut sl = setup();
@@ -1,80 +1,332 @@
-use std::path::Path;
+mod column_type;
+mod dialect;
+mod edit;
+mod filter;
+mod formula;
+mod motion;
+mod number_format;
+mod replace;
+mod search;
+mod selection;
+mod sort;
+mod viewport;
+mod yank;
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
 
 use eyre::{bail, OptionExt, Result};
 use ratatui::{
-    layout::{Constraint, Rect},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Text},
     widgets::{Block, Borders, Cell, Row, Table, TableState},
     Frame,
 };
 
-use crate::error::StrataError;
+use crate::{app::history::History, error::StrataError};
 
 use super::{component_style, selectable_item_style_factory, StrataComponent};
 
+pub use column_type::ColumnType;
+pub use dialect::{parse_ascii_char, CsvDialect, DialectOption, LineTerminator, SNIFF_SIZE};
+pub use edit::{EditRecord, TableEdit};
+pub use filter::{Condition, FilterOp, RowFilter};
+pub use formula::{column_name, is_formula, FormulaError, FormulaGraph, Value};
+pub use motion::Motion;
+pub use number_format::NumberFormat;
+pub use replace::Replacement;
+pub use search::{split_flags, Search};
+pub use selection::{CellRange, Selection, SelectionMode, Transform};
+pub use sort::{natural_cmp, parse_sort_keys, SortKey};
+pub use viewport::{ColumnWidths, Viewport};
+
 pub const INITIAL_TABLE_SIZE: usize = 10;
 
+/// Latest revisions whose changed cells are remembered
+const CHANGE_LOG_LIMIT: usize = 16;
+
+/// Cells whose values changed at a revision, or `None` when it changed more than values
+type ChangedCells = Option<Vec<(usize, usize)>>;
+
 #[derive(Default)]
 #[cfg_attr(test, derive(Clone, Debug, PartialEq))]
 pub struct TableView {
     pub has_header: bool,
+    /// Reject every edit when set
+    pub readonly: bool,
     pub header: Vec<String>,
     pub rows: Vec<Vec<String>>,
+    /// Type of each column, inferred when loaded
+    column_types: Vec<ColumnType>,
+    number_format: NumberFormat,
+    /// Show the numbers as typed instead of formatted, and formulas instead of their values
+    raw_display: bool,
+    formulas: FormulaGraph,
     pub cell_selector: TableState,
+    history: History<EditRecord>,
+    /// Edits made since the table was loaded, less those undone
+    history_position: usize,
+    /// `history_position` when the table was loaded or last saved, unless the edits made
+    /// since undoing past it can no longer lead back to it
+    saved_position: Option<usize>,
+    /// Counts the changes to the values and types of the table, telling when what was
+    /// computed from them is stale
+    revision: u64,
+    /// Cells whose value or computed value changed at each of the latest revisions, oldest
+    /// first
+    changes: VecDeque<(u64, ChangedCells)>,
+    /// File the table was loaded from or last saved to
+    file_path: Option<PathBuf>,
+    /// Dialect the table was loaded with, reused when saving
+    dialect: CsvDialect,
+    /// Rows whose field count differed from the header when loaded, with that field count
+    irregular_rows: BTreeMap<usize, usize>,
+    /// Columns only added to fit the rows longer than the header when loaded, left out of
+    /// the header and regular rows when saved while they stay unnamed and empty
+    padding_cols: BTreeSet<usize>,
+    viewport: std::cell::Cell<Viewport>,
+    column_widths: ColumnWidths,
+    search: Option<Search>,
+    /// Cell selected when the search being previewed started
+    search_origin: Option<(usize, usize)>,
+    filter: Option<RowFilter>,
+    /// Rows matching `filter`, in order
+    filtered_rows: Vec<usize>,
+    /// Visual selection, spanning from its anchor to the selected cell
+    selection: Option<Selection>,
 }
 
 impl TableView {
     pub fn new() -> Self {
         Self {
             has_header: true,
+            readonly: false,
             header: (0..(INITIAL_TABLE_SIZE))
                 .map(|i| format!("header{}", i))
                 .collect(),
             rows: vec![vec!["".to_string(); INITIAL_TABLE_SIZE]; INITIAL_TABLE_SIZE],
+            column_types: vec![ColumnType::Text; INITIAL_TABLE_SIZE],
+            number_format: NumberFormat::default(),
+            raw_display: false,
+            formulas: FormulaGraph::default(),
             cell_selector: TableState::default().with_selected_cell(Some((0, 0))),
+            history: History::new(),
+            history_position: 0,
+            saved_position: Some(0),
+            revision: 0,
+            changes: VecDeque::new(),
+            file_path: None,
+            dialect: CsvDialect::default(),
+            irregular_rows: BTreeMap::new(),
+            padding_cols: BTreeSet::new(),
+            viewport: std::cell::Cell::new(Viewport::default()),
+            column_widths: ColumnWidths::new(INITIAL_TABLE_SIZE),
+            search: None,
+            search_origin: None,
+            filter: None,
+            filtered_rows: Vec::new(),
+            selection: None,
         }
     }
 
+    /// Load the file with a dialect sniffed from its head
     pub fn from_csv(file_path: &Path, has_header: bool) -> Result<Self> {
-        let mut reader = csv::ReaderBuilder::new()
+        Self::from_csv_with_dialect(file_path, has_header, CsvDialect::sniff(file_path)?)
+    }
+
+    pub fn from_csv_with_dialect(
+        file_path: &Path,
+        has_header: bool,
+        dialect: CsvDialect,
+    ) -> Result<Self> {
+        let mut reader = dialect
+            .reader_builder()
             .has_headers(has_header)
             .from_path(file_path)?;
 
-        let header: Vec<String> = if has_header {
+        let mut header: Vec<String> = if has_header {
             reader.headers()?.iter().map(|s| s.to_string()).collect()
         } else {
-            (0..(reader.headers()?.len()))
-                .map(|i| format!("header{}", i))
-                .collect()
+            Vec::new()
         };
+        let expected_len = reader.headers()?.len();
 
         let mut rows = Vec::<Vec<String>>::new();
-        while let Some(record) = reader.records().next() {
-            rows.push(record?.iter().map(|s| s.to_string()).collect());
+        let mut irregular_rows = BTreeMap::new();
+        for record in reader.records() {
+            let record = record.map_err(unequal_lengths_error)?;
+            if record.len() != expected_len {
+                irregular_rows.insert(rows.len(), record.len());
+            }
+            rows.push(record.iter().map(|s| s.to_string()).collect());
+        }
+
+        // pad short rows and widen the header for long rows
+        let width = rows.iter().map(Vec::len).fold(expected_len, usize::max);
+        for row in rows.iter_mut() {
+            row.resize(width, String::new());
         }
+        header.extend((header.len()..width).map(|i| format!("header{}", i)));
 
-        Ok(Self {
+        let mut tv = Self {
             has_header,
+            readonly: false,
+            column_widths: ColumnWidths::new(header.len()),
             header,
             rows,
+            column_types: Vec::new(),
+            number_format: NumberFormat::default(),
+            raw_display: false,
+            formulas: FormulaGraph::default(),
             cell_selector: TableState::default().with_selected_cell(Some((0, 0))),
-        })
+            history: History::new(),
+            history_position: 0,
+            saved_position: Some(0),
+            revision: 0,
+            changes: VecDeque::new(),
+            file_path: Some(file_path.to_path_buf()),
+            dialect,
+            irregular_rows,
+            padding_cols: (expected_len..width).collect(),
+            viewport: std::cell::Cell::new(Viewport::default()),
+            search: None,
+            search_origin: None,
+            filter: None,
+            filtered_rows: Vec::new(),
+            selection: None,
+        };
+        tv.formulas.rebuild(&tv.header, &tv.rows);
+        tv.infer_column_types();
+        Ok(tv)
     }
 
-    pub fn save_csv(&self, file_path: &Path) -> Result<()> {
-        let mut writer = csv::Writer::from_path(file_path)?;
+    /// Save the table with the dialect it was loaded with
+    pub fn save_csv(&mut self, file_path: &Path) -> Result<()> {
+        self.save_csv_with_dialect(file_path, self.dialect.clone())
+    }
+
+    /// Save the table with the given dialect and keep it for the next saves
+    pub fn save_csv_with_dialect(&mut self, file_path: &Path, dialect: CsvDialect) -> Result<()> {
+        self.write_csv(file_path, &dialect, false)?;
+        self.saved_position = Some(self.history_position);
+        self.file_path = Some(file_path.to_path_buf());
+        self.dialect = dialect;
+        Ok(())
+    }
+
+    /// Write the table, with the computed values of formulas when `computed` is set
+    fn write_csv(&self, file_path: &Path, dialect: &CsvDialect, computed: bool) -> Result<()> {
+        let mut writer = dialect.writer_builder().from_path(file_path)?;
+
+        // drop the padding that is still empty to keep the original shape, keeping the
+        // padding columns filled in the regular rows since
+        let has_padding = !self.padding_cols.is_empty() && dialect.flexible;
+        let regular_len = has_padding.then(|| {
+            (0..self.header.len())
+                .rposition(|col| {
+                    !self.padding_cols.contains(&col)
+                        || self.rows.iter().enumerate().any(|(index, row)| {
+                            !self.is_irregular_row(index)
+                                && row.get(col).is_some_and(|value| !value.is_empty())
+                        })
+                })
+                .map_or(0, |col| col + 1)
+        });
 
         if self.has_header {
-            writer.write_record(self.header.iter())?;
+            let end = regular_len.unwrap_or(self.header.len());
+            writer.write_record(self.header[..end].iter())?;
         }
 
-        for row in self.rows.iter() {
-            writer.write_record(row.iter())?;
+        for (index, row) in self.rows.iter().enumerate() {
+            let row: Vec<Cow<str>> = row
+                .iter()
+                .enumerate()
+                .map(|(col, value)| match computed {
+                    true => self.computed_value(index, col),
+                    false => Cow::Borrowed(value.as_str()),
+                })
+                .collect();
+            let len = match self.irregular_rows.get(&index) {
+                Some(&len) if dialect.flexible => Some(len),
+                _ => regular_len,
+            };
+            let end = match len {
+                Some(len) => row
+                    .iter()
+                    .rposition(|cell| !cell.is_empty())
+                    .map_or(0, |i| i + 1)
+                    .max(len)
+                    .min(row.len()),
+                None => row.len(),
+            };
+            writer.write_record(row[..end].iter().map(AsRef::<str>::as_ref))?;
         }
 
         writer.flush()?;
         Ok(())
     }
 
+    /// Save the table to the file it was loaded from or last saved to
+    pub fn save(&mut self) -> Result<()> {
+        let file_path = self.file_path.clone().ok_or_eyre(StrataError::NoFilePath)?;
+        self.save_csv(&file_path)
+    }
+
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file_path.as_deref()
+    }
+
+    pub fn dialect(&self) -> &CsvDialect {
+        &self.dialect
+    }
+
+    pub fn is_irregular_row(&self, row: usize) -> bool {
+        self.irregular_rows.contains_key(&row)
+    }
+
+    pub fn irregular_row_count(&self) -> usize {
+        self.irregular_rows.len()
+    }
+
+    /// Whether the table differs from when it was loaded or last saved
+    pub fn is_modified(&self) -> bool {
+        self.saved_position != Some(self.history_position)
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Cells whose value or computed value changed since the revision, unless it is too
+    /// old to tell or more than values changed since
+    pub fn changed_cells_since(&self, revision: u64) -> ChangedCells {
+        if revision == self.revision {
+            return Some(Vec::new());
+        }
+        let start = self.changes.iter().position(|(r, _)| *r == revision + 1)?;
+        self.changes
+            .range(start..)
+            .try_fold(Vec::new(), |mut cells, (_, changed)| {
+                cells.extend(changed.as_ref()?);
+                Some(cells)
+            })
+    }
+
+    /// Count a new revision along with the cells it changed, if it only changed values
+    fn bump_revision(&mut self, changed: ChangedCells) {
+        self.revision += 1;
+        if self.changes.len() >= CHANGE_LOG_LIMIT {
+            self.changes.pop_front();
+        }
+        self.changes.push_back((self.revision, changed));
+    }
+
     pub fn headers(&self) -> &Vec<String> {
         &self.header
     }
@@ -91,39 +343,36 @@ impl TableView {
     }
 
     pub fn selected_cell_value(&self) -> Result<&str> {
-        let (row, col) = self
-            .selected_index()
-            .ok_or_else(|| StrataError::NoCellSelected)?;
+        let (row, col) = self.selected_index().ok_or(StrataError::NoCellSelected)?;
         self.cell_value(row, col)
     }
 
     pub fn header_widths(&self) -> Vec<Constraint> {
         vec![Constraint::Length(3)]
             .into_iter()
-            .chain(self.header.iter().enumerate().map(|(index, header)| {
-                let max_row_width = self
-                    .rows
-                    .iter()
-                    .map(|row| row.get(index).map(String::len).unwrap_or(0))
-                    .max()
-                    .unwrap_or(0);
-                Constraint::Length(max_row_width.max(header.len()) as u16)
-            }))
+            .chain(
+                (0..self.header.len()).map(|col| Constraint::Length(self.column_width(col) as u16)),
+            )
             .collect()
     }
 
     pub fn switch_headers(&mut self) -> Result<&mut Self> {
-        self.has_header = !self.has_header;
-        match self.has_header {
-            true => {
-                self.rows.remove(0);
-                Ok(self)
-            }
-            false => {
-                self.rows.insert(0, self.header.clone());
-                Ok(self)
-            }
-        }
+        let edit = match self.has_header {
+            true => TableEdit::Batch(vec![
+                TableEdit::SetHasHeader(false),
+                TableEdit::InsertRow {
+                    row: 0,
+                    values: self.header.clone(),
+                },
+            ]),
+            false if self.rows.is_empty() => TableEdit::SetHasHeader(true),
+            false => TableEdit::Batch(vec![
+                TableEdit::RemoveRow { row: 0 },
+                TableEdit::SetHasHeader(true),
+            ]),
+        };
+
+        self.commit(edit)
     }
 
     pub fn move_selector(&mut self, row_move: isize, col_move: isize) -> Result<&mut Self> {
@@ -131,9 +380,11 @@ impl TableView {
             .cell_selector
             .selected_cell()
             .ok_or_eyre(StrataError::NoCellSelected)?;
-        let new_row = selected_row
+        let new_row = self
+            .row_position(selected_row)
             .saturating_add_signed(row_move)
-            .min(self.max_row_index());
+            .min(self.visible_row_count().saturating_sub(1));
+        let new_row = self.row_at(new_row).unwrap_or(selected_row);
         let new_col = selected_col
             .saturating_add_signed(col_move)
             .min(self.max_col_index());
@@ -155,58 +406,284 @@ impl TableView {
         if !self.has_header {
             bail!(StrataError::TableHasNoHeader);
         }
-
-        if let Some(h) = self.header.get_mut(col) {
-            *h = value.to_string();
+        if self.header[col] == value {
+            return Ok(self);
         }
 
-        Ok(self)
+        let edit = TableEdit::UpdateHeader {
+            col,
+            value: value.to_string(),
+        };
+        // a named padding column is saved like the others
+        if self.padding_cols.contains(&col) {
+            let mut padding_cols = self.padding_cols.clone();
+            padding_cols.remove(&col);
+            return self.commit(TableEdit::Batch(vec![
+                edit,
+                TableEdit::SetPaddingCols(padding_cols),
+            ]));
+        }
+        self.commit(edit)
     }
 
     pub fn update_cell(&mut self, row: usize, col: usize, value: &str) -> Result<&mut Self> {
         self.is_valid_row_index(row)?;
         self.is_valid_col_index(col)?;
+        if self.rows[row].get(col).map(String::as_str) == Some(value) {
+            return Ok(self);
+        }
 
-        if let Some(r) = self.rows.get_mut(row) {
-            if let Some(c) = r.get_mut(col) {
-                *c = value.to_string();
-            }
+        self.commit(TableEdit::UpdateCell {
+            row,
+            col,
+            value: value.to_string(),
+        })
+    }
+
+    pub fn expand_row(&mut self) -> Result<&mut Self> {
+        self.commit(TableEdit::InsertRow {
+            row: self.rows.len(),
+            values: vec!["".to_string(); self.header.len()],
+        })
+    }
+
+    /// Insert an empty row before the row at the index, or at the end, and select it
+    pub fn insert_row(&mut self, row: usize) -> Result<&mut Self> {
+        if row != self.rows.len() {
+            self.is_valid_row_index(row)?;
+        }
+
+        self.commit(TableEdit::InsertRow {
+            row,
+            values: vec!["".to_string(); self.header.len()],
+        })?;
+        self.select_row(row);
+        Ok(self)
+    }
+
+    /// Insert a copy of the row below it and select the copy
+    pub fn duplicate_row(&mut self, row: usize) -> Result<&mut Self> {
+        self.is_valid_row_index(row)?;
+
+        // the copy is saved with as many fields as the original
+        let mut irregular_rows: BTreeMap<usize, usize> = self
+            .irregular_rows
+            .iter()
+            .map(|(&r, &len)| (if r > row { r + 1 } else { r }, len))
+            .collect();
+        if let Some(&len) = self.irregular_rows.get(&row) {
+            irregular_rows.insert(row + 1, len);
         }
 
+        self.commit(TableEdit::Batch(vec![
+            TableEdit::InsertRow {
+                row: row + 1,
+                values: self.rows[row].clone(),
+            },
+            TableEdit::SetIrregularRows(irregular_rows),
+        ]))?;
+        self.select_row(row + 1);
         Ok(self)
     }
 
-    pub fn expand_row(&mut self) -> &mut Self {
-        self.rows.push(vec!["".to_string(); self.header.len()]);
-        self
+    /// Move the row by `offset` visible rows and keep it selected
+    pub fn move_row(&mut self, row: usize, offset: isize) -> Result<&mut Self> {
+        self.is_valid_row_index(row)?;
+        let position = self
+            .row_position(row)
+            .saturating_add_signed(offset)
+            .min(self.visible_row_count().saturating_sub(1));
+        let target = self.row_at(position).unwrap_or(row);
+        if target == row {
+            return Ok(self);
+        }
+
+        self.commit(TableEdit::MoveRow {
+            from: row,
+            to: target,
+        })?;
+        self.select_row(target);
+        Ok(self)
     }
 
     pub fn collapse_row(&mut self, row: usize) -> Result<&mut Self> {
         self.is_valid_row_index(row)?;
 
-        self.rows.remove(row);
+        self.commit(TableEdit::RemoveRow { row })
+    }
+
+    /// Remove the rows of the range, undone as one edit
+    pub fn remove_rows(&mut self, rows: RangeInclusive<usize>) -> Result<&mut Self> {
+        self.is_valid_row_index(*rows.start())?;
+        self.is_valid_row_index(*rows.end())?;
+
+        let edits = rows.rev().map(|row| TableEdit::RemoveRow { row }).collect();
+        self.commit(TableEdit::Batch(edits))
+    }
+
+    pub fn expand_col(&mut self) -> Result<&mut Self> {
+        self.append_col(
+            format!("header{}", self.header.len()),
+            vec!["".to_string(); self.rows.len()],
+        )
+    }
+
+    /// Add a column after the last one, with a value for each row
+    fn append_col(&mut self, header: String, values: Vec<String>) -> Result<&mut Self> {
+        self.commit(TableEdit::InsertCol {
+            col: self.header.len(),
+            header,
+            values,
+        })
+    }
+
+    /// Insert an empty column before the column at the index, or at the end, and select it
+    pub fn insert_col(&mut self, col: usize) -> Result<&mut Self> {
+        if col != self.header.len() {
+            self.is_valid_col_index(col)?;
+        }
+
+        self.commit(TableEdit::InsertCol {
+            col,
+            header: format!("header{}", self.header.len()),
+            values: vec!["".to_string(); self.rows.len()],
+        })?;
+        self.select_col(col);
         Ok(self)
     }
 
-    pub fn expand_col(&mut self) -> &mut Self {
-        self.header.push(format!("header{}", self.header.len()));
-        for row in self.rows.iter_mut() {
-            row.push("".to_string());
+    /// Move the column by `offset` columns and keep it selected
+    pub fn move_col(&mut self, col: usize, offset: isize) -> Result<&mut Self> {
+        self.is_valid_col_index(col)?;
+        let target = col.saturating_add_signed(offset).min(self.max_col_index());
+        if target == col {
+            return Ok(self);
         }
-        self
+
+        self.commit(TableEdit::MoveCol {
+            from: col,
+            to: target,
+        })?;
+        self.select_col(target);
+        Ok(self)
     }
 
     pub fn collapse_col(&mut self, col: usize) -> Result<&mut Self> {
         self.is_valid_col_index(col)?;
 
-        self.header.remove(col);
-        for row in self.rows.iter_mut() {
-            row.remove(col);
+        self.commit(TableEdit::RemoveCol { col })
+    }
+
+    /// Forget the original field count of the rows so that every row is saved with the
+    /// full width of the table
+    pub fn normalize(&mut self) -> Result<&mut Self> {
+        if self.irregular_rows.is_empty() && self.padding_cols.is_empty() {
+            return Ok(self);
+        }
+
+        self.commit(TableEdit::Batch(vec![
+            TableEdit::SetIrregularRows(BTreeMap::new()),
+            TableEdit::SetPaddingCols(BTreeSet::new()),
+        ]))
+    }
+
+    /// Revert the last edit and restore the cursor to where it was made
+    pub fn undo(&mut self) -> Result<&mut Self> {
+        let record = self
+            .history
+            .take_undo()
+            .ok_or_eyre(StrataError::NothingToUndo)?;
+        let edit = self.apply_edit(record.edit);
+        let changed = self.refresh_formulas(&edit);
+        self.restore_cursor(record.cursor);
+        self.refresh_filter(&edit);
+        self.history_position -= 1;
+        self.bump_revision(changed);
+        self.history.push_undone(EditRecord {
+            edit,
+            cursor: record.cursor,
+        });
+        Ok(self)
+    }
+
+    /// Re-apply the last undone edit and restore the cursor to where it was made
+    pub fn redo(&mut self) -> Result<&mut Self> {
+        let record = self
+            .history
+            .take_redo()
+            .ok_or_eyre(StrataError::NothingToRedo)?;
+        let edit = self.apply_edit(record.edit);
+        let changed = self.refresh_formulas(&edit);
+        self.restore_cursor(record.cursor);
+        self.refresh_filter(&edit);
+        self.history_position += 1;
+        self.bump_revision(changed);
+        self.history.push_redone(EditRecord {
+            edit,
+            cursor: record.cursor,
+        });
+        Ok(self)
+    }
+
+    /// Apply the edit and record its inverse in the history
+    fn commit(&mut self, edit: TableEdit) -> Result<&mut Self> {
+        if self.readonly {
+            bail!(StrataError::ReadOnlyTable);
         }
 
+        let cursor = self.selected_index();
+        // column edits only roughly follow the original shape of the file, which undoing
+        // them restores exactly
+        let shape = (edit.moves_cols()
+            && !(self.irregular_rows.is_empty() && self.padding_cols.is_empty()))
+        .then(|| (self.irregular_rows.clone(), self.padding_cols.clone()));
+        let mut edit = self.apply_edit_shifting_references(edit);
+        if let Some((irregular_rows, padding_cols)) = shape {
+            edit = TableEdit::Batch(vec![
+                edit,
+                TableEdit::SetIrregularRows(irregular_rows),
+                TableEdit::SetPaddingCols(padding_cols),
+            ]);
+        }
+        let changed = self.refresh_formulas(&edit);
+        self.refresh_filter(&edit);
+        self.history.record(EditRecord { edit, cursor });
+        // the saved table was among the edits undone, which can no longer be redone
+        if self
+            .saved_position
+            .is_some_and(|position| position > self.history_position)
+        {
+            self.saved_position = None;
+        }
+        self.history_position += 1;
+        self.bump_revision(changed);
         Ok(self)
     }
 
+    /// Select the row in the current column, unless the filter hides it
+    fn select_row(&mut self, row: usize) {
+        if let Some((_, col)) = self.selected_index() {
+            if self.is_row_visible(row) {
+                self.cell_selector.select_cell(Some((row, col)));
+            }
+        }
+    }
+
+    fn select_col(&mut self, col: usize) {
+        if let Some((row, _)) = self.selected_index() {
+            self.cell_selector.select_cell(Some((row, col)));
+        }
+    }
+
+    fn restore_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        if let Some((row, col)) = cursor {
+            self.cell_selector.select_cell(Some((
+                row.min(self.max_row_index()),
+                col.min(self.max_col_index()),
+            )));
+        }
+    }
+
     fn max_row_index(&self) -> usize {
         self.rows.len().saturating_sub(1)
     }
@@ -236,42 +713,141 @@ impl TableView {
     }
 }
 
+impl TableView {
+    fn title(&self) -> String {
+        let mut title = "Table".to_string();
+        if self.readonly {
+            title.push_str(" (readonly)");
+        }
+        if !self.irregular_rows.is_empty() {
+            title.push_str(&format!(" ({} irregular rows)", self.irregular_rows.len()));
+        }
+        match self.selection.map(|selection| selection.mode) {
+            Some(SelectionMode::Cells) => title.push_str(" (visual)"),
+            Some(SelectionMode::Rows) => title.push_str(" (visual rows)"),
+            Some(SelectionMode::Columns) => title.push_str(" (visual columns)"),
+            None => {}
+        }
+        if self.raw_display {
+            title.push_str(" (raw)");
+        }
+        if self.filter.is_some() {
+            title.push_str(&format!(
+                " (showing {} of {} rows)",
+                self.visible_row_count(),
+                self.rows.len()
+            ));
+        }
+        title
+    }
+}
+
+/// Replace the csv error on ragged rows with one that tells where the row is
+fn unequal_lengths_error(error: csv::Error) -> eyre::Report {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths {
+            pos,
+            expected_len,
+            len,
+        } => StrataError::UnequalRowLength {
+            line: pos.as_ref().map_or(0, csv::Position::line),
+            expected: *expected_len as usize,
+            found: *len as usize,
+        }
+        .into(),
+        _ => error.into(),
+    }
+}
+
 impl StrataComponent for TableView {
     fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
         let (selected_row, selected_col) = self
             .selected_index()
             .map(|(row, col)| (Some(row), Some(col)))
             .unwrap_or((None, None));
+        let (visible_rows, visible_cols) = self.scroll_to_selection(area);
+        let max_width = area.width.saturating_sub(2);
 
         let cell_style = selectable_item_style_factory(is_focused);
 
-        let header = Row::new(std::iter::once(Cell::from("#")).chain(
-            self.header.iter().enumerate().map(|(col, header)| {
-                Cell::from(header.clone()).style(cell_style(Some(col) == selected_col))
-            }),
-        ))
+        let type_style = Style::default().fg(Color::DarkGray);
+        let header = Row::new(
+            std::iter::once(Cell::from("#")).chain(
+                self.header[visible_cols.clone()]
+                    .iter()
+                    .zip(visible_cols.clone())
+                    .map(|(header, col)| {
+                        let text = Text::from(vec![
+                            Line::from(header.as_str()),
+                            Line::styled(self.column_label(col), type_style),
+                        ]);
+                        let text = if self.is_numeric_column(col) {
+                            text.alignment(Alignment::Right)
+                        } else {
+                            text
+                        };
+                        Cell::from(text).style(cell_style(Some(col) == selected_col))
+                    }),
+            ),
+        )
+        .height(2)
         .bottom_margin(1);
 
-        let body = self.rows.iter().enumerate().map(|(row_index, row)| {
-            Row::new(
-                std::iter::once(
-                    Cell::from(row_index.to_string())
-                        .style(cell_style(Some(row_index) == selected_row)),
+        let body = visible_rows
+            .filter_map(|position| self.row_at(position))
+            .map(|row_index| {
+                let row_style = if self.is_irregular_row(row_index) {
+                    Style::default().fg(Color::LightRed)
+                } else {
+                    Style::default()
+                };
+                Row::new(
+                    std::iter::once(
+                        Cell::from(row_index.to_string())
+                            .style(cell_style(Some(row_index) == selected_row)),
+                    )
+                    .chain(visible_cols.clone().map(|col_index| {
+                        let shown_value = self.shown_value(row_index, col_index);
+                        let is_numeric = self.is_numeric_column(col_index);
+                        let is_selected = Some((row_index, col_index)) == self.selected_index();
+                        let style = if is_selected {
+                            cell_style(true)
+                        } else if self.is_in_selection(row_index, col_index) {
+                            Style::default().fg(Color::Black).bg(Color::Cyan)
+                        } else if self.is_search_match(row_index, col_index) {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else if !self.is_valid_value(row_index, col_index) {
+                            Style::default()
+                                .fg(Color::LightRed)
+                                .add_modifier(Modifier::UNDERLINED)
+                        } else if is_numeric && !self.raw_display && shown_value.starts_with('-') {
+                            Style::default().fg(Color::Red)
+                        } else {
+                            cell_style(false)
+                        };
+                        let line = Line::from(shown_value);
+                        let line = if is_numeric {
+                            line.alignment(Alignment::Right)
+                        } else {
+                            line
+                        };
+                        Cell::from(line).style(style)
+                    })),
                 )
-                .chain(row.iter().enumerate().map(|(col_index, cell_value)| {
-                    Cell::from(cell_value.clone()).style(cell_style(
-                        Some((row_index, col_index)) == self.selected_index(),
-                    ))
-                })),
-            )
-        });
+                .style(row_style)
+            });
 
-        let table = Table::new(body, self.header_widths())
-            .block(Block::default().title("Table").borders(Borders::ALL))
+        let widths = std::iter::once(Constraint::Length(self.index_width() as u16)).chain(
+            visible_cols
+                .clone()
+                .map(|col| Constraint::Length((self.column_width(col) as u16).min(max_width))),
+        );
+        let table = Table::new(body, widths)
+            .block(Block::default().title(self.title()).borders(Borders::ALL))
             .style(component_style(is_focused))
             .header(header);
 
-        frame.render_stateful_widget(table, area, &mut self.cell_selector.clone());
+        frame.render_widget(table, area);
     }
 }
 
@@ -323,8 +899,8 @@ mod tests {
         let mut tv = TableView::new();
         tv.switch_headers().unwrap();
         println!("{:?}", tv.has_header);
-        assert_eq!(tv.has_header, false);
-        assert_eq!(tv.rows.get(0), Some(&tv.header));
+        assert!(!tv.has_header);
+        assert_eq!(tv.rows.first(), Some(&tv.header));
 
         // let tv = tv.switch_headers().unwrap();
         // assert_eq!(tv.has_header, true);
@@ -391,7 +967,7 @@ mod tests {
     #[test]
     fn test_expand_row() {
         let mut tv = TableView::new();
-        tv.expand_row();
+        tv.expand_row().unwrap();
 
         assert_eq!(tv.rows.len(), INITIAL_TABLE_SIZE + 1);
         assert_eq!(tv.rows[INITIAL_TABLE_SIZE].len(), INITIAL_TABLE_SIZE);
@@ -405,10 +981,24 @@ mod tests {
         assert_eq!(tv.rows.len(), INITIAL_TABLE_SIZE - 1);
     }
 
+    #[test]
+    fn test_remove_rows() {
+        let mut tv = TableView::new();
+        tv.update_cell(4, 0, "kept").unwrap();
+        tv.remove_rows(1..=3).unwrap();
+        assert_eq!(tv.rows.len(), INITIAL_TABLE_SIZE - 3);
+        assert_eq!(tv.rows[1][0], "kept");
+        assert!(tv.remove_rows(0..=INITIAL_TABLE_SIZE).is_err());
+
+        tv.undo().unwrap();
+        assert_eq!(tv.rows.len(), INITIAL_TABLE_SIZE);
+        assert_eq!(tv.rows[4][0], "kept");
+    }
+
     #[test]
     fn test_expand_col() {
         let mut tv = TableView::new();
-        tv.expand_col();
+        tv.expand_col().unwrap();
 
         assert_eq!(tv.header.len(), INITIAL_TABLE_SIZE + 1);
         assert_eq!(tv.rows[0].len(), INITIAL_TABLE_SIZE + 1);
@@ -422,4 +1012,397 @@ mod tests {
         assert_eq!(tv.header.len(), INITIAL_TABLE_SIZE - 1);
         assert_eq!(tv.rows[0].len(), INITIAL_TABLE_SIZE - 1);
     }
+
+    #[test]
+    fn test_insert_row() {
+        let mut tv = TableView::new();
+        tv.update_cell(1, 0, "second").unwrap();
+        tv.select_cell(1, 2).unwrap();
+        tv.insert_row(1).unwrap();
+        assert_eq!(tv.rows.len(), INITIAL_TABLE_SIZE + 1);
+        assert_eq!(tv.rows[1], vec![""; INITIAL_TABLE_SIZE]);
+        assert_eq!(tv.rows[2][0], "second");
+        assert_eq!(tv.selected_index(), Some((1, 2)));
+
+        tv.insert_row(tv.rows.len()).unwrap();
+        assert_eq!(tv.selected_index(), Some((INITIAL_TABLE_SIZE + 1, 2)));
+        assert!(tv.insert_row(tv.rows.len() + 1).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_row() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+        let irregular_rows = tv.irregular_rows.clone();
+        tv.select_cell(1, 0).unwrap();
+        tv.duplicate_row(1).unwrap();
+        assert_eq!(tv.rows[2], tv.rows[1]);
+        assert_eq!(tv.selected_index(), Some((2, 0)));
+        assert_eq!(tv.irregular_rows.get(&2), irregular_rows.get(&1));
+
+        tv.undo().unwrap();
+        assert_eq!(tv.irregular_rows, irregular_rows);
+    }
+
+    #[test]
+    fn test_move_row() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 0, "first").unwrap();
+        tv.move_row(0, 1).unwrap();
+        assert_eq!(tv.rows[1][0], "first");
+        assert_eq!(tv.selected_index(), Some((1, 0)));
+        tv.move_row(1, -5).unwrap();
+        assert_eq!(tv.rows[0][0], "first");
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+
+        // hidden rows are skipped
+        tv.update_cell(3, 1, "shown").unwrap();
+        tv.update_cell(0, 1, "shown").unwrap();
+        tv.set_filter(Some(RowFilter::parse("1 !empty").unwrap()))
+            .unwrap();
+        tv.move_row(0, 1).unwrap();
+        assert_eq!(tv.rows[3][0], "first");
+        assert_eq!(tv.selected_index(), Some((3, 0)));
+
+        // the original field counts move with the rows
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+        tv.move_row(1, 1).unwrap();
+        assert_eq!(tv.rows[2][0], "orange");
+        assert_eq!(tv.irregular_rows, BTreeMap::from([(1, 3), (2, 1)]));
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[1][0], "orange");
+        assert_eq!(tv.irregular_rows, BTreeMap::from([(1, 1), (2, 3)]));
+    }
+
+    #[test]
+    fn test_insert_and_move_col() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 1, "value").unwrap();
+        tv.insert_col(1).unwrap();
+        assert_eq!(tv.header[1], "header10");
+        assert_eq!(tv.rows[0][2], "value");
+        assert_eq!(tv.selected_index(), Some((0, 1)));
+
+        tv.move_col(2, -2).unwrap();
+        assert_eq!(tv.header[0], "header1");
+        assert_eq!(tv.rows[0][0], "value");
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+
+        tv.undo().unwrap();
+        assert_eq!(tv.header[2], "header1");
+        assert_eq!(tv.rows[0][2], "value");
+    }
+
+    #[test]
+    fn test_readonly() {
+        let mut tv = TableView::new();
+        tv.readonly = true;
+
+        assert!(tv.update_cell(0, 0, "value").is_err());
+        assert!(tv.expand_row().is_err());
+        assert!(tv.collapse_col(0).is_err());
+        assert_eq!(tv.rows, TableView::new().rows);
+    }
+
+    #[test]
+    fn test_from_csv_with_dialect() {
+        let tv = TableView::from_csv_with_dialect(
+            Path::new("tests/data/fluits.tsv"),
+            true,
+            CsvDialect::with_delimiter(b'\t'),
+        )
+        .unwrap();
+        assert_eq!(*tv.headers(), vec!["fluits", "price"]);
+        assert_eq!(tv.rows[1][1], "200");
+
+        // sniffed
+        let tv = TableView::from_csv(Path::new("tests/data/fluits.tsv"), true).unwrap();
+        assert_eq!(tv.dialect().delimiter, b'\t');
+        assert_eq!(tv.rows[1][1], "200");
+    }
+
+    #[test]
+    fn test_from_csv_ragged() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+        assert_eq!(*tv.headers(), vec!["name", "price", "header2"]);
+        assert_eq!(tv.rows[0], vec!["apple", "100", ""]);
+        assert_eq!(tv.rows[1], vec!["orange", "", ""]);
+        assert_eq!(tv.rows[2], vec!["grape", "150", "sweet"]);
+        assert!(!tv.is_irregular_row(0));
+        assert!(tv.is_irregular_row(1));
+        assert!(tv.is_irregular_row(2));
+
+        // row indices follow inserted and removed rows
+        tv.collapse_row(0).unwrap();
+        assert!(tv.is_irregular_row(0));
+        tv.undo().unwrap();
+        assert!(tv.is_irregular_row(1));
+
+        let strict = CsvDialect {
+            flexible: false,
+            ..tv.dialect().clone()
+        };
+        let err =
+            TableView::from_csv_with_dialect(Path::new("tests/data/ragged.csv"), true, strict)
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            StrataError::UnequalRowLength {
+                line: 3,
+                expected: 2,
+                found: 1
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_save_ragged() {
+        let path = std::env::temp_dir().join("strata_test_save_ragged.csv");
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+
+        tv.save_csv(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price\napple,100\norange\ngrape,150,sweet\n"
+        );
+
+        // the widened columns are kept once a regular row fills them
+        tv.update_cell(0, 2, "red").unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price,header2\napple,100,red\norange\ngrape,150,sweet\n"
+        );
+        tv.undo().unwrap();
+
+        tv.normalize().unwrap();
+        assert_eq!(tv.irregular_row_count(), 0);
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price,header2\napple,100,\norange,,\ngrape,150,sweet\n"
+        );
+
+        tv.undo().unwrap();
+        assert_eq!(tv.irregular_row_count(), 2);
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price\napple,100\norange\ngrape,150,sweet\n"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_save_ragged_after_column_edits() {
+        let path = std::env::temp_dir().join("strata_test_save_ragged_cols.csv");
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+        tv.save_csv(&path).unwrap();
+
+        // the padding and the field counts follow the columns
+        tv.insert_col(0).unwrap();
+        tv.update_header(0, "id").unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,name,price\n,apple,100\n,orange\n,grape,150,sweet\n"
+        );
+        tv.move_col(3, -2).unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,header2,name,price\n,,apple,100\n,,orange\n,sweet,grape,150\n"
+        );
+
+        // undoing restores the original shape
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        tv.collapse_col(2).unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price\napple,100\norange\ngrape,150\n"
+        );
+        tv.undo().unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price\napple,100\norange\ngrape,150,sweet\n"
+        );
+
+        // a named padding column is saved
+        tv.update_header(2, "taste").unwrap();
+        tv.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,price,taste\napple,100,\norange\ngrape,150,sweet\n"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_save_round_trips_dialect() {
+        let path = std::env::temp_dir().join("strata_test_round_trip.csv");
+        let content = "name;note\r\napple;\"a \\\"red\\\" one\"\r\n";
+        std::fs::write(&path, content).unwrap();
+
+        let mut tv = TableView::from_csv(&path, true).unwrap();
+        assert_eq!(tv.rows[0][1], "a \"red\" one");
+        tv.update_cell(0, 0, "banana").unwrap();
+        tv.update_cell(0, 0, "apple").unwrap();
+        tv.save().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        tv.save_csv_with_dialect(&path, CsvDialect::default())
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "name,note\napple,\"a \"\"red\"\" one\"\n"
+        );
+        assert_eq!(*tv.dialect(), CsvDialect::default());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_modified() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        assert!(!tv.is_modified());
+
+        tv.update_cell(0, 0, "apple").unwrap();
+        assert!(!tv.is_modified());
+
+        tv.update_cell(0, 0, "banana").unwrap();
+        assert!(tv.is_modified());
+
+        let path = std::env::temp_dir().join("strata_test_modified.csv");
+        tv.save_csv(&path).unwrap();
+        assert!(!tv.is_modified());
+
+        tv.undo().unwrap();
+        assert!(tv.is_modified());
+        tv.redo().unwrap();
+        assert!(!tv.is_modified());
+
+        // undoing every edit goes back to the loaded table, which was not saved
+        tv.undo().unwrap();
+        assert!(tv.is_modified());
+
+        // the saved table can no longer be reached once another edit is made
+        tv.update_cell(0, 0, "cherry").unwrap();
+        tv.undo().unwrap();
+        tv.update_cell(0, 0, "banana").unwrap();
+        assert!(tv.is_modified());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_save() {
+        let mut tv = TableView::new();
+        assert!(tv.file_path().is_none());
+        assert!(tv.save().is_err());
+
+        let path = std::env::temp_dir().join("strata_test_save.csv");
+        tv.save_csv(&path).unwrap();
+        assert_eq!(tv.file_path(), Some(path.as_path()));
+
+        tv.update_cell(0, 0, "saved").unwrap();
+        tv.save().unwrap();
+        let tv = TableView::from_csv(&path, true).unwrap();
+        assert_eq!(tv.rows[0][0], "saved");
+        assert_eq!(tv.file_path(), Some(path.as_path()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_render_visible_window() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut tv = TableView::new();
+        for row in 0..INITIAL_TABLE_SIZE {
+            tv.collapse_row(INITIAL_TABLE_SIZE - 1 - row).unwrap();
+        }
+        let mut terminal = Terminal::new(TestBackend::new(40, 15)).unwrap();
+        terminal.draw(|f| tv.render(f, f.area(), true)).unwrap();
+
+        for _ in 0..1000 {
+            tv.expand_row().unwrap();
+        }
+        tv.update_cell(500, 9, "target").unwrap();
+        tv.select_cell(500, 9).unwrap();
+        terminal.draw(|f| tv.render(f, f.area(), true)).unwrap();
+
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("header9"));
+        assert!(content.contains("500"));
+        assert!(content.contains("target"));
+        assert!(!content.contains("header0"));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut tv = TableView::new();
+        let initial = tv.clone();
+
+        tv.update_cell(1, 1, "value").unwrap();
+        tv.select_cell(3, 3).unwrap();
+        tv.collapse_col(0).unwrap();
+        tv.expand_row().unwrap();
+        tv.update_header(0, "header").unwrap();
+        let edited = tv.clone();
+
+        tv.undo().unwrap().undo().unwrap().undo().unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 3)));
+        assert_eq!(tv.rows[1][1], "value");
+        tv.undo().unwrap();
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+        assert_eq!(tv.rows, initial.rows);
+        assert_eq!(tv.header, initial.header);
+        assert!(tv.undo().is_err());
+
+        tv.redo()
+            .unwrap()
+            .redo()
+            .unwrap()
+            .redo()
+            .unwrap()
+            .redo()
+            .unwrap();
+        assert_eq!(tv.rows, edited.rows);
+        assert_eq!(tv.header, edited.header);
+        assert!(tv.redo().is_err());
+    }
+
+    #[test]
+    fn test_undo_switch_headers() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        let initial = tv.clone();
+
+        tv.switch_headers().unwrap();
+        assert_eq!(tv.rows.len(), 4);
+        tv.undo().unwrap();
+        assert!(tv.has_header);
+        assert_eq!(tv.rows, initial.rows);
+
+        tv.redo().unwrap();
+        assert!(!tv.has_header);
+        assert_eq!(tv.rows[0], initial.header);
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 0, "first").unwrap();
+        tv.undo().unwrap();
+        tv.update_cell(0, 0, "second").unwrap();
+
+        assert!(tv.redo().is_err());
+        assert_eq!(tv.rows[0][0], "second");
+    }
 }
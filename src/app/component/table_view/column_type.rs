@@ -0,0 +1,407 @@
+use std::{borrow::Cow, collections::BTreeSet, fmt};
+
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+use super::{is_formula, TableView};
+
+/// Number of non-empty values looked at to infer the type of a column
+const INFER_SAMPLE_SIZE: usize = 1000;
+
+/// Most distinct values a column can have to be inferred as an enum
+const ENUM_MAX_VARIANTS: usize = 8;
+
+/// Kind of values a column holds
+///
+/// Empty cells are valid in every column.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ColumnType {
+    #[default]
+    Text,
+    Integer,
+    Decimal,
+    /// `true`/`false` or `yes`/`no`, in any case
+    Boolean,
+    /// `YYYY-MM-DD`
+    Date,
+    /// `YYYY-MM-DD` followed by a time such as `12:30`, `12:30:15.250` or `12:30:15+09:00`
+    DateTime,
+    /// One of a few values, sorted
+    Enum(Vec<String>),
+}
+
+impl ColumnType {
+    /// Types tried in order when inferring, from the strictest
+    const INFERRED: [ColumnType; 5] = [
+        ColumnType::Boolean,
+        ColumnType::Integer,
+        ColumnType::Decimal,
+        ColumnType::Date,
+        ColumnType::DateTime,
+    ];
+
+    /// Strictest type accepting a sample of the non-empty values
+    ///
+    /// Values that repeat a handful of distinct values make an enum, anything else that no
+    /// type accepts is text.
+    pub fn infer<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        let sample: Vec<&str> = values
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .take(INFER_SAMPLE_SIZE)
+            .collect();
+        if sample.is_empty() {
+            return ColumnType::Text;
+        }
+        if let Some(column_type) = Self::INFERRED
+            .into_iter()
+            .find(|column_type| sample.iter().all(|value| column_type.accepts(value)))
+        {
+            return column_type;
+        }
+
+        let variants: BTreeSet<&str> = sample.iter().copied().collect();
+        if variants.len() <= ENUM_MAX_VARIANTS && sample.len() >= variants.len() * 2 {
+            return ColumnType::Enum(variants.into_iter().map(str::to_string).collect());
+        }
+        ColumnType::Text
+    }
+
+    /// Parse a type written as its name, e.g. `integer`, or `enum small,medium,large`
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (name, variants) = input.split_once(' ').unwrap_or((input, ""));
+        let column_type = match name {
+            "text" => ColumnType::Text,
+            "integer" => ColumnType::Integer,
+            "decimal" => ColumnType::Decimal,
+            "boolean" => ColumnType::Boolean,
+            "date" => ColumnType::Date,
+            "datetime" => ColumnType::DateTime,
+            "enum" => {
+                let variants: BTreeSet<String> = variants
+                    .split(',')
+                    .map(|variant| variant.trim().to_string())
+                    .filter(|variant| !variant.is_empty())
+                    .collect();
+                if variants.is_empty() {
+                    bail!(StrataError::StringParseError(input.to_string()));
+                }
+                return Ok(ColumnType::Enum(variants.into_iter().collect()));
+            }
+            _ => bail!(StrataError::StringParseError(input.to_string())),
+        };
+        if !variants.is_empty() {
+            bail!(StrataError::StringParseError(input.to_string()));
+        }
+        Ok(column_type)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Integer => "integer",
+            ColumnType::Decimal => "decimal",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+            ColumnType::DateTime => "datetime",
+            ColumnType::Enum(_) => "enum",
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        if value.is_empty() {
+            return true;
+        }
+        match self {
+            ColumnType::Text => true,
+            ColumnType::Integer => is_integer(value),
+            ColumnType::Decimal => is_decimal(value),
+            ColumnType::Boolean => ["true", "false", "yes", "no"]
+                .iter()
+                .any(|b| value.eq_ignore_ascii_case(b)),
+            ColumnType::Date => is_date(value),
+            ColumnType::DateTime => is_datetime(value),
+            ColumnType::Enum(variants) => variants.iter().any(|variant| variant == value),
+        }
+    }
+}
+
+/// Written as it is parsed, e.g. `enum small,medium,large`
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Enum(variants) => write!(f, "enum {}", variants.join(",")),
+            column_type => write!(f, "{}", column_type.name()),
+        }
+    }
+}
+
+fn is_digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_integer(value: &str) -> bool {
+    is_digits(value.strip_prefix(['+', '-']).unwrap_or(value))
+}
+
+/// Plain or scientific notation, but not `inf` or `NaN`
+fn is_decimal(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b))
+        && value.parse::<f64>().is_ok()
+}
+
+fn is_date(value: &str) -> bool {
+    let mut parts = value.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    if ![year, month, day].into_iter().all(is_digits) {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+fn is_datetime(value: &str) -> bool {
+    if value.len() < 10 || !value.is_char_boundary(10) || !is_date(&value[..10]) {
+        return false;
+    }
+    let time = match value[10..].strip_prefix(['T', ' ']) {
+        Some(time) => time,
+        None => return value.len() == 10,
+    };
+
+    // offset from UTC
+    let time = match time.strip_suffix('Z') {
+        Some(time) => time,
+        None => match time.rfind(['+', '-']) {
+            Some(sign) if is_clock(&time[sign + 1..], false) => &time[..sign],
+            _ => time,
+        },
+    };
+    is_clock(time, true)
+}
+
+/// `HH:MM`, followed by `:SS` and a fraction of second when `with_seconds` is set
+fn is_clock(time: &str, with_seconds: bool) -> bool {
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) if with_seconds => (time, Some(fraction)),
+        Some(_) => return false,
+        None => (time, None),
+    };
+    let parts: Vec<&str> = time.split(':').collect();
+    let limits: &[u32] = match (parts.len(), with_seconds) {
+        (2, _) if fraction.is_none() => &[23, 59],
+        (3, true) => &[23, 59, 60],
+        _ => return false,
+    };
+    parts.iter().zip(limits).all(|(part, &limit)| {
+        part.len() == 2 && is_digits(part) && part.parse::<u32>().is_ok_and(|n| n <= limit)
+    }) && fraction.is_none_or(is_digits)
+}
+
+impl TableView {
+    pub fn column_type(&self, col: usize) -> &ColumnType {
+        self.column_types.get(col).unwrap_or(&ColumnType::Text)
+    }
+
+    /// Set the type of the column, or infer it again from its values with `None`
+    ///
+    /// The type is not saved with the table, so this is not an edit.
+    pub fn set_column_type(
+        &mut self,
+        col: usize,
+        column_type: Option<ColumnType>,
+    ) -> Result<&mut Self> {
+        self.is_valid_col_index(col)?;
+
+        let column_type = column_type.unwrap_or_else(|| self.infer_column_type(col));
+        self.column_types[col] = column_type;
+        self.column_widths.invalidate(col);
+        self.bump_revision(None);
+        Ok(self)
+    }
+
+    /// Fail when the value does not match the type of the column, or when it is a formula
+    /// that cannot be computed
+    pub fn check_value(&self, row: usize, col: usize, value: &str) -> Result<()> {
+        if is_formula(value) {
+            return self.check_formula(row, col, value);
+        }
+        let column_type = self.column_type(col);
+        if !column_type.accepts(value) {
+            bail!(StrataError::InvalidCellValue {
+                expected: column_type.to_string(),
+                value: value.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the value of the cell, or the result of its formula, matches the type of
+    /// the column
+    pub fn is_valid_value(&self, row: usize, col: usize) -> bool {
+        match self.formula_result(row, col) {
+            Some(Ok(value)) => self.column_type(col).accepts(&value.to_string()),
+            Some(Err(_)) => false,
+            None => self
+                .rows
+                .get(row)
+                .and_then(|values| values.get(col))
+                .is_none_or(|value| self.column_type(col).accepts(value)),
+        }
+    }
+
+    /// Infer the type of the column from its values, taking formulas by their result
+    pub(super) fn infer_column_type(&self, col: usize) -> ColumnType {
+        let values: Vec<Cow<str>> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(row, values)| match self.formula_result(row, col) {
+                Some(Ok(value)) => Cow::Owned(value.to_string()),
+                Some(Err(_)) => Cow::Borrowed(""),
+                None => Cow::Borrowed(values.get(col).map_or("", String::as_str)),
+            })
+            .filter(|value| !value.is_empty())
+            .take(INFER_SAMPLE_SIZE)
+            .collect();
+        ColumnType::infer(values.iter().map(AsRef::as_ref))
+    }
+
+    /// Infer the type of every column from its values
+    pub(super) fn infer_column_types(&mut self) {
+        self.column_types = (0..self.header.len())
+            .map(|col| self.infer_column_type(col))
+            .collect();
+        self.column_widths.invalidate_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        let cases = [
+            (
+                ColumnType::Integer,
+                &["12", "-3", "+0"][..],
+                &["1.5", "-", "1e3"][..],
+            ),
+            (
+                ColumnType::Decimal,
+                &["1.5", "-.5", "1e-3", "7"],
+                &["inf", "NaN", "1,5"],
+            ),
+            (ColumnType::Boolean, &["true", "No", "FALSE"], &["1", "y"]),
+            (
+                ColumnType::Date,
+                &["2024-02-29", "1999-12-31"],
+                &["2023-02-29", "2024-13-01", "24-01-01", "2024-1-01"],
+            ),
+            (
+                ColumnType::DateTime,
+                &[
+                    "2024-01-31 23:59",
+                    "2024-01-31T08:00:00",
+                    "2024-01-31T08:00:00.125Z",
+                    "2024-01-31T08:00:00-05:00",
+                    "2024-01-31",
+                ],
+                &["2024-01-31 24:00", "2024-01-31T08", "2024-01-31T08:00.5"],
+            ),
+        ];
+        for (column_type, valid, invalid) in cases {
+            for value in valid {
+                assert!(column_type.accepts(value), "{} {}", column_type, value);
+            }
+            for value in invalid {
+                assert!(!column_type.accepts(value), "{} {}", column_type, value);
+            }
+            assert!(column_type.accepts(""));
+        }
+    }
+
+    #[test]
+    fn test_infer() {
+        assert_eq!(ColumnType::infer(["1", "", "20"]), ColumnType::Integer);
+        assert_eq!(ColumnType::infer(["1", "2.5"]), ColumnType::Decimal);
+        assert_eq!(ColumnType::infer(["yes", "no"]), ColumnType::Boolean);
+        assert_eq!(
+            ColumnType::infer(["2024-01-01", "2024-01-01 10:00"]),
+            ColumnType::DateTime
+        );
+        assert_eq!(
+            ColumnType::infer(["s", "m", "s", "l", "m", "s"]),
+            ColumnType::Enum(vec!["l".into(), "m".into(), "s".into()])
+        );
+        assert_eq!(ColumnType::infer(["apple", "orange"]), ColumnType::Text);
+        assert_eq!(ColumnType::infer(["", ""]), ColumnType::Text);
+    }
+
+    #[test]
+    fn test_parse() {
+        for input in ["text", "integer", "datetime", "enum a,b"] {
+            assert_eq!(ColumnType::parse(input).unwrap().to_string(), input);
+        }
+        assert_eq!(
+            ColumnType::parse("enum b, a,,b").unwrap(),
+            ColumnType::Enum(vec!["a".into(), "b".into()])
+        );
+        assert!(ColumnType::parse("enum").is_err());
+        assert!(ColumnType::parse("integer 3").is_err());
+        assert!(ColumnType::parse("number").is_err());
+    }
+
+    #[test]
+    fn test_column_types() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        assert_eq!(tv.column_type(0), &ColumnType::Text);
+        assert_eq!(tv.column_type(1), &ColumnType::Integer);
+        assert!(tv.check_value(0, 1, "120").is_ok());
+        assert!(tv.check_value(0, 1, "abc").is_err());
+        assert!(tv.check_value(0, 1, "=1+2").is_ok());
+
+        // existing values are kept but marked
+        tv.update_cell(0, 1, "abc").unwrap();
+        assert!(!tv.is_valid_value(0, 1));
+        tv.set_column_type(1, None).unwrap();
+        assert_eq!(tv.column_type(1), &ColumnType::Text);
+
+        tv.set_column_type(1, Some(ColumnType::Decimal)).unwrap();
+        tv.expand_col().unwrap();
+        tv.collapse_col(0).unwrap();
+        assert_eq!(tv.column_type(0), &ColumnType::Decimal);
+        tv.undo().unwrap();
+        assert_eq!(tv.column_type(0), &ColumnType::Text);
+
+        // the header becomes a row
+        tv.switch_headers().unwrap();
+        assert_eq!(tv.column_type(1), &ColumnType::Text);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{fs::File, io::Read, path::Path};
+
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+/// Number of bytes read from the head of a file to guess its dialect
+pub const SNIFF_SIZE: usize = 8 * 1024;
+
+/// Delimiters tried when sniffing, in order of preference
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineTerminator {
+    #[default]
+    Lf,
+    CrLf,
+    Other(u8),
+}
+
+/// How fields and records are laid out in a CSV file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    /// Escape character for quotes inside quoted fields, used when `double_quote` is unset
+    pub escape: Option<u8>,
+    /// Whether quotes inside quoted fields are escaped by doubling them
+    pub double_quote: bool,
+    /// Lines starting with this character are skipped when reading
+    pub comment: Option<u8>,
+    pub terminator: LineTerminator,
+    /// Whether records may have different numbers of fields
+    pub flexible: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            double_quote: true,
+            comment: None,
+            terminator: LineTerminator::Lf,
+            flexible: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    pub fn with_delimiter(delimiter: u8) -> Self {
+        Self {
+            delimiter,
+            ..Self::default()
+        }
+    }
+
+    /// Guess the dialect from the first `SNIFF_SIZE` bytes of the file
+    pub fn sniff(file_path: &Path) -> Result<Self> {
+        let mut sample = Vec::with_capacity(SNIFF_SIZE);
+        File::open(file_path)?
+            .take(SNIFF_SIZE as u64)
+            .read_to_end(&mut sample)?;
+        Ok(Self::sniff_bytes(&sample))
+    }
+
+    /// Guess the delimiter, quote escaping and line terminator from a sample
+    ///
+    /// The delimiter is the candidate that occurs the same number of times on most lines.
+    /// Comments are never guessed, and the sniffed dialect is flexible so that ragged rows
+    /// are loaded rather than rejected.
+    pub fn sniff_bytes(sample: &[u8]) -> Self {
+        let mut dialect = Self {
+            flexible: true,
+            ..Self::default()
+        };
+        let mut line_counts: Vec<[usize; DELIMITER_CANDIDATES.len()]> = Vec::new();
+        let mut counts = [0; DELIMITER_CANDIDATES.len()];
+        let mut line_is_empty = true;
+        let mut in_quotes = false;
+
+        let mut i = 0;
+        while i < sample.len() {
+            let byte = sample[i];
+            let next = sample.get(i + 1).copied();
+            if in_quotes {
+                if byte == b'\\' && next == Some(dialect.quote) {
+                    dialect.escape = Some(b'\\');
+                    dialect.double_quote = false;
+                    i += 1;
+                } else if byte == dialect.quote && next == Some(dialect.quote) {
+                    i += 1;
+                } else if byte == dialect.quote {
+                    in_quotes = false;
+                }
+            } else if byte == dialect.quote {
+                in_quotes = true;
+                line_is_empty = false;
+            } else if byte == b'\n' || byte == b'\r' {
+                if byte == b'\r' && next == Some(b'\n') {
+                    dialect.terminator = LineTerminator::CrLf;
+                    i += 1;
+                }
+                if !line_is_empty {
+                    line_counts.push(counts);
+                }
+                counts = [0; DELIMITER_CANDIDATES.len()];
+                line_is_empty = true;
+            } else {
+                if let Some(c) = DELIMITER_CANDIDATES.iter().position(|&d| d == byte) {
+                    counts[c] += 1;
+                }
+                line_is_empty = false;
+            }
+            i += 1;
+        }
+        // the last line is likely cut in the middle when the sample is full
+        if !line_is_empty && (sample.len() < SNIFF_SIZE || line_counts.is_empty()) {
+            line_counts.push(counts);
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        for (c, &delimiter) in DELIMITER_CANDIDATES.iter().enumerate() {
+            let Some((count, frequency)) = mode(line_counts.iter().map(|counts| counts[c])) else {
+                continue;
+            };
+            if count == 0 {
+                continue;
+            }
+            if best.is_none_or(|best| (frequency, count) > best) {
+                best = Some((frequency, count));
+                dialect.delimiter = delimiter;
+            }
+        }
+
+        dialect
+    }
+
+    pub fn reader_builder(&self) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .flexible(self.flexible)
+            .terminator(match self.terminator {
+                LineTerminator::Lf | LineTerminator::CrLf => csv::Terminator::CRLF,
+                LineTerminator::Other(byte) => csv::Terminator::Any(byte),
+            });
+        builder
+    }
+
+    pub fn writer_builder(&self) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape.unwrap_or(b'\\'))
+            .double_quote(self.double_quote)
+            .flexible(self.flexible)
+            .terminator(match self.terminator {
+                LineTerminator::Lf => csv::Terminator::Any(b'\n'),
+                LineTerminator::CrLf => csv::Terminator::CRLF,
+                LineTerminator::Other(byte) => csv::Terminator::Any(byte),
+            });
+        builder
+    }
+}
+
+/// Setting of the dialect changed when saving, e.g. `delimiter=;`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialectOption {
+    Delimiter(u8),
+    Quote(u8),
+    /// Escape character for quotes, or `None` to double them
+    Escape(Option<u8>),
+    Terminator(LineTerminator),
+}
+
+impl DialectOption {
+    /// Names of the settings, written before `=`
+    pub const NAMES: [&'static str; 4] = ["delimiter", "quote", "escape", "terminator"];
+
+    /// Parse `delimiter=;`, `quote='`, `escape=\`, `escape=none`, `terminator=lf` or
+    /// `terminator=crlf`
+    pub fn parse(setting: &str) -> Result<Self> {
+        let parse_error = || StrataError::StringParseError(setting.to_string());
+        let (name, value) = setting.split_once('=').ok_or_else(parse_error)?;
+        let byte = || parse_ascii_char(value).map_err(|_| parse_error());
+        Ok(match (name, value) {
+            ("delimiter", _) => DialectOption::Delimiter(byte()?),
+            ("quote", _) => DialectOption::Quote(byte()?),
+            ("escape", "none") => DialectOption::Escape(None),
+            ("escape", _) => DialectOption::Escape(Some(byte()?)),
+            ("terminator", "lf") => DialectOption::Terminator(LineTerminator::Lf),
+            ("terminator", "crlf") => DialectOption::Terminator(LineTerminator::CrLf),
+            _ => bail!(parse_error()),
+        })
+    }
+}
+
+impl CsvDialect {
+    pub fn apply(&mut self, option: DialectOption) {
+        match option {
+            DialectOption::Delimiter(delimiter) => self.delimiter = delimiter,
+            DialectOption::Quote(quote) => self.quote = quote,
+            DialectOption::Escape(escape) => {
+                self.escape = escape;
+                self.double_quote = escape.is_none();
+            }
+            DialectOption::Terminator(terminator) => self.terminator = terminator,
+        }
+    }
+}
+
+/// Parse a single ASCII character, or `\t` and `tab` for a tab
+pub fn parse_ascii_char(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("must be a single ASCII character: {}", s)),
+    }
+}
+
+/// Most frequent value and its frequency, preferring the larger value on ties
+fn mode(values: impl Iterator<Item = usize>) -> Option<(usize, usize)> {
+    let mut values: Vec<usize> = values.collect();
+    values.sort_unstable();
+    values
+        .chunk_by(|a, b| a == b)
+        .map(|chunk| (chunk[0], chunk.len()))
+        .max_by_key(|&(value, frequency)| (frequency, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiter() {
+        assert_eq!(CsvDialect::sniff_bytes(b"a,b\n1,2\n").delimiter, b',');
+        assert_eq!(CsvDialect::sniff_bytes(b"a\tb\n1\t2\n").delimiter, b'\t');
+        assert_eq!(
+            CsvDialect::sniff_bytes(b"name;price\n\"1,5\";2\nx;3\n").delimiter,
+            b';'
+        );
+        assert_eq!(CsvDialect::sniff_bytes(b"a|b|c\n1|2|3\n").delimiter, b'|');
+        assert_eq!(CsvDialect::sniff_bytes(b"single\n").delimiter, b',');
+    }
+
+    #[test]
+    fn test_sniff_quote_and_terminator() {
+        let dialect = CsvDialect::sniff_bytes(b"a,b\r\n\"say \\\"hi\\\"\",2\r\n");
+        assert_eq!(dialect.terminator, LineTerminator::CrLf);
+        assert_eq!(dialect.escape, Some(b'\\'));
+        assert!(!dialect.double_quote);
+
+        let dialect = CsvDialect::sniff_bytes(b"a,b\n\"say \"\"hi\"\"\",2\n");
+        assert_eq!(dialect.terminator, LineTerminator::Lf);
+        assert_eq!(dialect.escape, None);
+        assert!(dialect.double_quote);
+    }
+
+    #[test]
+    fn test_sniff_file() {
+        let dialect = CsvDialect::sniff(Path::new("tests/data/fluits.tsv")).unwrap();
+        assert_eq!(
+            dialect,
+            CsvDialect {
+                flexible: true,
+                ..CsvDialect::with_delimiter(b'\t')
+            }
+        );
+    }
+
+    #[test]
+    fn test_dialect_options() {
+        let mut dialect = CsvDialect::default();
+        for setting in ["delimiter=;", "quote='", "escape=\\", "terminator=crlf"] {
+            dialect.apply(DialectOption::parse(setting).unwrap());
+        }
+        assert_eq!(dialect.delimiter, b';');
+        assert_eq!(dialect.quote, b'\'');
+        assert_eq!(dialect.escape, Some(b'\\'));
+        assert!(!dialect.double_quote);
+        assert_eq!(dialect.terminator, LineTerminator::CrLf);
+
+        dialect.apply(DialectOption::parse("escape=none").unwrap());
+        assert!(dialect.double_quote);
+        assert_eq!(
+            DialectOption::parse("delimiter=\\t").unwrap(),
+            DialectOption::Delimiter(b'\t')
+        );
+        for setting in ["delimiter", "delimiter=ab", "terminator=cr", "comment=#"] {
+            assert!(DialectOption::parse(setting).is_err(), "{}", setting);
+        }
+    }
+}
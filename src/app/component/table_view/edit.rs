@@ -0,0 +1,305 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use super::{is_formula, TableView};
+
+/// Reversible mutation of a `TableView`
+///
+/// Indices are assumed to be valid: edits are only built by `TableView` methods after
+/// validation, or returned as inverses of edits that have just been applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableEdit {
+    UpdateCell {
+        row: usize,
+        col: usize,
+        value: String,
+    },
+    UpdateHeader {
+        col: usize,
+        value: String,
+    },
+    InsertRow {
+        row: usize,
+        values: Vec<String>,
+    },
+    RemoveRow {
+        row: usize,
+    },
+    MoveRow {
+        from: usize,
+        to: usize,
+    },
+    InsertCol {
+        col: usize,
+        header: String,
+        values: Vec<String>,
+    },
+    RemoveCol {
+        col: usize,
+    },
+    MoveCol {
+        from: usize,
+        to: usize,
+    },
+    /// Put the rows in this order: the new row `i` is the old row `order[i]`
+    ReorderRows(Vec<usize>),
+    SetHasHeader(bool),
+    /// Replace the original field counts of irregular rows
+    SetIrregularRows(BTreeMap<usize, usize>),
+    /// Replace the columns only added to fit the longer rows when loaded
+    SetPaddingCols(BTreeSet<usize>),
+    /// Several edits applied in order and reverted as one
+    Batch(Vec<TableEdit>),
+}
+
+impl TableEdit {
+    /// Whether the edit moves cells to other rows or columns
+    fn moves_cells(&self) -> bool {
+        matches!(
+            self,
+            TableEdit::InsertRow { .. }
+                | TableEdit::RemoveRow { .. }
+                | TableEdit::MoveRow { .. }
+                | TableEdit::InsertCol { .. }
+                | TableEdit::RemoveCol { .. }
+                | TableEdit::MoveCol { .. }
+                | TableEdit::ReorderRows(_)
+        )
+    }
+
+    /// Whether the edit inserts, removes or moves columns
+    pub(super) fn moves_cols(&self) -> bool {
+        match self {
+            TableEdit::InsertCol { .. }
+            | TableEdit::RemoveCol { .. }
+            | TableEdit::MoveCol { .. } => true,
+            TableEdit::Batch(edits) => edits.iter().any(TableEdit::moves_cols),
+            _ => false,
+        }
+    }
+}
+
+/// Entry of the undo/redo history
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditRecord {
+    pub edit: TableEdit,
+    /// Selected cell when the edit was made
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl TableView {
+    /// Apply the edit and return the edit that reverts it
+    pub(super) fn apply_edit(&mut self, edit: TableEdit) -> TableEdit {
+        // the anchor of the visual selection would no longer be on the same cell
+        if edit.moves_cells() {
+            self.selection = None;
+        }
+        match edit {
+            TableEdit::UpdateCell { row, col, value } => {
+                let old = std::mem::replace(&mut self.rows[row][col], value);
+                self.column_widths.update(
+                    col,
+                    self.cell_width(col, &old),
+                    self.cell_width(col, &self.rows[row][col]),
+                );
+                TableEdit::UpdateCell {
+                    row,
+                    col,
+                    value: old,
+                }
+            }
+            TableEdit::UpdateHeader { col, value } => {
+                let old = std::mem::replace(&mut self.header[col], value);
+                self.column_widths
+                    .update(col, old.width(), self.header[col].width());
+                TableEdit::UpdateHeader { col, value: old }
+            }
+            TableEdit::InsertRow { row, values } => {
+                for (col, value) in values.iter().enumerate() {
+                    self.column_widths.grow(col, self.cell_width(col, value));
+                }
+                self.rows.insert(row, values);
+                self.shift_irregular_rows(row, |r| r + 1);
+                TableEdit::RemoveRow { row }
+            }
+            TableEdit::RemoveRow { row } => {
+                let values = self.rows.remove(row);
+                for (col, value) in values.iter().enumerate() {
+                    self.column_widths.shrink(col, self.cell_width(col, value));
+                }
+                self.irregular_rows.remove(&row);
+                self.shift_irregular_rows(row, |r| r - 1);
+                TableEdit::InsertRow { row, values }
+            }
+            TableEdit::MoveRow { from, to } => {
+                let values = self.rows.remove(from);
+                self.rows.insert(to, values);
+                let len = self.irregular_rows.remove(&from);
+                self.shift_irregular_rows(from.min(to), |r| match r {
+                    r if from < r && r <= to => r - 1,
+                    r if to <= r && r < from => r + 1,
+                    r => r,
+                });
+                if let Some(len) = len {
+                    self.irregular_rows.insert(to, len);
+                }
+                TableEdit::MoveRow { from: to, to: from }
+            }
+            TableEdit::InsertCol {
+                col,
+                header,
+                values,
+            } => {
+                self.header.insert(col, header);
+                self.column_widths.insert_col(col);
+                for (row, value) in self.rows.iter_mut().zip(values) {
+                    row.insert(col.min(row.len()), value);
+                }
+                // the type of a column of formulas is inferred from their results
+                if self
+                    .rows
+                    .iter()
+                    .any(|row| row.get(col).is_some_and(|v| is_formula(v)))
+                {
+                    self.formulas.rebuild(&self.header, &self.rows);
+                }
+                let column_type = self.infer_column_type(col);
+                self.column_types
+                    .insert(col.min(self.column_types.len()), column_type);
+                let shift = |c| Some(if c >= col { c + 1 } else { c });
+                self.shift_filter_cols(shift);
+                self.shift_file_cols(shift);
+                TableEdit::RemoveCol { col }
+            }
+            TableEdit::RemoveCol { col } => {
+                let header = self.header.remove(col);
+                self.column_widths.remove_col(col);
+                if col < self.column_types.len() {
+                    self.column_types.remove(col);
+                }
+                let values = self
+                    .rows
+                    .iter_mut()
+                    .map(|row| {
+                        if col < row.len() {
+                            row.remove(col)
+                        } else {
+                            String::new()
+                        }
+                    })
+                    .collect();
+                let shift = |c: usize| match c.cmp(&col) {
+                    Ordering::Less => Some(c),
+                    Ordering::Equal => None,
+                    Ordering::Greater => Some(c - 1),
+                };
+                self.shift_filter_cols(shift);
+                self.shift_file_cols(shift);
+                TableEdit::InsertCol {
+                    col,
+                    header,
+                    values,
+                }
+            }
+            TableEdit::MoveCol { from, to } => {
+                let header = self.header.remove(from);
+                self.header.insert(to, header);
+                self.column_widths.remove_col(from);
+                self.column_widths.insert_col(to);
+                if from < self.column_types.len() && to < self.column_types.len() {
+                    let column_type = self.column_types.remove(from);
+                    self.column_types.insert(to, column_type);
+                }
+                for row in &mut self.rows {
+                    if from < row.len() && to < row.len() {
+                        let value = row.remove(from);
+                        row.insert(to, value);
+                    }
+                }
+                let shift = |c| {
+                    Some(match c {
+                        c if c == from => to,
+                        c if from < c && c <= to => c - 1,
+                        c if to <= c && c < from => c + 1,
+                        c => c,
+                    })
+                };
+                self.shift_filter_cols(shift);
+                self.shift_file_cols(shift);
+                TableEdit::MoveCol { from: to, to: from }
+            }
+            TableEdit::ReorderRows(order) => {
+                let mut rows = std::mem::take(&mut self.rows);
+                self.rows = order
+                    .iter()
+                    .map(|&i| std::mem::take(&mut rows[i]))
+                    .collect();
+
+                let irregular_rows = std::mem::take(&mut self.irregular_rows);
+                let mut inverse = vec![0; order.len()];
+                for (new, &old) in order.iter().enumerate() {
+                    inverse[old] = new;
+                    if let Some(&len) = irregular_rows.get(&old) {
+                        self.irregular_rows.insert(new, len);
+                    }
+                }
+                TableEdit::ReorderRows(inverse)
+            }
+            TableEdit::SetHasHeader(has_header) => {
+                let old = std::mem::replace(&mut self.has_header, has_header);
+                TableEdit::SetHasHeader(old)
+            }
+            TableEdit::SetIrregularRows(irregular_rows) => {
+                let old = std::mem::replace(&mut self.irregular_rows, irregular_rows);
+                TableEdit::SetIrregularRows(old)
+            }
+            TableEdit::SetPaddingCols(padding_cols) => {
+                let old = std::mem::replace(&mut self.padding_cols, padding_cols);
+                TableEdit::SetPaddingCols(old)
+            }
+            TableEdit::Batch(edits) => self.apply_batch(edits, Self::apply_edit),
+        }
+    }
+
+    /// Apply the edits in order with `apply` and return the edit that reverts them all
+    pub(super) fn apply_batch(
+        &mut self,
+        edits: Vec<TableEdit>,
+        mut apply: impl FnMut(&mut Self, TableEdit) -> TableEdit,
+    ) -> TableEdit {
+        // the header row moves to or from the rows along with the flag
+        let moves_header = edits
+            .iter()
+            .any(|e| matches!(e, TableEdit::SetHasHeader(_)));
+        let mut inverses: Vec<TableEdit> = edits.into_iter().map(|e| apply(self, e)).collect();
+        inverses.reverse();
+        if moves_header {
+            self.infer_column_types();
+        }
+        TableEdit::Batch(inverses)
+    }
+
+    /// Follow a column moved, inserted or removed by an edit in the padding columns and in
+    /// the original field counts of the irregular rows, which then reach the last of their
+    /// fields
+    fn shift_file_cols(&mut self, shift: impl Fn(usize) -> Option<usize>) {
+        self.padding_cols = std::mem::take(&mut self.padding_cols)
+            .into_iter()
+            .filter_map(&shift)
+            .collect();
+        for len in self.irregular_rows.values_mut() {
+            *len = (0..*len).filter_map(&shift).max().map_or(0, |col| col + 1);
+        }
+    }
+
+    /// Move the irregular rows at or after `from` to their new index
+    fn shift_irregular_rows(&mut self, from: usize, shift: impl Fn(usize) -> usize) {
+        let shifted = self.irregular_rows.split_off(&from);
+        self.irregular_rows
+            .extend(shifted.into_iter().map(|(row, len)| (shift(row), len)));
+    }
+}
@@ -0,0 +1,478 @@
+use eyre::{bail, Result};
+use regex::Regex;
+
+use crate::error::StrataError;
+
+use super::{TableEdit, TableView};
+
+/// Test applied to the value of a column
+#[derive(Debug, Clone)]
+pub enum FilterOp {
+    Equals(String),
+    NotEquals(String),
+    Contains(String),
+    Regex(Regex),
+    LessThan(f64),
+    LessOrEqual(f64),
+    GreaterThan(f64),
+    GreaterOrEqual(f64),
+    Empty,
+    NotEmpty,
+}
+
+impl FilterOp {
+    /// Numeric comparisons only match values that parse as numbers
+    pub fn matches(&self, value: &str) -> bool {
+        let number = || value.trim().parse::<f64>().ok();
+        match self {
+            FilterOp::Equals(expected) => value == expected,
+            FilterOp::NotEquals(expected) => value != expected,
+            FilterOp::Contains(expected) => value.contains(expected.as_str()),
+            FilterOp::Regex(regex) => regex.is_match(value),
+            FilterOp::LessThan(n) => number().is_some_and(|v| v < *n),
+            FilterOp::LessOrEqual(n) => number().is_some_and(|v| v <= *n),
+            FilterOp::GreaterThan(n) => number().is_some_and(|v| v > *n),
+            FilterOp::GreaterOrEqual(n) => number().is_some_and(|v| v >= *n),
+            FilterOp::Empty => value.is_empty(),
+            FilterOp::NotEmpty => !value.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub col: usize,
+    pub op: FilterOp,
+}
+
+/// Conditions a row must all meet to be shown
+#[derive(Debug, Clone)]
+pub struct RowFilter {
+    query: String,
+    conditions: Vec<Condition>,
+}
+
+impl PartialEq for RowFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+    }
+}
+
+/// Operators in the order they are tried, so that `!=` is not read as `!` and `=`
+const OPERATORS: [&str; 10] = [
+    "!empty", "empty", "=~", "!=", "<=", ">=", "=", "<", ">", "~",
+];
+
+impl RowFilter {
+    /// Parse conditions separated by `;`, each made of a column index, an operator and a
+    /// value, e.g. `1 >= 100; 0 ~ apple`
+    ///
+    /// Operators: `=`, `!=`, `~` (contains), `=~` (regex), `<`, `<=`, `>`, `>=`, and
+    /// `empty` / `!empty` which take no value.
+    pub fn parse(query: &str) -> Result<Self> {
+        let conditions = query
+            .split(';')
+            .map(|condition| {
+                let parse_error = || StrataError::StringParseError(condition.trim().to_string());
+                let condition = condition.trim();
+                let digits = condition
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(condition.len());
+                let col = condition[..digits]
+                    .parse::<usize>()
+                    .map_err(|_| parse_error())?;
+                let rest = condition[digits..].trim_start();
+                let operator = OPERATORS
+                    .iter()
+                    .find(|operator| rest.starts_with(*operator))
+                    .ok_or_else(parse_error)?;
+                let value = rest[operator.len()..].trim();
+                let number = || value.parse::<f64>().map_err(|_| parse_error());
+
+                let op = match *operator {
+                    "empty" | "!empty" if !value.is_empty() => bail!(parse_error()),
+                    "empty" => FilterOp::Empty,
+                    "!empty" => FilterOp::NotEmpty,
+                    "=" => FilterOp::Equals(value.to_string()),
+                    "!=" => FilterOp::NotEquals(value.to_string()),
+                    "~" => FilterOp::Contains(value.to_string()),
+                    "=~" => FilterOp::Regex(
+                        Regex::new(value)
+                            .map_err(|e| StrataError::InvalidSearchPattern(e.to_string()))?,
+                    ),
+                    "<" => FilterOp::LessThan(number()?),
+                    "<=" => FilterOp::LessOrEqual(number()?),
+                    ">" => FilterOp::GreaterThan(number()?),
+                    _ => FilterOp::GreaterOrEqual(number()?),
+                };
+                Ok(Condition { col, op })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            query: query.trim().to_string(),
+            conditions,
+        })
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// Move the conditions to the new index of their column, rewriting the query, or
+    /// return `false` when a column is gone
+    fn shift_cols(&mut self, shift: impl Fn(usize) -> Option<usize>) -> bool {
+        for condition in &mut self.conditions {
+            match shift(condition.col) {
+                Some(col) => condition.col = col,
+                None => return false,
+            }
+        }
+        self.query = self
+            .query
+            .split(';')
+            .zip(&self.conditions)
+            .map(|(text, condition)| {
+                let text = text.trim();
+                let digits = text
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(text.len());
+                format!("{}{}", condition.col, &text[digits..])
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        true
+    }
+
+    pub fn matches(&self, row: &[String]) -> bool {
+        self.conditions.iter().all(|condition| {
+            condition
+                .op
+                .matches(row.get(condition.col).map_or("", String::as_str))
+        })
+    }
+}
+
+impl TableView {
+    pub fn filter(&self) -> Option<&RowFilter> {
+        self.filter.as_ref()
+    }
+
+    /// Hide the rows that do not match the filter, or show every row again with `None`
+    ///
+    /// Rows are only hidden: edits and saving still apply to every row.
+    pub fn set_filter(&mut self, filter: Option<RowFilter>) -> Result<&mut Self> {
+        if let Some(filter) = &filter {
+            for condition in filter.conditions() {
+                self.is_valid_col_index(condition.col)?;
+            }
+        }
+        self.filter = filter;
+        self.rescan_filter();
+        Ok(self)
+    }
+
+    /// Number of rows shown
+    pub fn visible_row_count(&self) -> usize {
+        match self.filter {
+            Some(_) => self.filtered_rows.len(),
+            None => self.rows.len(),
+        }
+    }
+
+    pub fn is_row_visible(&self, row: usize) -> bool {
+        match self.filter {
+            Some(_) => self.filtered_rows.binary_search(&row).is_ok(),
+            None => row < self.rows.len(),
+        }
+    }
+
+    /// Row shown at the position among the visible rows
+    pub fn row_at(&self, position: usize) -> Option<usize> {
+        match self.filter {
+            Some(_) => self.filtered_rows.get(position).copied(),
+            None => (position < self.rows.len()).then_some(position),
+        }
+    }
+
+    /// Position of the row among the visible rows, or of the next visible row when it is
+    /// hidden
+    pub fn row_position(&self, row: usize) -> usize {
+        match self.filter {
+            Some(_) => self.filtered_rows.partition_point(|&r| r < row),
+            None => row,
+        }
+    }
+
+    /// Follow a column moved, inserted or removed by an edit, dropping the filter when one
+    /// of its columns is removed
+    pub(super) fn shift_filter_cols(&mut self, shift: impl Fn(usize) -> Option<usize>) {
+        if let Some(filter) = &mut self.filter {
+            if !filter.shift_cols(shift) {
+                self.filter = None;
+            }
+        }
+    }
+
+    /// Match the rows touched by the edit that has just been applied against the filter
+    ///
+    /// Cell updates only test their rows again. Other edits move rows and columns around,
+    /// so every row is tested again.
+    pub(super) fn refresh_filter(&mut self, edit: &TableEdit) {
+        let rows: Option<Vec<usize>> = match edit {
+            TableEdit::UpdateCell { row, .. } => Some(vec![*row]),
+            TableEdit::Batch(edits) => edits
+                .iter()
+                .map(|edit| match edit {
+                    TableEdit::UpdateCell { row, .. } => Some(*row),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+
+        match (&self.filter, rows) {
+            (Some(filter), Some(rows)) => {
+                for row in rows {
+                    let matches = filter.matches(&self.rows[row]);
+                    match self.filtered_rows.binary_search(&row) {
+                        Ok(index) if !matches => {
+                            self.filtered_rows.remove(index);
+                        }
+                        Err(index) if matches => self.filtered_rows.insert(index, row),
+                        _ => {}
+                    }
+                }
+                self.keep_selection_visible();
+            }
+            _ => self.rescan_filter(),
+        }
+    }
+
+    /// Match every row against the filter again
+    ///
+    /// A filter on a column that no longer exists is dropped. When the selected row gets
+    /// hidden, the selection moves to the nearest visible row.
+    fn rescan_filter(&mut self) {
+        match &self.filter {
+            Some(filter)
+                if filter
+                    .conditions()
+                    .iter()
+                    .all(|condition| condition.col < self.header.len()) =>
+            {
+                self.filtered_rows = self
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row)| filter.matches(row))
+                    .map(|(index, _)| index)
+                    .collect();
+            }
+            _ => {
+                self.filter = None;
+                self.filtered_rows.clear();
+            }
+        }
+        self.keep_selection_visible();
+    }
+
+    /// Move the selection off a hidden row, or leave no row selected when every row is
+    /// hidden
+    ///
+    /// The selected column is kept so that the first row shown again gets selected in it.
+    fn keep_selection_visible(&mut self) {
+        match self.selected_index() {
+            Some((row, col)) if !self.is_row_visible(row) => {
+                let position = self.row_position(row);
+                let row = self
+                    .row_at(position)
+                    .or_else(|| self.row_at(position.checked_sub(1)?));
+                match row {
+                    Some(row) => self.cell_selector.select_cell(Some((row, col))),
+                    None => self.cell_selector.select(None),
+                }
+            }
+            Some(_) => {}
+            None => {
+                if let (Some(row), Some(col)) =
+                    (self.row_at(0), self.cell_selector.selected_column())
+                {
+                    self.cell_selector.select_cell(Some((row, col)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::app::component::table_view::CellRange;
+
+    fn setup() -> TableView {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        tv.expand_row().unwrap();
+        tv.update_cell(3, 0, "melon").unwrap();
+        tv
+    }
+
+    #[test]
+    fn test_parse() {
+        let filter = RowFilter::parse("1 >= 150; 0 ~ ap").unwrap();
+        assert_eq!(filter.conditions().len(), 2);
+        assert!(filter.matches(&["grape".to_string(), "150".to_string()]));
+        assert!(!filter.matches(&["apple".to_string(), "100".to_string()]));
+
+        let filter = RowFilter::parse("0 =~ ^(apple|grape)$;1 !empty").unwrap();
+        assert!(filter.matches(&["apple".to_string(), "1".to_string()]));
+        assert!(!filter.matches(&["apple".to_string(), "".to_string()]));
+        assert!(!filter.matches(&["pineapple".to_string(), "1".to_string()]));
+
+        let filter = RowFilter::parse("1 != 100").unwrap();
+        assert!(filter.matches(&["apple".to_string()]));
+
+        assert!(RowFilter::parse("").is_err());
+        assert!(RowFilter::parse("a = 1").is_err());
+        assert!(RowFilter::parse("1 < abc").is_err());
+        assert!(RowFilter::parse("1 empty x").is_err());
+        assert!(RowFilter::parse("1 =~ (").is_err());
+        assert!(RowFilter::parse("1 ? x").is_err());
+    }
+
+    #[test]
+    fn test_set_filter() {
+        let mut tv = setup();
+        tv.select_cell(1, 1).unwrap();
+        tv.set_filter(Some(RowFilter::parse("1 > 120").unwrap()))
+            .unwrap();
+        assert_eq!(tv.visible_row_count(), 2);
+        assert_eq!(tv.row_at(0), Some(1));
+        assert_eq!(tv.row_at(1), Some(2));
+        assert!(!tv.is_row_visible(0));
+        assert!(!tv.is_row_visible(3));
+
+        // the selection moves between the visible rows only
+        tv.move_selector(1, 0).unwrap();
+        assert_eq!(tv.selected_index(), Some((2, 1)));
+        tv.move_selector(1, 0).unwrap();
+        assert_eq!(tv.selected_index(), Some((2, 1)));
+        tv.move_selector(-5, 0).unwrap();
+        assert_eq!(tv.selected_index(), Some((1, 1)));
+
+        assert!(tv
+            .set_filter(Some(RowFilter::parse("2 empty").unwrap()))
+            .is_err());
+
+        tv.set_filter(None).unwrap();
+        assert_eq!(tv.visible_row_count(), 4);
+        assert!(tv.is_row_visible(0));
+    }
+
+    #[test]
+    fn test_edit_while_filtered() {
+        let mut tv = setup();
+        tv.set_filter(Some(RowFilter::parse("0 ~ a").unwrap()))
+            .unwrap();
+        assert_eq!(tv.visible_row_count(), 3);
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+
+        // edits land on the real row
+        tv.move_selector(2, 1).unwrap();
+        let (row, col) = tv.selected_index().unwrap();
+        assert_eq!((row, col), (2, 1));
+        tv.update_cell(row, col, "300").unwrap();
+        assert_eq!(tv.rows[2], vec!["grape", "300"]);
+
+        // an edited row that no longer matches is hidden and the selection moves on
+        tv.update_cell(2, 0, "kiwi").unwrap();
+        assert_eq!(tv.visible_row_count(), 2);
+        assert_eq!(tv.selected_index(), Some((1, 1)));
+
+        tv.undo().unwrap();
+        assert!(tv.is_row_visible(2));
+
+        // rows updated together are tested again together
+        tv.transform_range(&CellRange::new((0, 0), (3, 0)), |value| {
+            value.replace("ape", "ip")
+        })
+        .unwrap();
+        assert_eq!(tv.filtered_rows, vec![0, 1]);
+        tv.undo().unwrap();
+        assert_eq!(tv.filtered_rows, vec![0, 1, 2]);
+
+        // every row is still saved
+        let path = std::env::temp_dir().join("strata_test_edit_while_filtered.csv");
+        tv.save_csv(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved,
+            "fluits,price\napple,100\norange,200\ngrape,300\nmelon,\n"
+        );
+    }
+
+    #[test]
+    fn test_filter_dropped_with_its_column() {
+        let mut tv = setup();
+        tv.set_filter(Some(RowFilter::parse("1 > 120").unwrap()))
+            .unwrap();
+        tv.collapse_col(1).unwrap();
+        assert!(tv.filter().is_none());
+        assert_eq!(tv.visible_row_count(), 4);
+    }
+
+    #[test]
+    fn test_filter_without_match() {
+        let mut tv = setup();
+        tv.select_cell(2, 1).unwrap();
+        tv.set_filter(Some(RowFilter::parse("0 = kiwi").unwrap()))
+            .unwrap();
+        assert_eq!(tv.visible_row_count(), 0);
+        assert_eq!(tv.selected_index(), None);
+        assert!(tv.move_selector(1, 0).is_err());
+        assert!(tv.selected_cell_value().is_err());
+
+        tv.set_filter(Some(RowFilter::parse("0 ~ e").unwrap()))
+            .unwrap();
+        assert_eq!(tv.selected_index(), Some((0, 1)));
+        tv.set_filter(Some(RowFilter::parse("0 = kiwi").unwrap()))
+            .unwrap();
+        tv.set_filter(None).unwrap();
+        assert_eq!(tv.selected_index(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_filter_follows_its_columns() {
+        let mut tv = setup();
+        tv.set_filter(Some(RowFilter::parse("1 > 120;0 ~ a").unwrap()))
+            .unwrap();
+        assert_eq!(tv.visible_row_count(), 2);
+
+        tv.insert_col(0).unwrap();
+        assert_eq!(tv.filter().unwrap().query(), "2 > 120; 1 ~ a");
+        assert_eq!(tv.visible_row_count(), 2);
+        tv.undo().unwrap();
+        assert_eq!(tv.filter().unwrap().query(), "1 > 120; 0 ~ a");
+        tv.redo().unwrap();
+
+        tv.move_col(2, -2).unwrap();
+        assert_eq!(tv.filter().unwrap().query(), "0 > 120; 2 ~ a");
+        assert_eq!(tv.visible_row_count(), 2);
+
+        tv.collapse_col(1).unwrap();
+        assert_eq!(tv.filter().unwrap().query(), "0 > 120; 1 ~ a");
+        assert_eq!(tv.row_at(0), Some(1));
+        assert_eq!(tv.visible_row_count(), 2);
+
+        // removing a filtered column drops the filter, even when others remain
+        tv.collapse_col(0).unwrap();
+        assert!(tv.filter().is_none());
+        assert_eq!(tv.visible_row_count(), 4);
+    }
+}
@@ -0,0 +1,769 @@
+mod derive;
+mod expr;
+mod shift;
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, HashSet},
+    path::Path,
+};
+
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+use super::{TableEdit, TableView};
+
+pub use expr::{column_name, FormulaError, Value};
+use expr::{shift_references, CellSource, Expr};
+use shift::Shift;
+
+/// Whether the value of a cell is a formula, e.g. `=B2*C2`
+pub fn is_formula(value: &str) -> bool {
+    value.len() > 1 && value.starts_with('=')
+}
+
+/// Cells a formula reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Dependency {
+    Cell(usize, usize),
+    /// Every cell of the column
+    Column(usize),
+    /// Cells from the top left corner to the bottom right one, kept whole as they may be
+    /// countless
+    Range((usize, usize), (usize, usize)),
+}
+
+/// Top left and bottom right corners of a range
+type Corners = ((usize, usize), (usize, usize));
+
+/// Whether the cell is within the range from the top left corner to the bottom right one
+fn in_range(cell: (usize, usize), start: (usize, usize), end: (usize, usize)) -> bool {
+    (start.0..=end.0).contains(&cell.0) && (start.1..=end.1).contains(&cell.1)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Formula {
+    expr: Result<Expr, FormulaError>,
+    dependencies: Vec<Dependency>,
+}
+
+impl Formula {
+    fn parse(value: &str, row: usize, header: &[String]) -> Self {
+        let expr = Expr::parse(&value[1..], header);
+        let mut dependencies = Vec::new();
+        if let Ok(expr) = &expr {
+            expr.visit_references(false, &mut |reference, in_list| match reference {
+                Expr::Cell { row, col } => dependencies.push(Dependency::Cell(*row, *col)),
+                Expr::Range { start, end } => dependencies.push(Dependency::Range(*start, *end)),
+                Expr::Column(col) if in_list => dependencies.push(Dependency::Column(*col)),
+                Expr::Column(col) => dependencies.push(Dependency::Cell(row, *col)),
+                _ => {}
+            });
+        }
+        Self { expr, dependencies }
+    }
+}
+
+/// Formulas of a table with their computed values
+///
+/// Each formula knows the cells it reads, and each cell the formulas reading it, so that
+/// changing a cell only computes again the formulas depending on it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FormulaGraph {
+    formulas: HashMap<(usize, usize), Formula>,
+    values: HashMap<(usize, usize), Result<Value, FormulaError>>,
+    /// Formulas reading each cell
+    cell_dependents: HashMap<(usize, usize), HashSet<(usize, usize)>>,
+    /// Formulas reading every cell of each column
+    column_dependents: HashMap<usize, HashSet<(usize, usize)>>,
+    /// Formulas reading each range, by its corners
+    range_dependents: HashMap<Corners, HashSet<(usize, usize)>>,
+}
+
+impl FormulaGraph {
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+
+    /// Result of the formula of the cell, or `None` when it holds no formula
+    pub fn value(&self, row: usize, col: usize) -> Option<&Result<Value, FormulaError>> {
+        self.values.get(&(row, col))
+    }
+
+    /// Parse every formula of the table and compute it
+    pub fn rebuild(&mut self, header: &[String], rows: &[Vec<String>]) {
+        *self = Self::default();
+        for (row, values) in rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if is_formula(value) {
+                    self.insert((row, col), Formula::parse(value, row, header));
+                }
+            }
+        }
+        self.evaluate(rows, self.formulas.keys().copied().collect());
+    }
+
+    /// Take the new values of the cells into account and return the formulas computed
+    /// again, along with the cells that no longer hold a formula
+    pub fn update_cells(
+        &mut self,
+        header: &[String],
+        rows: &[Vec<String>],
+        cells: &[(usize, usize)],
+    ) -> BTreeSet<(usize, usize)> {
+        let mut changed = BTreeSet::new();
+        for &(row, col) in cells {
+            if self.remove((row, col)) {
+                changed.insert((row, col));
+            }
+            let value = rows.get(row).and_then(|values| values.get(col));
+            if let Some(value) = value.filter(|value| is_formula(value)) {
+                self.insert((row, col), Formula::parse(value, row, header));
+                changed.insert((row, col));
+            }
+        }
+
+        let dependents = self.dependents(cells);
+        for cell in &dependents {
+            self.values.remove(cell);
+        }
+        self.evaluate(rows, dependents.iter().copied().collect());
+        changed.extend(dependents);
+        changed
+    }
+
+    /// Compute the expression for the row without storing it, reading the values of the
+    /// formulas as they were last computed
+    fn compute(
+        &self,
+        expr: &Expr,
+        rows: &[Vec<String>],
+        row: usize,
+    ) -> Result<Value, FormulaError> {
+        expr.eval(&mut ComputedCells { graph: self, rows }, row)
+    }
+
+    /// Whether a formula at `target` reading these cells would end up reading itself
+    fn reaches(&self, dependencies: &[Dependency], target: (usize, usize)) -> bool {
+        let mut stack = dependencies.to_vec();
+        let mut visited = HashSet::new();
+        while let Some(dependency) = stack.pop() {
+            if !visited.insert(dependency) {
+                continue;
+            }
+            match dependency {
+                Dependency::Cell(row, col) if (row, col) == target => return true,
+                Dependency::Column(col) if col == target.1 => return true,
+                Dependency::Range(start, end) if in_range(target, start, end) => return true,
+                Dependency::Cell(row, col) => {
+                    if let Some(formula) = self.formulas.get(&(row, col)) {
+                        stack.extend(&formula.dependencies);
+                    }
+                }
+                Dependency::Column(col) => {
+                    for (_, formula) in self.formulas.iter().filter(|(cell, _)| cell.1 == col) {
+                        stack.extend(&formula.dependencies);
+                    }
+                }
+                Dependency::Range(start, end) => {
+                    for (_, formula) in self
+                        .formulas
+                        .iter()
+                        .filter(|(&cell, _)| in_range(cell, start, end))
+                    {
+                        stack.extend(&formula.dependencies);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn insert(&mut self, cell: (usize, usize), formula: Formula) {
+        for dependency in &formula.dependencies {
+            let dependents = match dependency {
+                Dependency::Cell(row, col) => self.cell_dependents.entry((*row, *col)).or_default(),
+                Dependency::Column(col) => self.column_dependents.entry(*col).or_default(),
+                Dependency::Range(start, end) => {
+                    self.range_dependents.entry((*start, *end)).or_default()
+                }
+            };
+            dependents.insert(cell);
+        }
+        self.formulas.insert(cell, formula);
+    }
+
+    /// Remove the formula of the cell and tell whether there was one
+    fn remove(&mut self, cell: (usize, usize)) -> bool {
+        let Some(formula) = self.formulas.remove(&cell) else {
+            return false;
+        };
+        for dependency in formula.dependencies {
+            let dependents = match dependency {
+                Dependency::Cell(row, col) => self.cell_dependents.get_mut(&(row, col)),
+                Dependency::Column(col) => self.column_dependents.get_mut(&col),
+                Dependency::Range(start, end) => self.range_dependents.get_mut(&(start, end)),
+            };
+            if let Some(dependents) = dependents {
+                dependents.remove(&cell);
+            }
+        }
+        self.values.remove(&cell);
+        true
+    }
+
+    /// Formulas among the cells and those depending on them, directly or not
+    fn dependents(&self, cells: &[(usize, usize)]) -> BTreeSet<(usize, usize)> {
+        let mut dependents = BTreeSet::new();
+        let mut stack = cells.to_vec();
+        while let Some(cell) = stack.pop() {
+            if self.formulas.contains_key(&cell) && !dependents.insert(cell) {
+                continue;
+            }
+            let ranges = self
+                .range_dependents
+                .iter()
+                .filter(|((start, end), _)| in_range(cell, *start, *end))
+                .map(|(_, readers)| readers);
+            let readers = self
+                .cell_dependents
+                .get(&cell)
+                .into_iter()
+                .chain(self.column_dependents.get(&cell.1))
+                .chain(ranges)
+                .flatten();
+            stack.extend(readers.filter(|reader| !dependents.contains(reader)));
+        }
+        dependents
+    }
+
+    /// Compute the formulas of the cells that have no value yet
+    ///
+    /// The formulas a cell reads are computed before it, so that computing a formula never
+    /// nests into another one however long the chain.
+    fn evaluate(&mut self, rows: &[Vec<String>], mut cells: Vec<(usize, usize)>) {
+        let mut formula_rows: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(row, col) in self.formulas.keys() {
+            formula_rows.entry(col).or_default().push(row);
+        }
+        formula_rows
+            .values_mut()
+            .for_each(|rows| rows.sort_unstable());
+
+        cells.sort_unstable();
+        let mut evaluator = Evaluator {
+            formulas: &self.formulas,
+            values: &mut self.values,
+            rows,
+        };
+        // cells are computed once the formulas they read are, except for a formula met
+        // again before that, which is in a cycle
+        let mut entered = HashSet::new();
+        for cell in cells {
+            let mut stack = vec![(cell, false)];
+            while let Some((cell, ready)) = stack.pop() {
+                if ready {
+                    evaluator.evaluate(cell);
+                    continue;
+                }
+                if evaluator.values.contains_key(&cell) || !entered.insert(cell) {
+                    continue;
+                }
+                let Some(formula) = evaluator.formulas.get(&cell) else {
+                    continue;
+                };
+                stack.push((cell, true));
+                for dependency in &formula.dependencies {
+                    match *dependency {
+                        Dependency::Cell(row, col) => stack.push(((row, col), false)),
+                        Dependency::Column(col) => {
+                            let rows = formula_rows.get(&col).into_iter().flatten();
+                            stack.extend(rows.map(|&row| ((row, col), false)));
+                        }
+                        Dependency::Range(start, end) => {
+                            for (&col, rows) in formula_rows
+                                .iter()
+                                .filter(|(&col, _)| (start.1..=end.1).contains(&col))
+                            {
+                                let from = rows.partition_point(|&row| row < start.0);
+                                let to = rows.partition_point(|&row| row <= end.0);
+                                stack.extend(rows[from..to].iter().map(|&row| ((row, col), false)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Computes formulas, remembering their values
+///
+/// The formulas a formula reads are expected to be computed already: one that is not yet
+/// is part of a cycle.
+struct Evaluator<'a> {
+    formulas: &'a HashMap<(usize, usize), Formula>,
+    values: &'a mut HashMap<(usize, usize), Result<Value, FormulaError>>,
+    rows: &'a [Vec<String>],
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&mut self, cell: (usize, usize)) {
+        if self.values.contains_key(&cell) {
+            return;
+        }
+        let Some(formula) = self.formulas.get(&cell) else {
+            return;
+        };
+        let value = match &formula.expr {
+            Ok(expr) => expr.eval(self, cell.0),
+            Err(error) => Err(*error),
+        };
+        self.values.insert(cell, value);
+    }
+}
+
+impl CellSource for Evaluator<'_> {
+    fn value(&mut self, row: usize, col: usize) -> Result<Value, FormulaError> {
+        let value = self
+            .rows
+            .get(row)
+            .and_then(|values| values.get(col))
+            .ok_or(FormulaError::Ref)?;
+        match self.values.get(&(row, col)) {
+            Some(result) => result.clone(),
+            None if self.formulas.contains_key(&(row, col)) => Err(FormulaError::Cycle),
+            None => Ok(Value::from_cell(value)),
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn col_count(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+}
+
+/// Cells of the table, formulas giving their last computed value
+struct ComputedCells<'a> {
+    graph: &'a FormulaGraph,
+    rows: &'a [Vec<String>],
+}
+
+impl CellSource for ComputedCells<'_> {
+    fn value(&mut self, row: usize, col: usize) -> Result<Value, FormulaError> {
+        let value = self
+            .rows
+            .get(row)
+            .and_then(|values| values.get(col))
+            .ok_or(FormulaError::Ref)?;
+        match self.graph.value(row, col) {
+            Some(result) => result.clone(),
+            None => Ok(Value::from_cell(value)),
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn col_count(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+}
+
+impl TableView {
+    /// Result of the formula of the cell, or `None` when it holds no formula
+    pub fn formula_result(&self, row: usize, col: usize) -> Option<&Result<Value, FormulaError>> {
+        self.formulas.value(row, col)
+    }
+
+    /// Value of the cell, or the result of its formula
+    pub fn computed_value(&self, row: usize, col: usize) -> Cow<'_, str> {
+        match self.formula_result(row, col) {
+            Some(Ok(value)) => Cow::Owned(value.to_string()),
+            Some(Err(error)) => Cow::Owned(error.to_string()),
+            None => Cow::Borrowed(
+                self.rows
+                    .get(row)
+                    .and_then(|values| values.get(col))
+                    .map_or("", String::as_str),
+            ),
+        }
+    }
+
+    /// Value of the cell as it is shown: the result of its formula, with numbers formatted
+    ///
+    /// Formulas themselves are shown in raw display.
+    pub fn shown_value(&self, row: usize, col: usize) -> Cow<'_, str> {
+        if self.raw_display || self.formula_result(row, col).is_none() {
+            let value = self
+                .rows
+                .get(row)
+                .and_then(|values| values.get(col))
+                .map_or("", String::as_str);
+            return self.display_value(col, value);
+        }
+        let computed = self.computed_value(row, col);
+        Cow::Owned(self.display_value(col, &computed).into_owned())
+    }
+
+    /// Letter naming the column in formulas, followed by its type
+    pub fn column_label(&self, col: usize) -> String {
+        format!("{} {}", column_name(col), self.column_type(col).name())
+    }
+
+    /// Fail when the formula cannot be parsed or would read its own value
+    pub fn check_formula(&self, row: usize, col: usize, value: &str) -> Result<()> {
+        let formula = Formula::parse(value, row, &self.header);
+        if let Err(error) = formula.expr {
+            bail!(StrataError::InvalidFormula {
+                formula: value.to_string(),
+                error: error.to_string(),
+            });
+        }
+        if self.formulas.reaches(&formula.dependencies, (row, col)) {
+            bail!(StrataError::CircularReference(format!(
+                "{}{}",
+                column_name(col),
+                row
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write the computed values instead of the formulas, leaving the file of the table
+    /// unchanged
+    pub fn export_values(&self, file_path: &Path) -> Result<()> {
+        self.write_csv(file_path, &self.dialect, true)
+    }
+
+    /// Compute again the formulas affected by the edit that has just been applied, and
+    /// return the cells whose value or computed value changed
+    ///
+    /// Cell updates only touch their dependents. Other edits move cells around, so every
+    /// formula is parsed again and `None` is returned.
+    pub(super) fn refresh_formulas(&mut self, edit: &TableEdit) -> Option<Vec<(usize, usize)>> {
+        let cells = match edit {
+            TableEdit::UpdateCell { row, col, .. } => Some(vec![(*row, *col)]),
+            TableEdit::Batch(edits) => edits
+                .iter()
+                .map(|edit| match edit {
+                    TableEdit::UpdateCell { row, col, .. } => Some((*row, *col)),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+
+        match cells {
+            Some(mut cells) => {
+                let changed = self.formulas.update_cells(&self.header, &self.rows, &cells);
+                for &(_, col) in &changed {
+                    self.column_widths.invalidate(col);
+                }
+                cells.extend(changed);
+                Some(cells)
+            }
+            None => {
+                let had_formulas = !self.formulas.is_empty();
+                self.formulas.rebuild(&self.header, &self.rows);
+                if had_formulas || !self.formulas.is_empty() {
+                    self.column_widths.invalidate_all();
+                }
+                None
+            }
+        }
+    }
+
+    /// Apply the edit as `apply_edit` does and rewrite the references of the formulas to
+    /// the rows and columns it moves, returning the edit that reverts both
+    ///
+    /// The rewrite is part of the edit, so that undoing and redoing it do not shift the
+    /// references again.
+    pub(super) fn apply_edit_shifting_references(&mut self, edit: TableEdit) -> TableEdit {
+        if self.formulas.is_empty() {
+            return self.apply_edit(edit);
+        }
+        let mut cells = self.formulas.formulas.keys().copied().collect();
+        self.apply_shifting(edit, &mut cells)
+    }
+
+    /// Apply the edit and rewrite the references of the formulas in the cells, following
+    /// the formulas the edit moves, adds or removes
+    fn apply_shifting(
+        &mut self,
+        edit: TableEdit,
+        cells: &mut BTreeSet<(usize, usize)>,
+    ) -> TableEdit {
+        let edit = match edit {
+            TableEdit::Batch(edits) => {
+                return self.apply_batch(edits, |tv, edit| tv.apply_shifting(edit, cells));
+            }
+            edit => edit,
+        };
+
+        // formulas brought by the edit read the cells as they are after it
+        let added: Vec<(usize, usize)> = match &edit {
+            TableEdit::UpdateCell { row, col, value } => {
+                cells.remove(&(*row, *col));
+                if is_formula(value) {
+                    vec![(*row, *col)]
+                } else {
+                    Vec::new()
+                }
+            }
+            TableEdit::InsertRow { row, values } => (0..values.len())
+                .filter(|&col| is_formula(&values[col]))
+                .map(|col| (*row, col))
+                .collect(),
+            TableEdit::InsertCol { col, values, .. } => (0..values.len())
+                .filter(|&row| is_formula(&values[row]))
+                .map(|row| (row, *col))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let shift = Shift::of(&edit);
+        let inverse = self.apply_edit(edit);
+        let Some(shift) = shift else {
+            cells.extend(added);
+            return inverse;
+        };
+
+        *cells = cells.iter().filter_map(|&cell| shift.cell(cell)).collect();
+        let rewrites: Vec<TableEdit> = cells
+            .iter()
+            .filter_map(|&(row, col)| {
+                let value = self.rows.get(row)?.get(col).filter(|v| is_formula(v))?;
+                let shifted = shift_references(
+                    &value[1..],
+                    |cell| shift.cell(cell),
+                    |start, end| shift.range(start, end),
+                )?;
+                Some(TableEdit::UpdateCell {
+                    row,
+                    col,
+                    value: format!("={}", shifted),
+                })
+            })
+            .collect();
+        cells.extend(added);
+        if rewrites.is_empty() {
+            return inverse;
+        }
+        let mut inverses: Vec<TableEdit> =
+            rewrites.into_iter().map(|e| self.apply_edit(e)).collect();
+        inverses.reverse();
+        inverses.push(inverse);
+        TableEdit::Batch(inverses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::component::table_view::{ColumnType, SortKey};
+
+    use super::*;
+
+    /// Prices in column 1 and quantities in column 2, with a total in column 3
+    fn setup() -> TableView {
+        let mut tv = TableView::new();
+        tv.update_header(1, "price").unwrap();
+        tv.update_header(2, "quantity").unwrap();
+        for (row, (price, quantity)) in [("100", "2"), ("250", "1"), ("40", "5")]
+            .into_iter()
+            .enumerate()
+        {
+            tv.update_cell(row, 1, price).unwrap();
+            tv.update_cell(row, 2, quantity).unwrap();
+            tv.update_cell(row, 3, "=price*quantity").unwrap();
+        }
+        tv
+    }
+
+    fn computed(tv: &TableView, row: usize, col: usize) -> String {
+        match tv.formula_result(row, col).unwrap() {
+            Ok(value) => value.to_string(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dependents() {
+        let mut tv = setup();
+        tv.update_cell(4, 3, "=SUM(D0:D2)").unwrap();
+        tv.update_cell(5, 3, "=D4/COUNT(price)").unwrap();
+        assert_eq!(computed(&tv, 0, 3), "200");
+        assert_eq!(computed(&tv, 4, 3), "650");
+
+        tv.update_cell(1, 2, "3").unwrap();
+        assert_eq!(computed(&tv, 1, 3), "750");
+        assert_eq!(computed(&tv, 4, 3), "1150");
+        assert_eq!(computed(&tv, 5, 3), "383.3333333333333");
+
+        // a new price is counted by the whole column reference
+        tv.update_cell(6, 1, "10").unwrap();
+        assert_eq!(computed(&tv, 5, 3), "287.5");
+
+        tv.undo().unwrap();
+        assert_eq!(computed(&tv, 5, 3), "383.3333333333333");
+
+        tv.update_cell(1, 3, "").unwrap();
+        assert!(tv.formula_result(1, 3).is_none());
+        assert_eq!(computed(&tv, 4, 3), "400");
+    }
+
+    #[test]
+    fn test_structural_edits() {
+        let mut tv = setup();
+        tv.update_cell(4, 0, "=D0+1").unwrap();
+        tv.update_cell(5, 0, "=SUM(D0:D2)").unwrap();
+        tv.collapse_row(3).unwrap();
+        assert_eq!(tv.rows[3][0], "=D0+1");
+        assert_eq!(computed(&tv, 3, 0), "201");
+
+        // references follow the cells they point to
+        tv.insert_row(0).unwrap();
+        assert_eq!(tv.rows[4][0], "=D1+1");
+        assert_eq!(tv.rows[5][0], "=SUM(D1:D3)");
+        assert_eq!(computed(&tv, 4, 0), "201");
+        assert_eq!(computed(&tv, 5, 0), "650");
+        assert_eq!(computed(&tv, 2, 3), "250");
+
+        tv.insert_col(0).unwrap();
+        assert_eq!(tv.rows[4][1], "=E1+1");
+        assert_eq!(computed(&tv, 4, 1), "201");
+
+        tv.move_row(1, 2).unwrap();
+        assert_eq!(tv.rows[4][1], "=E3+1");
+        assert_eq!(tv.rows[5][1], "=SUM(E1:E3)");
+        assert_eq!(computed(&tv, 4, 1), "201");
+
+        // ranges shrink with the rows removed from them
+        tv.collapse_row(3).unwrap();
+        assert_eq!(tv.rows[3][1], "=#REF!+1");
+        assert_eq!(tv.rows[4][1], "=SUM(E1:E2)");
+        assert_eq!(computed(&tv, 3, 1), "#REF!");
+        assert_eq!(computed(&tv, 4, 1), "450");
+
+        // undoing puts the formulas back as they were
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[4][1], "=E3+1");
+        assert_eq!(computed(&tv, 4, 1), "201");
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[3][0], "=D0+1");
+        assert_eq!(tv.rows[4][0], "=SUM(D0:D2)");
+        tv.redo().unwrap();
+        assert_eq!(tv.rows[4][0], "=D1+1");
+        assert_eq!(computed(&tv, 4, 0), "201");
+    }
+
+    #[test]
+    fn test_sort_shifts_references() {
+        let mut tv = setup();
+        tv.update_cell(4, 0, "=B2*2").unwrap();
+        tv.sort_rows(&[SortKey::ascending(1)], false).unwrap();
+        assert_eq!(tv.rows[0][1], "40");
+        assert_eq!(tv.rows[4][0], "=B0*2");
+        assert_eq!(computed(&tv, 4, 0), "80");
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut tv = setup();
+        assert!(tv.check_formula(0, 0, "=A1").is_ok());
+        tv.update_cell(0, 0, "=A1").unwrap();
+
+        let error = tv.check_formula(1, 0, "=A0 + 1").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<StrataError>(),
+            Some(StrataError::CircularReference(cell)) if cell == "A1"
+        ));
+        assert!(tv.check_formula(2, 1, "=SUM(price)").is_err());
+        assert!(tv.check_formula(0, 1, "=quantity").is_ok());
+        assert!(tv.check_formula(0, 2, "=D0").is_err());
+        assert!(tv.check_formula(0, 4, "=SUM(").is_err());
+
+        // values that are not checked still show the cycle
+        tv.update_cell(1, 0, "=A0").unwrap();
+        assert_eq!(computed(&tv, 0, 0), "#CYCLE!");
+        assert_eq!(computed(&tv, 1, 0), "#CYCLE!");
+        tv.update_cell(1, 0, "1").unwrap();
+        assert_eq!(computed(&tv, 0, 0), "1");
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut tv = setup();
+        tv.update_cell(4, 3, "=SUM(D2:D0)").unwrap();
+        tv.update_cell(5, 3, "=SUM(A0:A999999999)").unwrap();
+        assert_eq!(computed(&tv, 4, 3), "650");
+        assert_eq!(computed(&tv, 5, 3), "#REF!");
+
+        // a cell within the range computes the sum again
+        tv.update_cell(2, 2, "1").unwrap();
+        assert_eq!(computed(&tv, 4, 3), "490");
+        assert!(tv.check_formula(1, 3, "=D4").is_err());
+    }
+
+    #[test]
+    fn test_long_chains() {
+        let header = vec!["n".to_string()];
+        let len = 20_000;
+        let mut rows: Vec<Vec<String>> = (0..len)
+            .map(|row| vec![format!("=A{}+1", row.max(1) - 1)])
+            .collect();
+        rows[0][0] = "0".to_string();
+        let mut graph = FormulaGraph::default();
+        graph.rebuild(&header, &rows);
+        assert_eq!(
+            graph.value(len - 1, 0),
+            Some(&Ok(Value::Number((len - 1) as f64)))
+        );
+
+        // a long cycle is an error rather than a stack overflow
+        rows[0][0] = format!("=A{}", len - 1);
+        graph.rebuild(&header, &rows);
+        assert_eq!(graph.value(0, 0), Some(&Err(FormulaError::Cycle)));
+        assert_eq!(graph.value(len / 2, 0), Some(&Err(FormulaError::Cycle)));
+    }
+
+    #[test]
+    fn test_shown_value() {
+        let mut tv = setup();
+        tv.update_cell(0, 1, "1000").unwrap();
+        tv.set_column_type(3, Some(ColumnType::Integer)).unwrap();
+        assert_eq!(tv.shown_value(0, 3), "2,000");
+        assert_eq!(tv.column_width(3), "integer".len() + 2);
+        tv.update_cell(0, 4, "=price/0").unwrap();
+        assert_eq!(tv.shown_value(0, 4), "#DIV/0!");
+        assert!(!tv.is_valid_value(0, 4));
+
+        tv.toggle_raw_display();
+        assert_eq!(tv.shown_value(0, 3), "=price*quantity");
+        assert_eq!(tv.column_width(3), "=price*quantity".len());
+        assert_eq!(tv.column_label(27), "AB text");
+    }
+
+    #[test]
+    fn test_export_values() {
+        let path = std::env::temp_dir().join("strata_test_export_values.csv");
+        let mut tv = setup();
+        tv.collapse_col(0).unwrap();
+        tv.update_cell(1, 2, "=price+quantity").unwrap();
+        tv.export_values(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("100,2,200,"));
+        assert!(contents.contains("250,1,251,"));
+        assert_eq!(tv.cell_value(1, 2).unwrap(), "=price+quantity");
+        assert!(tv.is_modified());
+        assert!(tv.file_path().is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+use super::{expr::Expr, TableView};
+
+impl TableView {
+    /// Append a column named `header` holding the expression computed over each row, e.g.
+    /// `price * qty`, and select it
+    ///
+    /// A live column holds the expression as a formula in every row, so that it is
+    /// computed again when the row changes. Otherwise the values are computed once.
+    pub fn derive_col(&mut self, header: &str, expression: &str, live: bool) -> Result<&mut Self> {
+        let expr = match Expr::parse(expression, &self.header) {
+            Ok(expr) => expr,
+            Err(error) => bail!(StrataError::InvalidFormula {
+                formula: expression.to_string(),
+                error: error.to_string(),
+            }),
+        };
+
+        let values = if live {
+            vec![format!("={}", expression.trim()); self.rows.len()]
+        } else {
+            (0..self.rows.len())
+                .map(|row| match self.formulas.compute(&expr, &self.rows, row) {
+                    Ok(value) => value.to_string(),
+                    Err(error) => error.to_string(),
+                })
+                .collect()
+        };
+
+        let col = self.header.len();
+        self.append_col(header.to_string(), values)?;
+        self.select_col(col);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::app::component::table_view::ColumnType;
+
+    use super::*;
+
+    #[test]
+    fn test_derive_col() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        tv.derive_col("label", "UPPER(LEFT(fluits, 3)) & \":\" & price", false)
+            .unwrap();
+        assert_eq!(tv.headers()[2], "label");
+        assert_eq!(tv.cell_value(0, 2).unwrap(), "APP:100");
+        assert_eq!(tv.selected_index(), Some((0, 2)));
+
+        tv.derive_col("double", "price * 2", true).unwrap();
+        assert_eq!(tv.cell_value(1, 3).unwrap(), "=price * 2");
+        assert_eq!(tv.shown_value(1, 3), "400");
+        assert_eq!(tv.column_type(3), &ColumnType::Integer);
+
+        // live columns follow the edits, the others keep their values
+        tv.update_cell(1, 1, "300").unwrap();
+        assert_eq!(tv.shown_value(1, 3), "600");
+        assert_eq!(tv.cell_value(1, 2).unwrap(), "ORA:200");
+
+        tv.undo().unwrap();
+        tv.undo().unwrap();
+        assert_eq!(tv.headers().len(), 3);
+        assert!(tv.derive_col("total", "price * qty", false).is_err());
+        assert_eq!(tv.headers().len(), 3);
+    }
+}
@@ -0,0 +1,964 @@
+use std::{cmp::Ordering, fmt, ops::Range};
+
+use regex::Regex;
+
+use crate::app::component::table_view::ColumnType;
+
+/// Error shown in place of the value of a formula, as in spreadsheets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormulaError {
+    /// The formula cannot be parsed
+    Syntax,
+    /// Unknown function or column name
+    Name,
+    /// Reference to a cell outside the table
+    Ref,
+    /// Operation on values of the wrong kind
+    Value,
+    DivByZero,
+    /// The formula depends on its own value
+    Cycle,
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            FormulaError::Syntax => "#ERROR!",
+            FormulaError::Name => "#NAME?",
+            FormulaError::Ref => "#REF!",
+            FormulaError::Value => "#VALUE!",
+            FormulaError::DivByZero => "#DIV/0!",
+            FormulaError::Cycle => "#CYCLE!",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Value of a cell or of an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Empty,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// Read a cell: numbers are values, anything else is text
+    pub fn from_cell(value: &str) -> Self {
+        if value.is_empty() {
+            return Value::Empty;
+        }
+        let is_number = value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b));
+        match value.parse::<f64>() {
+            Ok(number) if is_number => Value::Number(number),
+            _ => Value::Text(value.to_string()),
+        }
+    }
+
+    /// Number for arithmetic, where empty counts as zero
+    fn number(&self) -> Result<f64, FormulaError> {
+        match self {
+            Value::Empty => Ok(0.0),
+            Value::Number(number) => Ok(*number),
+            Value::Bool(b) => Ok(f64::from(u8::from(*b))),
+            Value::Text(_) => Err(FormulaError::Value),
+        }
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Empty => false,
+            Value::Number(number) => *number != 0.0,
+            Value::Text(text) => !text.is_empty(),
+            Value::Bool(b) => *b,
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Number(_) | Value::Empty, Value::Number(_))
+            | (Value::Number(_), Value::Empty) => {
+                let (a, b) = (self.number().unwrap_or(0.0), other.number().unwrap_or(0.0));
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            }
+            _ => self.to_string().cmp(&other.to_string()),
+        }
+    }
+}
+
+/// Written as shown in the table, e.g. `3`, `0.5` or `TRUE`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Empty => Ok(()),
+            Value::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+                write!(f, "{}", *number as i64)
+            }
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Bool(true) => write!(f, "TRUE"),
+            Value::Bool(false) => write!(f, "FALSE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sum,
+    Average,
+    Min,
+    Max,
+    Count,
+    If,
+    Abs,
+    Round,
+    Len,
+    /// Part of a text from a position counted from 1, e.g. `MID("abcd", 2, 2)` is `bc`
+    Mid,
+    Left,
+    Right,
+    Upper,
+    Lower,
+    Trim,
+    /// Text matched by a regular expression or by one of its groups, or empty
+    Regex,
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function); 21] = [
+        ("SUM", Function::Sum),
+        ("AVERAGE", Function::Average),
+        ("MIN", Function::Min),
+        ("MAX", Function::Max),
+        ("COUNT", Function::Count),
+        ("IF", Function::If),
+        ("ABS", Function::Abs),
+        ("ROUND", Function::Round),
+        ("LEN", Function::Len),
+        ("MID", Function::Mid),
+        ("LEFT", Function::Left),
+        ("RIGHT", Function::Right),
+        ("UPPER", Function::Upper),
+        ("LOWER", Function::Lower),
+        ("TRIM", Function::Trim),
+        ("REGEX", Function::Regex),
+        ("YEAR", Function::Year),
+        ("MONTH", Function::Month),
+        ("DAY", Function::Day),
+        ("HOUR", Function::Hour),
+        ("MINUTE", Function::Minute),
+    ];
+
+    fn from_name(name: &str) -> Option<Function> {
+        Self::ALL
+            .iter()
+            .find(|(function_name, _)| function_name.eq_ignore_ascii_case(name))
+            .map(|(_, function)| *function)
+    }
+
+    /// Whether the arguments are lists of values, so that a column name stands for the
+    /// whole column
+    pub fn takes_ranges(self) -> bool {
+        matches!(
+            self,
+            Function::Sum | Function::Average | Function::Min | Function::Max | Function::Count
+        )
+    }
+}
+
+/// Parsed formula, without the leading `=`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// Cell given as `B2`
+    Cell {
+        row: usize,
+        col: usize,
+    },
+    /// Cells from the top left corner to the bottom right one, given as `A0:B9` or
+    /// `B9:A0`
+    Range {
+        start: (usize, usize),
+        end: (usize, usize),
+    },
+    /// Column given by its header: the cell of the same row, or every cell of the column
+    /// in the arguments of `SUM` and the like
+    Column(usize),
+    /// Cells removed from the table, written `#REF!`
+    RemovedRef,
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+/// Cells a formula reads its values from
+pub trait CellSource {
+    fn value(&mut self, row: usize, col: usize) -> Result<Value, FormulaError>;
+    fn row_count(&self) -> usize;
+    fn col_count(&self) -> usize;
+}
+
+impl Expr {
+    /// Parse a formula such as `B2*C2`, `SUM(price)` or `IF(A1="",0,1)`
+    ///
+    /// Names other than functions are looked up in the header.
+    pub fn parse(formula: &str, header: &[String]) -> Result<Expr, FormulaError> {
+        let tokens = tokenize(formula)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            header,
+            depth: 0,
+        };
+        let expr = parser.comparison()?;
+        if parser.position != tokens.len() {
+            return Err(FormulaError::Syntax);
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate the formula of a cell of the row
+    pub fn eval(&self, source: &mut impl CellSource, row: usize) -> Result<Value, FormulaError> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Cell { row, col } => source.value(*row, *col),
+            Expr::Column(col) => source.value(row, *col),
+            Expr::RemovedRef => Err(FormulaError::Ref),
+            // a range only makes sense in a list of values
+            Expr::Range { .. } => Err(FormulaError::Value),
+            Expr::Negate(expr) => Ok(Value::Number(-expr.eval(source, row)?.number()?)),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.eval(source, row)?, right.eval(source, row)?);
+                binary(*op, &left, &right)
+            }
+            Expr::Call(function, args) => call(*function, args, source, row),
+        }
+    }
+
+    /// Values of a function argument, which may be a range or a whole column
+    fn eval_list(
+        &self,
+        source: &mut impl CellSource,
+        row: usize,
+    ) -> Result<Vec<Value>, FormulaError> {
+        let cells: Vec<(usize, usize)> = match self {
+            // checked before listing the cells, which may be countless
+            Expr::Range { end, .. }
+                if end.0 >= source.row_count() || end.1 >= source.col_count() =>
+            {
+                return Err(FormulaError::Ref)
+            }
+            Expr::Range { start, end } => (start.0..=end.0)
+                .flat_map(|row| (start.1..=end.1).map(move |col| (row, col)))
+                .collect(),
+            Expr::Column(col) => (0..source.row_count()).map(|row| (row, *col)).collect(),
+            expr => return Ok(vec![expr.eval(source, row)?]),
+        };
+        cells
+            .into_iter()
+            .map(|(row, col)| source.value(row, col))
+            .collect()
+    }
+
+    /// Visit the cells, ranges and columns the expression refers to, telling whether
+    /// columns stand for the whole column
+    pub fn visit_references(&self, in_list: bool, visit: &mut impl FnMut(&Expr, bool)) {
+        match self {
+            Expr::Literal(_) | Expr::RemovedRef => {}
+            Expr::Cell { .. } | Expr::Range { .. } | Expr::Column(_) => visit(self, in_list),
+            Expr::Negate(expr) => expr.visit_references(false, visit),
+            Expr::Binary(_, left, right) => {
+                left.visit_references(false, visit);
+                right.visit_references(false, visit);
+            }
+            Expr::Call(function, args) => {
+                for arg in args {
+                    arg.visit_references(function.takes_ranges(), visit);
+                }
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, FormulaError> {
+    let number = |f: fn(f64, f64) -> f64| Ok(Value::Number(f(left.number()?, right.number()?)));
+    let ordering = || left.compare(right);
+    match op {
+        BinaryOp::Add => number(|a, b| a + b),
+        BinaryOp::Sub => number(|a, b| a - b),
+        BinaryOp::Mul => number(|a, b| a * b),
+        BinaryOp::Div if right.number()? == 0.0 => Err(FormulaError::DivByZero),
+        BinaryOp::Div => number(|a, b| a / b),
+        BinaryOp::Pow => number(f64::powf),
+        BinaryOp::Concat => Ok(Value::Text(format!("{}{}", left, right))),
+        BinaryOp::Eq => Ok(Value::Bool(ordering().is_eq())),
+        BinaryOp::Ne => Ok(Value::Bool(ordering().is_ne())),
+        BinaryOp::Lt => Ok(Value::Bool(ordering().is_lt())),
+        BinaryOp::Le => Ok(Value::Bool(ordering().is_le())),
+        BinaryOp::Gt => Ok(Value::Bool(ordering().is_gt())),
+        BinaryOp::Ge => Ok(Value::Bool(ordering().is_ge())),
+    }
+}
+
+fn call(
+    function: Function,
+    args: &[Expr],
+    source: &mut impl CellSource,
+    row: usize,
+) -> Result<Value, FormulaError> {
+    if function.takes_ranges() {
+        let mut numbers = Vec::new();
+        for arg in args {
+            for value in arg.eval_list(source, row)? {
+                if let Value::Number(number) = value {
+                    numbers.push(number);
+                }
+            }
+        }
+        let result = match function {
+            Function::Sum => numbers.iter().sum(),
+            Function::Average if numbers.is_empty() => return Err(FormulaError::DivByZero),
+            Function::Average => numbers.iter().sum::<f64>() / numbers.len() as f64,
+            Function::Min => numbers.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            Function::Max => numbers.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            _ => numbers.len() as f64,
+        };
+        return Ok(Value::Number(result));
+    }
+
+    let mut arg = |index: usize| -> Result<Value, FormulaError> {
+        args.get(index)
+            .ok_or(FormulaError::Value)?
+            .eval(source, row)
+    };
+    match (function, args.len()) {
+        (Function::If, 2 | 3) => {
+            if arg(0)?.is_truthy() {
+                arg(1)
+            } else if args.len() == 3 {
+                arg(2)
+            } else {
+                Ok(Value::Bool(false))
+            }
+        }
+        (Function::Abs, 1) => Ok(Value::Number(arg(0)?.number()?.abs())),
+        (Function::Round, 1 | 2) => {
+            let places = match args.len() {
+                2 => arg(1)?.number()?.trunc() as i32,
+                _ => 0,
+            };
+            let factor = 10f64.powi(places);
+            Ok(Value::Number((arg(0)?.number()? * factor).round() / factor))
+        }
+        (Function::Len, 1) => Ok(Value::Number(arg(0)?.to_string().chars().count() as f64)),
+        (Function::Mid, 3) => {
+            let start = arg(1)?.number()?;
+            let len = arg(2)?.number()?;
+            if start < 1.0 || len < 0.0 {
+                return Err(FormulaError::Value);
+            }
+            Ok(substring(&arg(0)?, start as usize - 1, Some(len as usize)))
+        }
+        (Function::Left | Function::Right, 1 | 2) => {
+            let len = match args.len() {
+                2 => arg(1)?.number()?,
+                _ => 1.0,
+            };
+            if len < 0.0 {
+                return Err(FormulaError::Value);
+            }
+            let text = arg(0)?;
+            if function == Function::Left {
+                Ok(substring(&text, 0, Some(len as usize)))
+            } else {
+                let count = text.to_string().chars().count();
+                Ok(substring(&text, count.saturating_sub(len as usize), None))
+            }
+        }
+        (Function::Upper, 1) => Ok(Value::Text(arg(0)?.to_string().to_uppercase())),
+        (Function::Lower, 1) => Ok(Value::Text(arg(0)?.to_string().to_lowercase())),
+        (Function::Trim, 1) => Ok(Value::Text(arg(0)?.to_string().trim().to_string())),
+        (Function::Regex, 2 | 3) => {
+            let text = arg(0)?.to_string();
+            let pattern = Regex::new(&arg(1)?.to_string()).map_err(|_| FormulaError::Value)?;
+            let group = match args.len() {
+                3 => arg(2)?.number()? as usize,
+                _ => 0,
+            };
+            let matched = pattern
+                .captures(&text)
+                .and_then(|captures| captures.get(group))
+                .map_or("", |m| m.as_str());
+            Ok(Value::Text(matched.to_string()))
+        }
+        (
+            Function::Year | Function::Month | Function::Day | Function::Hour | Function::Minute,
+            1,
+        ) => date_part(function, &arg(0)?.to_string()),
+        _ => Err(FormulaError::Value),
+    }
+}
+
+/// Characters of the text from `start`, counted from 0
+fn substring(text: &Value, start: usize, len: Option<usize>) -> Value {
+    let text = text.to_string();
+    let chars = text.chars().skip(start);
+    Value::Text(match len {
+        Some(len) => chars.take(len).collect(),
+        None => chars.collect(),
+    })
+}
+
+/// Part of a date such as `2024-01-31` or of a date and time such as `2024-01-31 08:30`
+fn date_part(function: Function, value: &str) -> Result<Value, FormulaError> {
+    if !ColumnType::DateTime.accepts(value) || value.is_empty() {
+        return Err(FormulaError::Value);
+    }
+    let range = match function {
+        Function::Year => 0..4,
+        Function::Month => 5..7,
+        Function::Day => 8..10,
+        Function::Hour => 11..13,
+        _ => 14..16,
+    };
+    // a date alone is at midnight
+    let part = value.get(range).unwrap_or("0");
+    part.parse()
+        .map(Value::Number)
+        .map_err(|_| FormulaError::Value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Name(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 17] = [
+    "<>", "<=", ">=", "+", "-", "*", "/", "^", "&", "(", ")", ",", ":", "=", "<", ">", "#REF!",
+];
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, FormulaError> {
+    let tokens = tokenize_spans(formula)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Tokens of the formula along with where they are written
+fn tokenize_spans(formula: &str) -> Result<Vec<(Token, Range<usize>)>, FormulaError> {
+    let mut tokens = Vec::new();
+    let mut rest = formula.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let start = formula.len() - rest.len();
+        let len = if c == '"' {
+            // a doubled quote stands for a quote
+            let mut text = String::new();
+            let mut chars = rest.char_indices().skip(1).peekable();
+            let end = loop {
+                match chars.next() {
+                    Some((_, '"')) if chars.peek().is_some_and(|(_, c)| *c == '"') => {
+                        chars.next();
+                        text.push('"');
+                    }
+                    Some((i, '"')) => break i + 1,
+                    Some((_, c)) => text.push(c),
+                    None => return Err(FormulaError::Syntax),
+                }
+            };
+            tokens.push((Token::Text(text), start..start + end));
+            end
+        } else if c.is_ascii_digit() || c == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len].parse().map_err(|_| FormulaError::Syntax)?;
+            tokens.push((Token::Number(number), start..start + len));
+            len
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((Token::Name(rest[..len].to_string()), start..start + len));
+            len
+        } else {
+            let symbol = SYMBOLS
+                .into_iter()
+                .find(|symbol| rest.starts_with(symbol))
+                .ok_or(FormulaError::Syntax)?;
+            tokens.push((Token::Symbol(symbol), start..start + symbol.len()));
+            symbol.len()
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Rewrite the cells and ranges the formula refers to, e.g. `B2` to `B3` when a row is
+/// inserted above it, writing `#REF!` in place of those removed
+///
+/// Returns `None` when no reference changes, or when the formula cannot be read.
+pub fn shift_references(
+    formula: &str,
+    cell: impl Fn((usize, usize)) -> Option<(usize, usize)>,
+    range: impl Fn((usize, usize), (usize, usize)) -> Option<((usize, usize), (usize, usize))>,
+) -> Option<String> {
+    let tokens = tokenize_spans(formula).ok()?;
+    let cell_at = |i: usize| match tokens.get(i) {
+        Some((Token::Name(name), _)) => parse_cell_name(name),
+        _ => None,
+    };
+    let symbol_at = |i: usize, symbol: &str| matches!(tokens.get(i), Some((Token::Symbol(s), _)) if *s == symbol);
+
+    let mut shifted = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        // names followed by a bracket are functions
+        let Some(first) = cell_at(i).filter(|_| !symbol_at(i + 1, "(")) else {
+            i += 1;
+            continue;
+        };
+        let (span, replacement) = match cell_at(i + 2).filter(|_| symbol_at(i + 1, ":")) {
+            Some(second) => {
+                let start = (first.0.min(second.0), first.1.min(second.1));
+                let end = (first.0.max(second.0), first.1.max(second.1));
+                let replacement = match range(start, end) {
+                    Some(corners) if corners == (start, end) => None,
+                    Some((start, end)) => Some(format!("{}:{}", cell_name(start), cell_name(end))),
+                    None => Some("#REF!".to_string()),
+                };
+                i += 3;
+                (tokens[i - 3].1.start..tokens[i - 1].1.end, replacement)
+            }
+            None => {
+                let replacement = match cell(first) {
+                    Some(moved) if moved == first => None,
+                    Some(moved) => Some(cell_name(moved)),
+                    None => Some("#REF!".to_string()),
+                };
+                i += 1;
+                (tokens[i - 1].1.clone(), replacement)
+            }
+        };
+        if let Some(replacement) = replacement {
+            shifted.push_str(&formula[copied..span.start]);
+            shifted.push_str(&replacement);
+            copied = span.end;
+        }
+    }
+    if copied == 0 {
+        return None;
+    }
+    shifted.push_str(&formula[copied..]);
+    Some(shifted)
+}
+
+/// Name of the cell in formulas, e.g. `B2` for column 1 of row 2
+fn cell_name((row, col): (usize, usize)) -> String {
+    format!("{}{}", column_name(col), row)
+}
+
+/// Parse a cell name such as `B2` into its row and column, rows being numbered as shown
+pub fn parse_cell_name(name: &str) -> Option<(usize, usize)> {
+    let digits = name.find(|c: char| c.is_ascii_digit())?;
+    let (letters, row) = name.split_at(digits);
+    if letters.is_empty() || !letters.bytes().all(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    if !row.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let col = letters.bytes().try_fold(0usize, |col, b| {
+        col.checked_mul(26)?.checked_add(usize::from(b - b'A') + 1)
+    })? - 1;
+    Some((row.parse().ok()?, col))
+}
+
+/// Letters naming the column in formulas, e.g. `A`, `Z` then `AA`
+pub fn column_name(col: usize) -> String {
+    let mut name = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Depth of the parsed expression, counting brackets, function calls, unary and binary
+/// operators, beyond which a formula is rejected so that evaluating it cannot overflow
+/// the stack
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    header: &'a [String],
+    /// Depth of the expression being parsed
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consume the symbol if it comes next
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), FormulaError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(FormulaError::Syntax)
+        }
+    }
+
+    /// Go one level deeper into the expression
+    fn descend(&mut self) -> Result<(), FormulaError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(FormulaError::Syntax);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Parse the next level of the expression one level deeper
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Expr, FormulaError>,
+    ) -> Result<Expr, FormulaError> {
+        self.descend()?;
+        let expr = parse(self)?;
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    /// Parse operands joined by the operators, from left to right
+    ///
+    /// Each operator nests the operands before it one level deeper.
+    fn binary(
+        &mut self,
+        operators: &[(&str, BinaryOp)],
+        operand: fn(&mut Self) -> Result<Expr, FormulaError>,
+    ) -> Result<Expr, FormulaError> {
+        let depth = self.depth;
+        let mut expr = operand(self)?;
+        'outer: loop {
+            for (symbol, op) in operators {
+                if self.eat(symbol) {
+                    self.descend()?;
+                    expr = Expr::Binary(*op, Box::new(expr), Box::new(operand(self)?));
+                    continue 'outer;
+                }
+            }
+            self.depth = depth;
+            return Ok(expr);
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(
+            &[
+                ("<>", BinaryOp::Ne),
+                ("<=", BinaryOp::Le),
+                (">=", BinaryOp::Ge),
+                ("=", BinaryOp::Eq),
+                ("<", BinaryOp::Lt),
+                (">", BinaryOp::Gt),
+            ],
+            Self::concat,
+        )
+    }
+
+    fn concat(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&[("&", BinaryOp::Concat)], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::term)
+    }
+
+    fn term(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&[("*", BinaryOp::Mul), ("/", BinaryOp::Div)], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FormulaError> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.nested(Self::unary)?)));
+        }
+        let base = self.primary()?;
+        if self.eat("^") {
+            return Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.nested(Self::unary)?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, FormulaError> {
+        let token = self.peek().cloned().ok_or(FormulaError::Syntax)?;
+        self.position += 1;
+        match token {
+            Token::Number(number) => Ok(Expr::Literal(Value::Number(number))),
+            Token::Text(text) => Ok(Expr::Literal(Value::Text(text))),
+            Token::Symbol("(") => {
+                let expr = self.nested(Self::comparison)?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Symbol("#REF!") => Ok(Expr::RemovedRef),
+            Token::Symbol(_) => Err(FormulaError::Syntax),
+            Token::Name(name) if self.eat("(") => {
+                let function = Function::from_name(&name).ok_or(FormulaError::Name)?;
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.nested(Self::comparison)?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Token::Name(name) if name.eq_ignore_ascii_case("TRUE") => {
+                Ok(Expr::Literal(Value::Bool(true)))
+            }
+            Token::Name(name) if name.eq_ignore_ascii_case("FALSE") => {
+                Ok(Expr::Literal(Value::Bool(false)))
+            }
+            Token::Name(name) => match parse_cell_name(&name) {
+                Some(start) if self.eat(":") => match self.peek() {
+                    Some(Token::Name(end)) => {
+                        let end = parse_cell_name(end).ok_or(FormulaError::Syntax)?;
+                        self.position += 1;
+                        Ok(Expr::Range {
+                            start: (start.0.min(end.0), start.1.min(end.1)),
+                            end: (start.0.max(end.0), start.1.max(end.1)),
+                        })
+                    }
+                    _ => Err(FormulaError::Syntax),
+                },
+                Some((row, col)) => Ok(Expr::Cell { row, col }),
+                None => self
+                    .header
+                    .iter()
+                    .position(|header| *header == name)
+                    .map(Expr::Column)
+                    .ok_or(FormulaError::Name),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Column 0 holds 1, 2, 3 and column 1 holds a, "", c
+    struct Cells;
+
+    impl CellSource for Cells {
+        fn value(&mut self, row: usize, col: usize) -> Result<Value, FormulaError> {
+            let values = [["1", "a"], ["2", ""], ["3", "c"]];
+            let value = values
+                .get(row)
+                .and_then(|row| row.get(col))
+                .ok_or(FormulaError::Ref)?;
+            Ok(Value::from_cell(value))
+        }
+
+        fn row_count(&self) -> usize {
+            3
+        }
+
+        fn col_count(&self) -> usize {
+            2
+        }
+    }
+
+    fn eval(formula: &str, row: usize) -> Result<Value, FormulaError> {
+        let header = ["n".to_string(), "s".to_string()];
+        Expr::parse(formula, &header)?.eval(&mut Cells, row)
+    }
+
+    #[test]
+    fn test_eval() {
+        let number = |n: f64| Ok(Value::Number(n));
+        assert_eq!(eval("1 + 2 * 3 - 4 / 2", 0), number(5.0));
+        assert_eq!(eval("-2^2", 0), number(-4.0));
+        assert_eq!(eval("(A1 + A2) * 10", 0), number(50.0));
+        assert_eq!(eval("n * 10", 2), number(30.0));
+        assert_eq!(eval("SUM(n)", 0), number(6.0));
+        assert_eq!(eval("sum(A0:B2, 4)", 0), number(10.0));
+        assert_eq!(eval("SUM(A2:A1)", 0), number(5.0));
+        assert_eq!(eval("AVERAGE(A0:A2)", 0), number(2.0));
+        assert_eq!(eval("MAX(n) - MIN(n) + COUNT(s)", 0), number(2.0));
+        assert_eq!(eval("IF(B1=\"\",0,1)", 0), number(0.0));
+        assert_eq!(
+            eval("IF(s<>\"\",\"yes\",\"no\")", 0),
+            Ok(Value::Text("yes".into()))
+        );
+        assert_eq!(eval("ROUND(2/3, 2)", 0), number(0.67));
+        assert_eq!(
+            eval("s & \"-\" & n & \"\"\"\"", 2),
+            Ok(Value::Text("c-3\"".into()))
+        );
+        assert_eq!(eval("LEN(\"abc\") >= 3", 0), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_text_and_date_functions() {
+        let text = |s: &str| Ok(Value::Text(s.into()));
+        assert_eq!(eval("MID(\"strata\", 2, 3)", 0), text("tra"));
+        assert_eq!(
+            eval("LEFT(\"strata\", 3) & RIGHT(\"strata\")", 0),
+            text("stra")
+        );
+        assert_eq!(eval("RIGHT(\"ab\", 5)", 0), text("ab"));
+        assert_eq!(
+            eval("UPPER(s) & LOWER(\"B\") & TRIM(\" c \")", 0),
+            text("Abc")
+        );
+        assert_eq!(
+            eval("REGEX(\"item-42x\", \"(\\w+)-(\\d+)\", 2)", 0),
+            text("42")
+        );
+        assert_eq!(eval("REGEX(\"item\", \"\\d+\")", 0), text(""));
+        assert_eq!(
+            eval("YEAR(\"2024-01-31\") + MONTH(\"2024-01-31\")", 0),
+            Ok(Value::Number(2025.0))
+        );
+        assert_eq!(
+            eval(
+                "DAY(\"2024-01-31T08:30\") & \" \" & HOUR(\"2024-01-31T08:30\")",
+                0
+            ),
+            text("31 8")
+        );
+        assert_eq!(eval("MINUTE(\"2024-01-31\")", 0), Ok(Value::Number(0.0)));
+        assert_eq!(eval("YEAR(\"31/01/2024\")", 0), Err(FormulaError::Value));
+        assert_eq!(eval("MID(\"abc\", 0, 1)", 0), Err(FormulaError::Value));
+        assert_eq!(eval("REGEX(\"abc\", \"(\")", 0), Err(FormulaError::Value));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(eval("1 +", 0), Err(FormulaError::Syntax));
+        assert_eq!(eval("(1", 0), Err(FormulaError::Syntax));
+        assert_eq!(eval("\"open", 0), Err(FormulaError::Syntax));
+        assert_eq!(eval("price * 2", 0), Err(FormulaError::Name));
+        assert_eq!(eval("FOO(1)", 0), Err(FormulaError::Name));
+        assert_eq!(eval("C9", 0), Err(FormulaError::Ref));
+        assert_eq!(eval("SUM(A0:A999999999)", 0), Err(FormulaError::Ref));
+        assert_eq!(eval("SUM(A0:ZZZZZZZZ0)", 0), Err(FormulaError::Ref));
+        assert_eq!(eval("s * 2", 0), Err(FormulaError::Value));
+        assert_eq!(eval("1 / B1", 0), Err(FormulaError::DivByZero));
+        assert_eq!(eval("ABS(1, 2)", 0), Err(FormulaError::Value));
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = |depth: usize, open: &str, close: &str| {
+            format!("{}1{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert_eq!(eval(&nested(100, "(", ")"), 0), Ok(Value::Number(1.0)));
+        assert_eq!(eval(&nested(100, "ABS(", ")"), 0), Ok(Value::Number(1.0)));
+        assert_eq!(eval(&"-".repeat(100), 0), Err(FormulaError::Syntax));
+        assert_eq!(
+            eval(&format!("{}1", "-".repeat(100)), 0),
+            Ok(Value::Number(1.0))
+        );
+        assert_eq!(
+            eval(&format!("1{}", "+1".repeat(200)), 0),
+            Ok(Value::Number(201.0))
+        );
+
+        // too deep to be computed without overflowing the stack
+        for formula in [
+            nested(20_000, "(", ")"),
+            nested(20_000, "ABS(", ")"),
+            format!("{}1", "-".repeat(20_000)),
+            format!("1{}", "^1".repeat(20_000)),
+            format!("1{}", "+1".repeat(20_000)),
+        ] {
+            assert_eq!(eval(&formula, 0), Err(FormulaError::Syntax));
+        }
+    }
+
+    #[test]
+    fn test_shift_references() {
+        // rows from 2 on move down, and row 5 is removed
+        let shift = |formula: &str| {
+            shift_references(
+                formula,
+                |(row, col)| match row {
+                    5 => None,
+                    row if row >= 2 => Some((row + 1, col)),
+                    row => Some((row, col)),
+                },
+                |start, end| Some(((start.0, start.1), (end.0 + 1, end.1))),
+            )
+        };
+        assert_eq!(shift("A1 + B2*2").as_deref(), Some("A1 + B3*2"));
+        assert_eq!(shift("SUM(A0:B3, 1)").as_deref(), Some("SUM(A0:B4, 1)"));
+        assert_eq!(shift("SUM(b3:a0)").as_deref(), None);
+        assert_eq!(shift("SUM(B3:A0)").as_deref(), Some("SUM(A0:B4)"));
+        assert_eq!(shift("C5 & \"B2\"").as_deref(), Some("#REF! & \"B2\""));
+        assert_eq!(shift("A1 + price"), None);
+        assert_eq!(shift("A1 + ("), None);
+
+        assert_eq!(eval("#REF! + 1", 0), Err(FormulaError::Ref));
+    }
+
+    #[test]
+    fn test_cell_names() {
+        assert_eq!(parse_cell_name("B2"), Some((2, 1)));
+        assert_eq!(parse_cell_name("AA10"), Some((10, 26)));
+        assert_eq!(parse_cell_name("b2"), None);
+        assert_eq!(parse_cell_name("B2x"), None);
+        assert_eq!(parse_cell_name("price"), None);
+        for col in [0, 25, 26, 701, 702] {
+            let name = format!("{}0", column_name(col));
+            assert_eq!(parse_cell_name(&name), Some((0, col)));
+        }
+        assert_eq!(column_name(27), "AB");
+    }
+}
@@ -0,0 +1,110 @@
+use std::cmp::Ordering;
+
+use super::{Corners, TableEdit};
+
+/// How an edit changes the indices of the rows or of the columns
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum IndexShift {
+    Insert(usize),
+    Remove(usize),
+    Move {
+        from: usize,
+        to: usize,
+    },
+    /// New index of each index
+    Reorder(Vec<usize>),
+}
+
+impl IndexShift {
+    /// New index, or `None` when it is removed
+    fn index(&self, index: usize) -> Option<usize> {
+        match *self {
+            IndexShift::Insert(at) => Some(if index >= at { index + 1 } else { index }),
+            IndexShift::Remove(at) => match index.cmp(&at) {
+                Ordering::Less => Some(index),
+                Ordering::Equal => None,
+                Ordering::Greater => Some(index - 1),
+            },
+            IndexShift::Move { from, to } => Some(match index {
+                i if i == from => to,
+                i if from < i && i <= to => i - 1,
+                i if to <= i && i < from => i + 1,
+                i => i,
+            }),
+            IndexShift::Reorder(ref order) => Some(order.get(index).copied().unwrap_or(index)),
+        }
+    }
+
+    /// New first and last indices of a span, or `None` when all of it is removed
+    ///
+    /// Spans grow and shrink with the indices inserted or removed within them, but keep
+    /// their place when indices are moved.
+    fn span(&self, first: usize, last: usize) -> Option<(usize, usize)> {
+        match *self {
+            IndexShift::Insert(_) => Some((self.index(first)?, self.index(last)?)),
+            IndexShift::Remove(at) if first == at && last == at => None,
+            IndexShift::Remove(at) => Some((
+                if first > at { first - 1 } else { first },
+                if last >= at { last - 1 } else { last },
+            )),
+            IndexShift::Move { .. } | IndexShift::Reorder(_) => Some((first, last)),
+        }
+    }
+}
+
+/// Rows or columns whose indices an edit changes, for the references to them to follow
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Shift {
+    Rows(IndexShift),
+    Cols(IndexShift),
+}
+
+impl Shift {
+    /// Indices changed by the edit, unless it leaves them in place
+    pub(super) fn of(edit: &TableEdit) -> Option<Shift> {
+        match edit {
+            TableEdit::InsertRow { row, .. } => Some(Shift::Rows(IndexShift::Insert(*row))),
+            TableEdit::RemoveRow { row } => Some(Shift::Rows(IndexShift::Remove(*row))),
+            TableEdit::MoveRow { from, to } => Some(Shift::Rows(IndexShift::Move {
+                from: *from,
+                to: *to,
+            })),
+            TableEdit::ReorderRows(order) => {
+                let mut new = vec![0; order.len()];
+                for (row, &old) in order.iter().enumerate() {
+                    new[old] = row;
+                }
+                Some(Shift::Rows(IndexShift::Reorder(new)))
+            }
+            TableEdit::InsertCol { col, .. } => Some(Shift::Cols(IndexShift::Insert(*col))),
+            TableEdit::RemoveCol { col } => Some(Shift::Cols(IndexShift::Remove(*col))),
+            TableEdit::MoveCol { from, to } => Some(Shift::Cols(IndexShift::Move {
+                from: *from,
+                to: *to,
+            })),
+            _ => None,
+        }
+    }
+
+    /// New place of the cell, or `None` when it is removed
+    pub(super) fn cell(&self, (row, col): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Shift::Rows(shift) => Some((shift.index(row)?, col)),
+            Shift::Cols(shift) => Some((row, shift.index(col)?)),
+        }
+    }
+
+    /// New corners of the range, or `None` when all of it is removed
+    pub(super) fn range(&self, start: (usize, usize), end: (usize, usize)) -> Option<Corners> {
+        match self {
+            Shift::Rows(shift) => {
+                let (first, last) = shift.span(start.0, end.0)?;
+                Some(((first, start.1), (last, end.1)))
+            }
+            Shift::Cols(shift) => {
+                let (first, last) = shift.span(start.1, end.1)?;
+                Some(((start.0, first), (end.0, last)))
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use eyre::{OptionExt, Result};
+
+use crate::error::StrataError;
+
+use super::TableView;
+
+/// Cursor motion that can be repeated with a count, as in vim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// First row, or the row given by the count
+    FirstRow,
+    /// Last row, or the row given by the count
+    LastRow,
+    FirstColumn,
+    LastColumn,
+    HalfPageDown,
+    HalfPageUp,
+    /// Next non-empty cell, going through the rows
+    NextNonEmpty,
+    PreviousNonEmpty,
+    /// Empty row after the next block of non-empty rows
+    NextBlock,
+    PreviousBlock,
+}
+
+impl TableView {
+    /// Move the cursor `count` times, or to the row numbered `count` for `FirstRow` and
+    /// `LastRow`
+    ///
+    /// Rows hidden by the filter are skipped.
+    pub fn apply_motion(&mut self, motion: Motion, count: Option<usize>) -> Result<&mut Self> {
+        let (row, col) = self
+            .selected_index()
+            .ok_or_eyre(StrataError::NoCellSelected)?;
+        let times = count.unwrap_or(1).max(1);
+        let signed = |n: usize| isize::try_from(n).unwrap_or(isize::MAX);
+        let last_position = self.visible_row_count().saturating_sub(1);
+
+        let (position, col) = match motion {
+            Motion::Up => return self.move_selector(-signed(times), 0),
+            Motion::Down => return self.move_selector(signed(times), 0),
+            Motion::Left => return self.move_selector(0, -signed(times)),
+            Motion::Right => return self.move_selector(0, signed(times)),
+            Motion::HalfPageDown => {
+                return self.move_selector(signed(self.half_page().saturating_mul(times)), 0)
+            }
+            Motion::HalfPageUp => {
+                return self.move_selector(-signed(self.half_page().saturating_mul(times)), 0)
+            }
+            Motion::FirstRow | Motion::LastRow => {
+                let position = match (motion, count) {
+                    (_, Some(row)) => self.row_position(row).min(last_position),
+                    (Motion::FirstRow, None) => 0,
+                    _ => last_position,
+                };
+                (position, col)
+            }
+            Motion::FirstColumn => (self.row_position(row), 0),
+            Motion::LastColumn => (self.row_position(row), self.max_col_index()),
+            Motion::NextNonEmpty | Motion::PreviousNonEmpty => {
+                let forward = motion == Motion::NextNonEmpty;
+                (0..times).fold((self.row_position(row), col), |from, _| {
+                    self.find_non_empty(from, forward).unwrap_or(from)
+                })
+            }
+            Motion::NextBlock | Motion::PreviousBlock => {
+                let forward = motion == Motion::NextBlock;
+                let position = (0..times).fold(self.row_position(row), |from, _| {
+                    self.find_block_edge(from, forward)
+                });
+                (position, col)
+            }
+        };
+
+        let row = self.row_at(position).unwrap_or(row);
+        self.cell_selector.select_cell(Some((row, col)));
+        Ok(self)
+    }
+
+    /// Half of the rows shown the last time the table was drawn
+    fn half_page(&self) -> usize {
+        (self.viewport().height / 2).max(1)
+    }
+
+    fn is_empty_row(&self, position: usize) -> bool {
+        self.row_at(position)
+            .is_none_or(|row| self.rows[row].iter().all(String::is_empty))
+    }
+
+    /// Position and column of the next non-empty cell in row-major order
+    fn find_non_empty(&self, from: (usize, usize), forward: bool) -> Option<(usize, usize)> {
+        let col_count = self.header.len();
+        let cell_count = self.visible_row_count() * col_count;
+        let start = from.0 * col_count + from.1;
+        let is_non_empty = |index: &usize| {
+            let (position, col) = (index / col_count, index % col_count);
+            self.row_at(position)
+                .and_then(|row| self.rows[row].get(col))
+                .is_some_and(|value| !value.is_empty())
+        };
+        let index = if forward {
+            (start + 1..cell_count).find(is_non_empty)
+        } else {
+            (0..start).rev().find(is_non_empty)
+        }?;
+        Some((index / col_count, index % col_count))
+    }
+
+    /// Skip the empty rows, then the block of non-empty rows, and stop at the empty row
+    /// after it or at the edge of the table
+    fn find_block_edge(&self, from: usize, forward: bool) -> usize {
+        let last_position = self.visible_row_count().saturating_sub(1);
+        let step = |position: usize| {
+            if forward {
+                (position < last_position).then(|| position + 1)
+            } else {
+                position.checked_sub(1)
+            }
+        };
+
+        let mut position = from;
+        let mut in_block = false;
+        while let Some(next) = step(position) {
+            position = next;
+            match (self.is_empty_row(position), in_block) {
+                (true, true) => return position,
+                (false, _) => in_block = true,
+                (true, false) => {}
+            }
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use crate::app::component::table_view::RowFilter;
+
+    use super::*;
+
+    /// Blocks of non-empty rows at 2..4 and 6, in column 1
+    fn setup() -> TableView {
+        let mut tv = TableView::new();
+        for row in [2, 3, 6] {
+            tv.update_cell(row, 1, &format!("value {}", row)).unwrap();
+        }
+        tv.update_cell(3, 4, "other").unwrap();
+        tv
+    }
+
+    #[test]
+    fn test_counted_moves() {
+        let mut tv = setup();
+        tv.apply_motion(Motion::Down, Some(4)).unwrap();
+        assert_eq!(tv.selected_index(), Some((4, 0)));
+        tv.apply_motion(Motion::Right, Some(20)).unwrap();
+        assert_eq!(tv.selected_index(), Some((4, 9)));
+        tv.apply_motion(Motion::FirstColumn, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((4, 0)));
+        tv.apply_motion(Motion::LastRow, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((9, 0)));
+        tv.apply_motion(Motion::FirstRow, Some(3)).unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 0)));
+        tv.apply_motion(Motion::LastColumn, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 9)));
+    }
+
+    #[test]
+    fn test_half_page() {
+        let mut tv = setup();
+        tv.scroll_to_selection(Rect::new(0, 0, 80, 11));
+        tv.apply_motion(Motion::HalfPageDown, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 0)));
+        tv.apply_motion(Motion::HalfPageDown, Some(2)).unwrap();
+        assert_eq!(tv.selected_index(), Some((9, 0)));
+        tv.apply_motion(Motion::HalfPageUp, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((6, 0)));
+
+        // a huge count stops at the edges instead of overflowing
+        tv.apply_motion(Motion::HalfPageDown, Some(usize::MAX))
+            .unwrap();
+        assert_eq!(tv.selected_index(), Some((9, 0)));
+        tv.apply_motion(Motion::HalfPageUp, Some(usize::MAX))
+            .unwrap();
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_non_empty_cells() {
+        let mut tv = setup();
+        tv.apply_motion(Motion::NextNonEmpty, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((2, 1)));
+        tv.apply_motion(Motion::NextNonEmpty, Some(2)).unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 4)));
+        tv.apply_motion(Motion::NextNonEmpty, Some(5)).unwrap();
+        assert_eq!(tv.selected_index(), Some((6, 1)));
+        tv.apply_motion(Motion::PreviousNonEmpty, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let mut tv = setup();
+        tv.apply_motion(Motion::NextBlock, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((4, 0)));
+        tv.apply_motion(Motion::NextBlock, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((7, 0)));
+        tv.apply_motion(Motion::NextBlock, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((9, 0)));
+        tv.apply_motion(Motion::PreviousBlock, Some(2)).unwrap();
+        assert_eq!(tv.selected_index(), Some((1, 0)));
+
+        // hidden rows are skipped
+        tv.set_filter(Some(RowFilter::parse("1 !empty").unwrap()))
+            .unwrap();
+        tv.apply_motion(Motion::LastRow, None).unwrap();
+        assert_eq!(tv.selected_index(), Some((6, 0)));
+        tv.apply_motion(Motion::FirstRow, Some(4)).unwrap();
+        assert_eq!(tv.selected_index(), Some((6, 0)));
+    }
+}
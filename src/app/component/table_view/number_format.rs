@@ -0,0 +1,217 @@
+use std::borrow::Cow;
+
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+use super::{ColumnType, TableView};
+
+/// Most decimal places shown, beyond which `f64` values only show noise
+const MAX_DECIMAL_PLACES: usize = 17;
+
+/// How the values of numeric columns are shown
+///
+/// Only the display changes: the values themselves and what gets saved stay as typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Put between groups of three digits, e.g. `1,234,567`
+    pub thousands_separator: Option<char>,
+    /// Round decimal values to this many places, e.g. `3.10` with 2
+    pub decimal_places: Option<usize>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            thousands_separator: Some(','),
+            decimal_places: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Parse settings such as `sep=_ places=2` over the default format, where `none`
+    /// turns a setting off
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut format = Self::default();
+        for setting in input.split_whitespace() {
+            let parse_error = || StrataError::StringParseError(setting.to_string());
+            let (name, value) = setting.split_once('=').ok_or_else(parse_error)?;
+            match (name, value) {
+                ("sep", "none") => format.thousands_separator = None,
+                ("sep", separator) => {
+                    let mut chars = separator.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if !c.is_ascii_digit() && c != '.' && c != '-' => {
+                            format.thousands_separator = Some(c);
+                        }
+                        _ => bail!(parse_error()),
+                    }
+                }
+                ("places", "none") => format.decimal_places = None,
+                ("places", places) => match places.parse() {
+                    Ok(places) if places <= MAX_DECIMAL_PLACES => {
+                        format.decimal_places = Some(places)
+                    }
+                    _ => bail!(parse_error()),
+                },
+                _ => bail!(parse_error()),
+            }
+        }
+        Ok(format)
+    }
+
+    /// Format the number for display, or `None` when the value is not a number
+    ///
+    /// Decimal places only apply to decimal columns, so integers are just grouped.
+    pub fn format(&self, value: &str, column_type: &ColumnType) -> Option<String> {
+        if !column_type.accepts(value) || value.is_empty() {
+            return None;
+        }
+        let value = value.strip_prefix('+').unwrap_or(value);
+        let rounded;
+        let value = match (column_type, self.decimal_places) {
+            (ColumnType::Integer, _) => value,
+            (ColumnType::Decimal, Some(places)) => {
+                rounded = format!("{:.*}", places, value.parse::<f64>().ok()?);
+                &rounded
+            }
+            // scientific notation is left as typed
+            (ColumnType::Decimal, None) if !value.contains(['e', 'E']) => value,
+            _ => return None,
+        };
+
+        let (sign, unsigned) = match value.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", value),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut formatted = sign.to_string();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.extend(self.thousands_separator);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push('.');
+            formatted.push_str(fraction);
+        }
+        Some(formatted)
+    }
+}
+
+impl TableView {
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    pub fn set_number_format(&mut self, number_format: NumberFormat) -> &mut Self {
+        self.number_format = number_format;
+        self.column_widths.invalidate_all();
+        self
+    }
+
+    pub fn is_raw_display(&self) -> bool {
+        self.raw_display
+    }
+
+    /// Switch between the values as typed and the formatted numbers
+    pub fn toggle_raw_display(&mut self) -> &mut Self {
+        self.raw_display = !self.raw_display;
+        self.column_widths.invalidate_all();
+        self
+    }
+
+    /// Whether the column is shown as numbers, aligned to the right
+    pub fn is_numeric_column(&self, col: usize) -> bool {
+        matches!(
+            self.column_type(col),
+            ColumnType::Integer | ColumnType::Decimal
+        )
+    }
+
+    /// Value of a cell of the column as it is shown
+    pub fn display_value<'a>(&self, col: usize, value: &'a str) -> Cow<'a, str> {
+        if self.raw_display || !self.is_numeric_column(col) {
+            return Cow::Borrowed(value);
+        }
+        match self.number_format.format(value, self.column_type(col)) {
+            Some(formatted) => Cow::Owned(formatted),
+            None => Cow::Borrowed(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let format = NumberFormat::default();
+        let integer = ColumnType::Integer;
+        let decimal = ColumnType::Decimal;
+        assert_eq!(format.format("1234567", &integer).unwrap(), "1,234,567");
+        assert_eq!(format.format("-123456", &integer).unwrap(), "-123,456");
+        assert_eq!(format.format("+999", &integer).unwrap(), "999");
+        assert_eq!(format.format("1234.5678", &decimal).unwrap(), "1,234.5678");
+        assert_eq!(format.format("1e6", &decimal), None);
+        assert_eq!(format.format("abc", &integer), None);
+        assert_eq!(format.format("", &integer), None);
+
+        let format = NumberFormat::parse("sep=_ places=2").unwrap();
+        assert_eq!(format.format("1234.5678", &decimal).unwrap(), "1_234.57");
+        assert_eq!(format.format("-0.5", &decimal).unwrap(), "-0.50");
+        assert_eq!(format.format("1e6", &decimal).unwrap(), "1_000_000.00");
+        assert_eq!(format.format("1234", &integer).unwrap(), "1_234");
+
+        let format = NumberFormat::parse("sep=none").unwrap();
+        assert_eq!(format.format("1234567", &integer).unwrap(), "1234567");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(NumberFormat::parse("").unwrap(), NumberFormat::default());
+        assert_eq!(
+            NumberFormat::parse("places=3").unwrap().decimal_places,
+            Some(3)
+        );
+        assert_eq!(
+            NumberFormat::parse("places=17").unwrap().decimal_places,
+            Some(17)
+        );
+        // the decimal point cannot separate thousands
+        for input in [
+            "sep",
+            "sep=ab",
+            "sep=.",
+            "places=-1",
+            "places=18",
+            "places=99999999999",
+            "width=3",
+        ] {
+            assert!(NumberFormat::parse(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_display_value() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        tv.update_cell(0, 1, "1234567890").unwrap();
+        assert!(tv.is_numeric_column(1));
+        assert_eq!(tv.display_value(1, &tv.rows[0][1]), "1,234,567,890");
+        assert_eq!(tv.column_width(1), "1,234,567,890".len());
+        assert_eq!(tv.display_value(0, "1200"), "1200");
+
+        tv.toggle_raw_display();
+        assert_eq!(tv.display_value(1, &tv.rows[0][1]), "1234567890");
+        assert_eq!(tv.column_width(1), "1234567890".len());
+    }
+}
@@ -0,0 +1,145 @@
+use eyre::Result;
+use regex::Regex;
+
+use super::{CellRange, TableEdit, TableView};
+
+/// Cell rewritten by a find-and-replace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub row: usize,
+    pub col: usize,
+    pub old: String,
+    pub new: String,
+}
+
+impl TableView {
+    /// Cells matching the pattern with their replaced value, in row-major order
+    ///
+    /// `$1`, `${name}` and so on in `replacement` expand to the capture groups of the match.
+    pub fn find_replacements(
+        &self,
+        regex: &Regex,
+        replacement: &str,
+        column: Option<usize>,
+    ) -> Vec<Replacement> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(col, _)| column.is_none_or(|c| c == col))
+                    .filter(|(_, value)| regex.is_match(value))
+                    .map(move |(col, value)| Replacement {
+                        row,
+                        col,
+                        old: value.clone(),
+                        new: regex.replace_all(value, replacement).into_owned(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Cells of the visible rows of the range matching the pattern with their replaced
+    /// value, in row-major order
+    pub fn find_replacements_in(
+        &self,
+        regex: &Regex,
+        replacement: &str,
+        range: &CellRange,
+    ) -> Vec<Replacement> {
+        range
+            .rows
+            .clone()
+            .filter(|&row| self.is_row_visible(row))
+            .flat_map(|row| range.cols.clone().map(move |col| (row, col)))
+            .filter_map(|(row, col)| {
+                let value = self.rows.get(row)?.get(col)?;
+                regex.is_match(value).then(|| Replacement {
+                    row,
+                    col,
+                    old: value.clone(),
+                    new: regex.replace_all(value, replacement).into_owned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Rewrite the cells as one edit, so that a single undo reverts all of them
+    pub fn apply_replacements(&mut self, replacements: &[Replacement]) -> Result<&mut Self> {
+        for replacement in replacements {
+            self.is_valid_row_index(replacement.row)?;
+            self.is_valid_col_index(replacement.col)?;
+        }
+        let edits: Vec<TableEdit> = replacements
+            .iter()
+            .filter(|replacement| self.rows[replacement.row][replacement.col] != replacement.new)
+            .map(|replacement| TableEdit::UpdateCell {
+                row: replacement.row,
+                col: replacement.col,
+                value: replacement.new.clone(),
+            })
+            .collect();
+        if edits.is_empty() {
+            return Ok(self);
+        }
+        self.commit(TableEdit::Batch(edits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_replacements() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 0, "2024-01-31").unwrap();
+        tv.update_cell(3, 2, "2023-12-01").unwrap();
+        tv.update_cell(4, 2, "no date").unwrap();
+        let regex = Regex::new(r"(\d{4})-(\d{2})-(\d{2})").unwrap();
+
+        let replacements = tv.find_replacements(&regex, "$3/$2/$1", None);
+        assert_eq!(
+            replacements,
+            vec![
+                Replacement {
+                    row: 0,
+                    col: 0,
+                    old: "2024-01-31".to_string(),
+                    new: "31/01/2024".to_string(),
+                },
+                Replacement {
+                    row: 3,
+                    col: 2,
+                    old: "2023-12-01".to_string(),
+                    new: "01/12/2023".to_string(),
+                },
+            ]
+        );
+        assert_eq!(tv.find_replacements(&regex, "", Some(2)).len(), 1);
+    }
+
+    #[test]
+    fn test_apply_replacements() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 0, "a-b").unwrap();
+        tv.update_cell(1, 1, "b-a").unwrap();
+        let regex = Regex::new("-").unwrap();
+        let replacements = tv.find_replacements(&regex, "+", None);
+
+        tv.apply_replacements(&replacements).unwrap();
+        assert_eq!(tv.rows[0][0], "a+b");
+        assert_eq!(tv.rows[1][1], "b+a");
+
+        // the whole batch is undone at once
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[0][0], "a-b");
+        assert_eq!(tv.rows[1][1], "b-a");
+
+        tv.readonly = true;
+        assert!(tv.apply_replacements(&replacements).is_err());
+        assert_eq!(tv.rows[0][0], "a-b");
+    }
+}
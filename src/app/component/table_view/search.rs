@@ -0,0 +1,316 @@
+use eyre::{bail, OptionExt, Result};
+use regex::{Regex, RegexBuilder};
+
+use crate::error::StrataError;
+
+use super::TableView;
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Plain(String),
+    /// Lowercased pattern
+    IgnoreCase(String),
+    Regex(Regex),
+}
+
+impl PartialEq for Matcher {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Matcher::Plain(a), Matcher::Plain(b)) => a == b,
+            (Matcher::IgnoreCase(a), Matcher::IgnoreCase(b)) => a == b,
+            (Matcher::Regex(a), Matcher::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
+}
+
+/// Pattern searched for in the cells of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    query: String,
+    matcher: Matcher,
+    /// Whether `next_match` goes towards the top of the table
+    pub backward: bool,
+    /// Only search in this column
+    pub column: Option<usize>,
+}
+
+impl Search {
+    /// Parse the query typed in the search prompt
+    ///
+    /// The query may contain these flags anywhere:
+    /// - `\c`: ignore case
+    /// - `\r`: treat the pattern as a regular expression
+    /// - `\l`: only search in `current_col`
+    pub fn parse(query: &str, backward: bool, current_col: usize) -> Result<Self> {
+        let (pattern, flags) = split_flags(query, &['c', 'r', 'l']);
+        let ignore_case = flags.contains(&'c');
+        let is_regex = flags.contains(&'r');
+        let column = flags.contains(&'l').then_some(current_col);
+        if pattern.is_empty() {
+            bail!(StrataError::InvalidSearchPattern(query.to_string()));
+        }
+
+        let matcher = if is_regex {
+            RegexBuilder::new(&pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| StrataError::InvalidSearchPattern(e.to_string()))?
+        } else if ignore_case {
+            Matcher::IgnoreCase(pattern.to_lowercase())
+        } else {
+            Matcher::Plain(pattern)
+        };
+
+        Ok(Self {
+            query: query.to_string(),
+            matcher,
+            backward,
+            column,
+        })
+    }
+
+    /// Search for a regular expression, e.g. to preview the cells a replace will rewrite
+    pub fn from_regex(regex: Regex, column: Option<usize>) -> Self {
+        Self {
+            query: regex.as_str().to_string(),
+            matcher: Matcher::Regex(regex),
+            backward: false,
+            column,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        match &self.matcher {
+            Matcher::Plain(pattern) => value.contains(pattern.as_str()),
+            Matcher::IgnoreCase(pattern) => value.to_lowercase().contains(pattern.as_str()),
+            Matcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Remove the `\x` flags found in `flags` from the query and return them with the pattern
+///
+/// A backslash followed by any other character is kept in the pattern.
+pub fn split_flags(query: &str, flags: &[char]) -> (String, Vec<char>) {
+    let mut pattern = String::new();
+    let mut found = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&flag) if c == '\\' && flags.contains(&flag) => {
+                found.push(flag);
+                chars.next();
+            }
+            _ => pattern.push(c),
+        }
+    }
+    (pattern, found)
+}
+
+impl TableView {
+    pub fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    pub fn is_search_match(&self, row: usize, col: usize) -> bool {
+        self.search.as_ref().is_some_and(|search| {
+            search.column.is_none_or(|c| c == col)
+                && self
+                    .rows
+                    .get(row)
+                    .and_then(|r| r.get(col))
+                    .is_some_and(|value| search.is_match(value))
+        })
+    }
+
+    /// Show the matches of a search that is still being typed and move to the first one
+    ///
+    /// The cell selected when the preview started is kept so that the preview can be
+    /// cancelled.
+    pub fn preview_search(&mut self, search: Option<Search>) -> &mut Self {
+        let origin = *self
+            .search_origin
+            .get_or_insert(self.selected_index().unwrap_or((0, 0)));
+        self.search = search;
+        let target = self
+            .search
+            .as_ref()
+            .and_then(|search| self.find_match(origin, !search.backward))
+            .unwrap_or(origin);
+        self.cell_selector.select_cell(Some(target));
+        self
+    }
+
+    /// Stop highlighting the matches and forget where the search started
+    pub fn clear_search(&mut self) -> &mut Self {
+        self.search = None;
+        self.search_origin = None;
+        self
+    }
+
+    /// Clear the search being previewed and go back to where it started
+    pub fn cancel_search_preview(&mut self) -> &mut Self {
+        if let Some(origin) = self.search_origin.take() {
+            self.search = None;
+            self.cell_selector.select_cell(Some(origin));
+        }
+        self
+    }
+
+    /// Keep the search and move to its first match from where the search started
+    pub fn start_search(&mut self, search: Search) -> Result<&mut Self> {
+        let origin = self
+            .search_origin
+            .take()
+            .or(self.selected_index())
+            .unwrap_or((0, 0));
+        let forward = !search.backward;
+        self.search = Some(search);
+        self.cell_selector.select_cell(Some(origin));
+        self.jump_to_match(origin, forward)
+    }
+
+    /// Move to the next match in the direction of the search
+    pub fn next_match(&mut self) -> Result<&mut Self> {
+        let search = self.search.as_ref().ok_or_eyre(StrataError::NoSearch)?;
+        let forward = !search.backward;
+        self.jump_to_match(self.selected_index().unwrap_or((0, 0)), forward)
+    }
+
+    /// Move to the next match in the opposite direction of the search
+    pub fn previous_match(&mut self) -> Result<&mut Self> {
+        let search = self.search.as_ref().ok_or_eyre(StrataError::NoSearch)?;
+        let forward = search.backward;
+        self.jump_to_match(self.selected_index().unwrap_or((0, 0)), forward)
+    }
+
+    fn jump_to_match(&mut self, from: (usize, usize), forward: bool) -> Result<&mut Self> {
+        let target = self.find_match(from, forward).ok_or_else(|| {
+            StrataError::SearchNotFound(
+                self.search
+                    .as_ref()
+                    .map(|search| search.query().to_string())
+                    .unwrap_or_default(),
+            )
+        })?;
+        self.cell_selector.select_cell(Some(target));
+        Ok(self)
+    }
+
+    /// First visible matching cell after (or before) `from` in row-major order, wrapping
+    /// around the table
+    fn find_match(&self, from: (usize, usize), forward: bool) -> Option<(usize, usize)> {
+        let col_count = self.header.len();
+        let cell_count = self.rows.len() * col_count;
+        if cell_count == 0 {
+            return None;
+        }
+
+        let start = (from.0 * col_count + from.1).min(cell_count - 1);
+        (1..=cell_count)
+            .map(|step| {
+                if forward {
+                    (start + step) % cell_count
+                } else {
+                    (start + cell_count - step) % cell_count
+                }
+            })
+            .map(|index| (index / col_count, index % col_count))
+            .find(|&(row, col)| self.is_row_visible(row) && self.is_search_match(row, col))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> TableView {
+        let mut tv = TableView::new();
+        tv.update_cell(1, 1, "Apple").unwrap();
+        tv.update_cell(2, 3, "pineapple").unwrap();
+        tv.update_cell(5, 1, "apple pie").unwrap();
+        tv
+    }
+
+    #[test]
+    fn test_parse() {
+        let search = Search::parse("apple", false, 0).unwrap();
+        assert!(search.is_match("pineapple"));
+        assert!(!search.is_match("Apple"));
+
+        let search = Search::parse("\\capple", false, 0).unwrap();
+        assert!(search.is_match("APPLE"));
+
+        let search = Search::parse("^app\\r\\c", false, 3).unwrap();
+        assert!(search.is_match("Apple"));
+        assert!(!search.is_match("pineapple"));
+        assert_eq!(search.column, None);
+
+        let search = Search::parse("a\\l\\d", false, 3).unwrap();
+        assert_eq!(search.column, Some(3));
+        assert!(search.is_match("a\\d"));
+
+        assert!(Search::parse("\\c", false, 0).is_err());
+        assert!(Search::parse("(\\r", false, 0).is_err());
+    }
+
+    #[test]
+    fn test_next_match() {
+        let mut tv = setup();
+        tv.start_search(Search::parse("apple", false, 0).unwrap())
+            .unwrap();
+        assert_eq!(tv.selected_index(), Some((2, 3)));
+        assert!(tv.is_search_match(5, 1));
+        assert!(!tv.is_search_match(1, 1));
+
+        tv.next_match().unwrap();
+        assert_eq!(tv.selected_index(), Some((5, 1)));
+        // wrap around
+        tv.next_match().unwrap();
+        assert_eq!(tv.selected_index(), Some((2, 3)));
+
+        tv.previous_match().unwrap();
+        assert_eq!(tv.selected_index(), Some((5, 1)));
+    }
+
+    #[test]
+    fn test_backward_search_in_column() {
+        let mut tv = setup();
+        tv.select_cell(9, 1).unwrap();
+        tv.start_search(Search::parse("\\c\\lapple", true, 1).unwrap())
+            .unwrap();
+        assert_eq!(tv.selected_index(), Some((5, 1)));
+
+        tv.next_match().unwrap();
+        assert_eq!(tv.selected_index(), Some((1, 1)));
+        assert!(!tv.is_search_match(2, 3));
+
+        let err = tv
+            .start_search(Search::parse("banana", false, 1).unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            StrataError::SearchNotFound("banana".to_string()).to_string()
+        );
+        assert_eq!(tv.selected_index(), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_preview_search() {
+        let mut tv = setup();
+        tv.preview_search(Search::parse("pine", false, 0).ok());
+        assert_eq!(tv.selected_index(), Some((2, 3)));
+        tv.preview_search(Search::parse("pie", false, 0).ok());
+        assert_eq!(tv.selected_index(), Some((5, 1)));
+
+        tv.cancel_search_preview();
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+        assert!(tv.search().is_none());
+    }
+}
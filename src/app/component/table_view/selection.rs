@@ -0,0 +1,263 @@
+use std::ops::RangeInclusive;
+
+use eyre::{OptionExt, Result};
+
+use crate::error::StrataError;
+
+use super::{TableEdit, TableView};
+
+/// What the visual selection spans between its anchor and the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Rectangle of cells
+    Cells,
+    /// Whole rows
+    Rows,
+    /// Whole columns
+    Columns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub anchor: (usize, usize),
+    pub mode: SelectionMode,
+}
+
+/// Rectangular block of cells, bounds included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRange {
+    pub rows: RangeInclusive<usize>,
+    pub cols: RangeInclusive<usize>,
+}
+
+impl CellRange {
+    pub fn new(from: (usize, usize), to: (usize, usize)) -> Self {
+        Self {
+            rows: from.0.min(to.0)..=from.0.max(to.0),
+            cols: from.1.min(to.1)..=from.1.max(to.1),
+        }
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows.contains(&row) && self.cols.contains(&col)
+    }
+}
+
+/// Change applied to every value of a range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Uppercase,
+    Lowercase,
+    Trim,
+}
+
+impl Transform {
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Transform::Uppercase => value.to_uppercase(),
+            Transform::Lowercase => value.to_lowercase(),
+            Transform::Trim => value.trim().to_string(),
+        }
+    }
+}
+
+impl TableView {
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    /// Anchor a visual selection at the cursor, or leave it when it already has this mode
+    ///
+    /// Switching to another mode keeps the anchor.
+    pub fn toggle_selection(&mut self, mode: SelectionMode) -> Result<&mut Self> {
+        match self.selection {
+            Some(selection) if selection.mode == mode => self.selection = None,
+            Some(selection) => self.selection = Some(Selection { mode, ..selection }),
+            None => {
+                let anchor = self
+                    .selected_index()
+                    .ok_or_eyre(StrataError::NoCellSelected)?;
+                self.selection = Some(Selection { anchor, mode });
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn clear_selection(&mut self) -> &mut Self {
+        self.selection = None;
+        self
+    }
+
+    /// Cells between the anchor and the cursor, if a visual selection is active
+    pub fn selected_range(&self) -> Option<CellRange> {
+        let selection = self.selection?;
+        let cursor = self.selected_index()?;
+        let mut range = CellRange::new(selection.anchor, cursor);
+        match selection.mode {
+            SelectionMode::Cells => {}
+            SelectionMode::Rows => range.cols = 0..=self.max_col_index(),
+            SelectionMode::Columns => range.rows = 0..=self.max_row_index(),
+        }
+        Some(range)
+    }
+
+    /// Cells an operation applies to: the visual selection, or else the selected cell
+    pub fn target_range(&self) -> Result<CellRange> {
+        if let Some(range) = self.selected_range() {
+            return Ok(range);
+        }
+        let cursor = self
+            .selected_index()
+            .ok_or_eyre(StrataError::NoCellSelected)?;
+        Ok(CellRange::new(cursor, cursor))
+    }
+
+    /// Whether the cell is shown as part of the visual selection
+    pub fn is_in_selection(&self, row: usize, col: usize) -> bool {
+        self.selected_range()
+            .is_some_and(|range| range.contains(row, col))
+    }
+
+    /// Set every visible cell of the range to the value as one edit and leave the visual
+    /// selection
+    pub fn fill_range(&mut self, range: &CellRange, value: &str) -> Result<&mut Self> {
+        self.transform_range(range, |_| value.to_string())
+    }
+
+    /// Rewrite every visible cell of the range as one edit and leave the visual selection
+    pub fn transform_range(
+        &mut self,
+        range: &CellRange,
+        transform: impl Fn(&str) -> String,
+    ) -> Result<&mut Self> {
+        self.is_valid_row_index(*range.rows.end())?;
+        self.is_valid_col_index(*range.cols.end())?;
+
+        let edits: Vec<TableEdit> = range
+            .rows
+            .clone()
+            .filter(|&row| self.is_row_visible(row))
+            .flat_map(|row| range.cols.clone().map(move |col| (row, col)))
+            .filter_map(|(row, col)| {
+                let old = self.rows[row].get(col)?;
+                let value = transform(old);
+                (value != *old).then_some(TableEdit::UpdateCell { row, col, value })
+            })
+            .collect();
+        self.selection = None;
+        if edits.is_empty() {
+            return Ok(self);
+        }
+        self.commit(TableEdit::Batch(edits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::component::table_view::SortKey;
+
+    use super::*;
+
+    #[test]
+    fn test_selected_range() {
+        let mut tv = TableView::new();
+        tv.select_cell(2, 3).unwrap();
+        assert_eq!(tv.selected_range(), None);
+        assert_eq!(tv.target_range().unwrap(), CellRange::new((2, 3), (2, 3)));
+
+        tv.toggle_selection(SelectionMode::Cells).unwrap();
+        tv.move_selector(-1, 2).unwrap();
+        assert_eq!(
+            tv.selected_range(),
+            Some(CellRange {
+                rows: 1..=2,
+                cols: 3..=5
+            })
+        );
+        assert!(tv.is_in_selection(1, 4));
+        assert!(!tv.is_in_selection(0, 4));
+
+        tv.toggle_selection(SelectionMode::Rows).unwrap();
+        assert_eq!(
+            tv.selected_range(),
+            Some(CellRange {
+                rows: 1..=2,
+                cols: 0..=9
+            })
+        );
+
+        tv.toggle_selection(SelectionMode::Columns).unwrap();
+        assert_eq!(
+            tv.selected_range(),
+            Some(CellRange {
+                rows: 0..=9,
+                cols: 3..=5
+            })
+        );
+
+        tv.toggle_selection(SelectionMode::Columns).unwrap();
+        assert_eq!(tv.selection(), None);
+    }
+
+    #[test]
+    fn test_fill_and_transform_range() {
+        let mut tv = TableView::new();
+        tv.toggle_selection(SelectionMode::Cells).unwrap();
+        tv.select_cell(1, 1).unwrap();
+        let range = tv.target_range().unwrap();
+
+        tv.fill_range(&range, " Value ").unwrap();
+        assert_eq!(tv.selection(), None);
+        assert_eq!(tv.rows[0][0], " Value ");
+        assert_eq!(tv.rows[1][1], " Value ");
+        assert_eq!(tv.rows[2][2], "");
+
+        tv.transform_range(&range, |value| Transform::Trim.apply(value))
+            .unwrap();
+        tv.transform_range(&range, |value| Transform::Uppercase.apply(value))
+            .unwrap();
+        assert_eq!(tv.rows[1][0], "VALUE");
+
+        // each operation is undone at once
+        tv.undo().unwrap().undo().unwrap();
+        assert_eq!(tv.rows[1][0], " Value ");
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[1][0], "");
+
+        let out_of_bounds = CellRange::new((0, 0), (10, 0));
+        assert!(tv.fill_range(&out_of_bounds, "x").is_err());
+    }
+
+    #[test]
+    fn test_structural_edits_leave_selection() {
+        let mut tv = TableView::new();
+        tv.update_cell(5, 0, "a").unwrap();
+        let select = |tv: &mut TableView| {
+            tv.select_cell(8, 8).unwrap();
+            tv.toggle_selection(SelectionMode::Cells).unwrap();
+            tv.select_cell(0, 0).unwrap();
+        };
+
+        select(&mut tv);
+        tv.collapse_row(9).unwrap();
+        assert_eq!(tv.selection(), None);
+        select(&mut tv);
+        tv.collapse_col(9).unwrap();
+        assert_eq!(tv.selection(), None);
+        select(&mut tv);
+        tv.move_col(0, 1).unwrap();
+        assert_eq!(tv.selection(), None);
+        select(&mut tv);
+        tv.sort_rows(&[SortKey::ascending(1)], false).unwrap();
+        assert_eq!(tv.rows[0][1], "a");
+        assert_eq!(tv.selection(), None);
+        select(&mut tv);
+        tv.undo().unwrap();
+        assert_eq!(tv.selection(), None);
+
+        // editing values keeps it
+        select(&mut tv);
+        tv.update_cell(0, 0, "b").unwrap();
+        assert!(tv.selection().is_some());
+    }
+}
@@ -0,0 +1,358 @@
+use std::{cmp::Ordering, ops::Range};
+
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+use super::{split_flags, ColumnType, TableEdit, TableView};
+
+/// Column to sort the rows by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub col: usize,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn ascending(col: usize) -> Self {
+        Self {
+            col,
+            descending: false,
+        }
+    }
+
+    pub fn descending(col: usize) -> Self {
+        Self {
+            col,
+            descending: true,
+        }
+    }
+
+    /// Compare two values of the column, keeping empty values last in both orders
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if self.descending => natural_cmp(b, a),
+            (false, false) => natural_cmp(a, b),
+        }
+    }
+}
+
+/// Parse the sort keys typed in the sort prompt, e.g. `2 desc, 0`
+///
+/// The keys may be followed by `\p` to keep the first row on top.
+pub fn parse_sort_keys(input: &str) -> Result<(Vec<SortKey>, bool)> {
+    let (keys, flags) = split_flags(input, &['p']);
+    let keys = keys
+        .split(',')
+        .map(|key| {
+            let mut words = key.split_whitespace();
+            let col = words
+                .next()
+                .and_then(|col| col.parse::<usize>().ok())
+                .ok_or_else(|| StrataError::StringParseError(key.trim().to_string()))?;
+            match (words.next(), words.next()) {
+                (None | Some("asc"), None) => Ok(SortKey::ascending(col)),
+                (Some("desc"), None) => Ok(SortKey::descending(col)),
+                _ => bail!(StrataError::StringParseError(key.trim().to_string())),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((keys, flags.contains(&'p')))
+}
+
+/// Compare numbers by value and put them before other values, which are compared by
+/// their runs of digits as numbers, so that "item9" comes before "item10"
+///
+/// This is a total order, as sorting requires: "1.5" and "1.10" are compared as numbers,
+/// but both come before "1.7x".
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    match (as_number(a), as_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y).then_with(|| chunks_cmp(a, b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => chunks_cmp(a, b),
+    }
+}
+
+fn as_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if value.is_empty() || !ColumnType::Decimal.accepts(value) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Compare the runs of digits as numbers and the other runs as text, then the values
+/// themselves
+fn chunks_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+    loop {
+        let ordering = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if is_digits(x) && is_digits(y) => {
+                let x = x.trim_start_matches('0');
+                let y = y.trim_start_matches('0');
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => x.cmp(y),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// Split the value into runs of digits and runs of other characters
+fn chunks(value: &str) -> impl Iterator<Item = &str> {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+fn is_digits(chunk: &str) -> bool {
+    chunk.starts_with(|c: char| c.is_ascii_digit())
+}
+
+impl TableView {
+    /// Stable sort of the rows by the keys, in order of priority
+    ///
+    /// When `pin_first_row` is set, the first row stays on top, e.g. a header that was
+    /// loaded as data.
+    pub fn sort_rows(&mut self, keys: &[SortKey], pin_first_row: bool) -> Result<&mut Self> {
+        let start = usize::from(pin_first_row).min(self.rows.len());
+        self.sort_rows_in(start..self.rows.len(), keys)
+    }
+
+    /// Stable sort of the rows in the range only, leaving the other rows in place
+    ///
+    /// Rows are compared by their computed values, so formulas sort by their results.
+    pub fn sort_rows_in(&mut self, rows: Range<usize>, keys: &[SortKey]) -> Result<&mut Self> {
+        for key in keys {
+            self.is_valid_col_index(key.col)?;
+        }
+        if rows.end > self.rows.len() {
+            bail!(StrataError::InvalidRowIndex {
+                max: self.max_row_index(),
+                requested: rows.end - 1,
+            });
+        }
+
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        let values: Vec<Vec<_>> = keys
+            .iter()
+            .map(|key| {
+                (0..self.rows.len())
+                    .map(|row| {
+                        if rows.contains(&row) {
+                            self.computed_value(row, key.col)
+                        } else {
+                            Default::default()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        order[rows].sort_by(|&a, &b| {
+            keys.iter()
+                .zip(&values)
+                .map(|(key, values)| key.compare(&values[a], &values[b]))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        drop(values);
+        if order.iter().enumerate().all(|(new, &old)| new == old) {
+            return Ok(self);
+        }
+        self.commit(TableEdit::ReorderRows(order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_sort_keys() {
+        assert_eq!(
+            parse_sort_keys("2 desc, 0 asc,1").unwrap(),
+            (
+                vec![
+                    SortKey::descending(2),
+                    SortKey::ascending(0),
+                    SortKey::ascending(1)
+                ],
+                false
+            )
+        );
+        assert_eq!(
+            parse_sort_keys("0 \\p").unwrap(),
+            (vec![SortKey::ascending(0)], true)
+        );
+        assert!(parse_sort_keys("").is_err());
+        assert!(parse_sort_keys("a").is_err());
+        assert!(parse_sort_keys("0 up").is_err());
+        assert!(parse_sort_keys("0 desc 1").is_err());
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("9", "10"), Ordering::Less);
+        assert_eq!(natural_cmp("-5", "3"), Ordering::Less);
+        assert_eq!(natural_cmp("1.5", "1.10"), Ordering::Greater);
+        assert_eq!(natural_cmp("item9", "item10"), Ordering::Less);
+        assert_eq!(natural_cmp("item010", "item9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a10b2", "a10b10"), Ordering::Less);
+        assert_eq!(natural_cmp("apple", "banana"), Ordering::Less);
+        assert_eq!(natural_cmp("item", "item1"), Ordering::Less);
+        assert_eq!(natural_cmp("007", "7"), Ordering::Less);
+        assert_eq!(natural_cmp("1e3", "999"), Ordering::Greater);
+        assert_eq!(natural_cmp("100", "item"), Ordering::Less);
+
+        // numbers and versions compare consistently
+        assert_eq!(natural_cmp("1.10", "1.7x"), Ordering::Less);
+        assert_eq!(natural_cmp("1.5", "1.7x"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_natural_cmp_total_order() {
+        // values such as `1.5`, `1.10x` or `12`, from a fixed pseudo-random sequence
+        let mut seed: u64 = 7;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let mut values: Vec<String> = (0..300)
+            .map(|_| {
+                let mut value = next(20).to_string();
+                if next(2) == 0 {
+                    value.push_str(&format!(".{}", next(20)));
+                }
+                match next(4) {
+                    0 => value.push('x'),
+                    1 => value.insert(0, '-'),
+                    _ => {}
+                }
+                value
+            })
+            .collect();
+
+        for a in &values[..40] {
+            for b in &values[..40] {
+                assert_eq!(natural_cmp(a, b), natural_cmp(b, a).reverse());
+                for c in &values[..40] {
+                    if natural_cmp(a, b).is_le() && natural_cmp(b, c).is_le() {
+                        assert!(natural_cmp(a, c).is_le(), "{} {} {}", a, b, c);
+                    }
+                }
+            }
+        }
+        values.sort_by(|a, b| natural_cmp(a, b));
+        assert!(values.windows(2).all(|w| natural_cmp(&w[0], &w[1]).is_le()));
+    }
+
+    fn column(tv: &TableView, col: usize) -> Vec<&str> {
+        tv.rows.iter().map(|row| row[col].as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+
+        tv.sort_rows(&[SortKey::ascending(1)], false).unwrap();
+        assert_eq!(column(&tv, 0), vec!["apple", "grape", "orange"]);
+
+        tv.sort_rows(&[SortKey::descending(0)], false).unwrap();
+        assert_eq!(column(&tv, 0), vec!["orange", "grape", "apple"]);
+
+        tv.undo().unwrap();
+        assert_eq!(column(&tv, 0), vec!["apple", "grape", "orange"]);
+        tv.undo().unwrap();
+        assert_eq!(column(&tv, 0), vec!["apple", "orange", "grape"]);
+
+        assert!(tv.sort_rows(&[SortKey::ascending(2)], false).is_err());
+    }
+
+    #[test]
+    fn test_sort_rows_multi_key() {
+        let mut tv = TableView::new();
+        let values = [("b", "2"), ("a", "10"), ("b", "1"), ("a", "9"), ("", "3")];
+        for (row, (x, y)) in values.iter().enumerate() {
+            tv.update_cell(row + 1, 0, x).unwrap();
+            tv.update_cell(row + 1, 1, y).unwrap();
+        }
+        tv.update_cell(0, 0, "key").unwrap();
+
+        tv.sort_rows(&[SortKey::ascending(0), SortKey::descending(1)], true)
+            .unwrap();
+        assert_eq!(
+            column(&tv, 0)[..6].to_vec(),
+            vec!["key", "a", "a", "b", "b", ""]
+        );
+        assert_eq!(
+            column(&tv, 1)[..6].to_vec(),
+            vec!["", "10", "9", "2", "1", "3"]
+        );
+
+        // stable: rows with equal keys keep their order
+        tv.sort_rows(&[SortKey::ascending(0)], true).unwrap();
+        assert_eq!(
+            column(&tv, 1)[..6].to_vec(),
+            vec!["", "10", "9", "2", "1", "3"]
+        );
+    }
+
+    #[test]
+    fn test_sort_rows_moves_irregular_rows() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/ragged.csv"), true).unwrap();
+        assert!(tv.is_irregular_row(1));
+        assert!(tv.is_irregular_row(2));
+
+        tv.sort_rows(&[SortKey::descending(0)], false).unwrap();
+        assert_eq!(column(&tv, 0), vec!["orange", "grape", "apple"]);
+        assert!(tv.is_irregular_row(0));
+        assert!(tv.is_irregular_row(1));
+        assert!(!tv.is_irregular_row(2));
+    }
+
+    #[test]
+    fn test_sort_rows_in() {
+        let mut tv = TableView::new();
+        for (row, value) in ["d", "c", "b", "a"].iter().enumerate() {
+            tv.update_cell(row, 0, value).unwrap();
+        }
+        tv.sort_rows_in(1..3, &[SortKey::ascending(0)]).unwrap();
+        assert_eq!(column(&tv, 0)[..4].to_vec(), vec!["d", "b", "c", "a"]);
+
+        assert!(tv.sort_rows_in(5..11, &[SortKey::ascending(0)]).is_err());
+    }
+
+    #[test]
+    fn test_sort_rows_by_computed_values() {
+        let mut tv = TableView::new();
+        for (row, value) in ["=1+1", "3", "=0+5"].iter().enumerate() {
+            tv.update_cell(row, 0, value).unwrap();
+        }
+        tv.sort_rows_in(0..3, &[SortKey::ascending(0)]).unwrap();
+        assert_eq!(column(&tv, 0)[..3].to_vec(), vec!["=1+1", "3", "=0+5"]);
+
+        tv.sort_rows_in(0..3, &[SortKey::descending(0)]).unwrap();
+        assert_eq!(column(&tv, 0)[..3].to_vec(), vec!["=0+5", "3", "=1+1"]);
+    }
+}
@@ -0,0 +1,236 @@
+use std::{cell::RefCell, ops::Range};
+
+use ratatui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+use super::TableView;
+
+/// Rows taken by the borders, the header with the column types and the margin below it
+const TABLE_CHROME_HEIGHT: u16 = 5;
+
+/// First row and column shown in the table area
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub row_offset: usize,
+    pub col_offset: usize,
+    /// Number of rows that fit in the table area
+    pub height: usize,
+}
+
+/// Display width of each column, including its header
+///
+/// Widths are kept up to date by the edits where that is cheap. When the widest cell of a
+/// column shrinks or disappears, the column is marked stale and measured again the next
+/// time its width is needed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ColumnWidths {
+    widths: RefCell<Vec<Option<usize>>>,
+}
+
+impl ColumnWidths {
+    pub fn new(col_count: usize) -> Self {
+        Self {
+            widths: RefCell::new(vec![None; col_count]),
+        }
+    }
+
+    pub fn insert_col(&self, col: usize) {
+        self.widths.borrow_mut().insert(col, None);
+    }
+
+    pub fn remove_col(&self, col: usize) {
+        self.widths.borrow_mut().remove(col);
+    }
+
+    /// A cell of the column now holds a value as wide as `width`
+    pub fn grow(&self, col: usize, width: usize) {
+        if let Some(Some(w)) = self.widths.borrow_mut().get_mut(col) {
+            *w = (*w).max(width);
+        }
+    }
+
+    /// A cell of the column as wide as `width` has been removed or overwritten
+    pub fn shrink(&self, col: usize, width: usize) {
+        if let Some(w) = self.widths.borrow_mut().get_mut(col) {
+            if *w == Some(width) {
+                *w = None;
+            }
+        }
+    }
+
+    pub fn update(&self, col: usize, old_width: usize, new_width: usize) {
+        self.shrink(col, old_width);
+        self.grow(col, new_width);
+    }
+
+    /// Measure the column again the next time its width is needed
+    pub fn invalidate(&self, col: usize) {
+        if let Some(w) = self.widths.borrow_mut().get_mut(col) {
+            *w = None;
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        self.widths.borrow_mut().fill(None);
+    }
+}
+
+impl TableView {
+    pub fn viewport(&self) -> Viewport {
+        self.viewport.get()
+    }
+
+    /// Display width of the column, measured again only when it is stale
+    pub fn column_width(&self, col: usize) -> usize {
+        if let Some(Some(width)) = self.column_widths.widths.borrow().get(col) {
+            return *width;
+        }
+
+        let width = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(row, values)| match values.get(col) {
+                Some(_) => self.shown_value(row, col).width(),
+                None => 0,
+            })
+            .chain(self.header.get(col).map(|header| header.width()))
+            .chain(std::iter::once(self.column_label(col).len()))
+            .max()
+            .unwrap_or(0);
+        if let Some(w) = self.column_widths.widths.borrow_mut().get_mut(col) {
+            *w = Some(width);
+        }
+        width
+    }
+
+    /// Display width of a value of the column, once formatted
+    pub fn cell_width(&self, col: usize, value: &str) -> usize {
+        self.display_value(col, value).width()
+    }
+
+    /// Width of the column showing row numbers
+    pub fn index_width(&self) -> usize {
+        self.rows.len().saturating_sub(1).to_string().len()
+    }
+
+    /// Scroll the viewport so that the selected cell fits in the area and return the rows
+    /// and columns to render
+    ///
+    /// Rows are given as positions among the visible rows, see `row_at`.
+    pub fn scroll_to_selection(&self, area: Rect) -> (Range<usize>, Range<usize>) {
+        let height = area.height.saturating_sub(TABLE_CHROME_HEIGHT) as usize;
+        let available_width =
+            (area.width.saturating_sub(2) as usize).saturating_sub(self.index_width() + 1);
+        let (selected_row, selected_col) = self.selected_index().unwrap_or((0, 0));
+        let selected_row = self.row_position(selected_row);
+        let row_count = self.visible_row_count();
+        let mut viewport = self.viewport.get();
+        viewport.height = height;
+
+        // rows
+        if selected_row < viewport.row_offset {
+            viewport.row_offset = selected_row;
+        } else if height > 0 && selected_row >= viewport.row_offset + height {
+            viewport.row_offset = selected_row + 1 - height;
+        }
+        viewport.row_offset = viewport
+            .row_offset
+            .min(row_count.saturating_sub(height.max(1)));
+        let rows = viewport.row_offset..(viewport.row_offset + height).min(row_count);
+
+        // columns
+        if selected_col < viewport.col_offset {
+            viewport.col_offset = selected_col;
+        }
+        while viewport.col_offset < selected_col
+            && self.span_width(viewport.col_offset..selected_col + 1) > available_width
+        {
+            viewport.col_offset += 1;
+        }
+        viewport.col_offset = viewport.col_offset.min(self.header.len().saturating_sub(1));
+        let mut col_end = viewport.col_offset;
+        while col_end < self.header.len()
+            && (col_end == viewport.col_offset
+                || self.span_width(viewport.col_offset..col_end + 1) <= available_width)
+        {
+            col_end += 1;
+        }
+        let cols = viewport.col_offset..col_end;
+
+        self.viewport.set(viewport);
+        (rows, cols)
+    }
+
+    /// Width taken by the columns, including the spacing between them
+    fn span_width(&self, cols: Range<usize>) -> usize {
+        let spacing = cols.len().saturating_sub(1);
+        cols.map(|col| self.column_width(col)).sum::<usize>() + spacing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_width_cache() {
+        let mut tv = TableView::new();
+        assert_eq!(tv.column_width(0), "header0".len());
+
+        tv.update_cell(3, 0, "a very long value").unwrap();
+        assert_eq!(tv.column_width(0), "a very long value".len());
+
+        tv.update_cell(3, 0, "short").unwrap();
+        assert_eq!(tv.column_width(0), "header0".len());
+
+        tv.update_cell(3, 0, "全角文字").unwrap();
+        assert_eq!(tv.column_width(0), 8);
+
+        tv.collapse_row(3).unwrap();
+        assert_eq!(tv.column_width(0), "header0".len());
+
+        tv.undo().unwrap();
+        assert_eq!(tv.column_width(0), 8);
+
+        tv.collapse_col(0).unwrap();
+        assert_eq!(tv.column_width(0), "header1".len());
+    }
+
+    #[test]
+    fn test_scroll_to_selection() {
+        let mut tv = TableView::new();
+        for _ in 0..90 {
+            tv.expand_row().unwrap();
+        }
+        // 10 rows and 3 columns of "header0" fit in the area
+        let area = Rect::new(0, 0, 2 + 3 + 3 * 8, 15);
+
+        let (rows, cols) = tv.scroll_to_selection(area);
+        assert_eq!(rows, 0..10);
+        assert_eq!(cols, 0..3);
+
+        tv.select_cell(50, 5).unwrap();
+        let (rows, cols) = tv.scroll_to_selection(area);
+        assert_eq!(rows, 41..51);
+        assert_eq!(cols, 3..6);
+
+        tv.select_cell(45, 4).unwrap();
+        let (rows, cols) = tv.scroll_to_selection(area);
+        assert_eq!(rows, 41..51);
+        assert_eq!(cols, 3..6);
+
+        tv.select_cell(0, 0).unwrap();
+        let (rows, cols) = tv.scroll_to_selection(area);
+        assert_eq!(rows, 0..10);
+        assert_eq!(cols, 0..3);
+        assert_eq!(
+            tv.viewport(),
+            Viewport {
+                row_offset: 0,
+                col_offset: 0,
+                height: 10
+            }
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use eyre::Result;
+
+use super::{CellRange, TableEdit, TableView};
+
+impl TableView {
+    /// Values of the visible rows of the range
+    pub fn yank_range(&self, range: &CellRange) -> Vec<Vec<String>> {
+        range
+            .rows
+            .clone()
+            .filter(|&row| self.is_row_visible(row))
+            .filter_map(|row| self.rows.get(row))
+            .map(|values| {
+                range
+                    .cols
+                    .clone()
+                    .map(|col| values.get(col).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Write the block with its top-left corner at `at` over the visible rows, adding the
+    /// rows and columns it overflows to, as one edit
+    pub fn paste_block(&mut self, at: (usize, usize), values: &[Vec<String>]) -> Result<&mut Self> {
+        let (top, left) = at;
+        self.is_valid_row_index(top)?;
+        self.is_valid_col_index(left)?;
+
+        // rows past the last visible one are added after the last row
+        let position = self.row_position(top);
+        let remaining = self.visible_row_count().saturating_sub(position);
+        let targets: Vec<usize> = (0..values.len())
+            .map(|i| match self.row_at(position + i) {
+                Some(row) => row,
+                None => self.rows.len() + i - remaining,
+            })
+            .collect();
+
+        let block_width = values.iter().map(Vec::len).max().unwrap_or(0);
+        let col_count = self.header.len().max(left + block_width);
+        let row_count = targets.last().map_or(0, |row| row + 1).max(self.rows.len());
+
+        let mut edits: Vec<TableEdit> = (self.header.len()..col_count)
+            .map(|col| TableEdit::InsertCol {
+                col,
+                header: format!("header{}", col),
+                values: vec![String::new(); self.rows.len()],
+            })
+            .collect();
+        edits.extend(
+            (self.rows.len()..row_count).map(|row| TableEdit::InsertRow {
+                row,
+                values: vec![String::new(); col_count],
+            }),
+        );
+        let rows = &self.rows;
+        edits.extend(values.iter().zip(targets).flat_map(|(values, row)| {
+            values.iter().enumerate().filter_map(move |(j, value)| {
+                let col = left + j;
+                let unchanged = rows
+                    .get(row)
+                    .and_then(|r| r.get(col))
+                    .map_or(value.is_empty(), |old| old == value);
+                (!unchanged).then(|| TableEdit::UpdateCell {
+                    row,
+                    col,
+                    value: value.clone(),
+                })
+            })
+        }));
+        if edits.is_empty() {
+            return Ok(self);
+        }
+        self.commit(TableEdit::Batch(edits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::app::component::table_view::RowFilter;
+
+    #[test]
+    fn test_yank_and_paste() {
+        let mut tv = TableView::new();
+        tv.update_cell(0, 0, "a").unwrap();
+        tv.update_cell(1, 1, "b").unwrap();
+        let block = tv.yank_range(&CellRange::new((0, 0), (1, 1)));
+        assert_eq!(
+            block,
+            vec![
+                vec!["a".to_string(), "".to_string()],
+                vec!["".to_string(), "b".to_string()]
+            ]
+        );
+
+        tv.paste_block((5, 5), &block).unwrap();
+        assert_eq!(tv.rows[5][5], "a");
+        assert_eq!(tv.rows[6][6], "b");
+
+        tv.undo().unwrap();
+        assert_eq!(tv.rows[5][5], "");
+    }
+
+    #[test]
+    fn test_paste_expands_table() {
+        let mut tv = TableView::new();
+        let block = vec![
+            vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            vec!["4".to_string()],
+        ];
+        tv.paste_block((9, 8), &block).unwrap();
+        assert_eq!(tv.rows.len(), 11);
+        assert_eq!(tv.header.len(), 11);
+        assert_eq!(tv.header[10], "header10");
+        assert_eq!(tv.rows[9][8..], ["1", "2", "3"]);
+        assert_eq!(tv.rows[10][8..], ["4", "", ""]);
+        assert!(tv.rows.iter().all(|row| row.len() == 11));
+
+        // growing the table is undone with the paste
+        tv.undo().unwrap();
+        assert_eq!(tv.rows.len(), 10);
+        assert_eq!(tv.header.len(), 10);
+    }
+
+    #[test]
+    fn test_paste_filtered() {
+        let mut tv = TableView::from_csv(Path::new("tests/data/fluits.csv"), true).unwrap();
+        tv.expand_row().unwrap();
+        tv.update_cell(3, 0, "melon").unwrap();
+        tv.set_filter(Some(RowFilter::parse("1 > 120").unwrap()))
+            .unwrap();
+        let block = vec![
+            vec!["a".to_string()],
+            vec!["b".to_string()],
+            vec!["c".to_string()],
+        ];
+
+        // the hidden rows are skipped and the rest added after the last row
+        tv.paste_block((1, 0), &block).unwrap();
+        assert_eq!(tv.rows.len(), 5);
+        let names: Vec<&str> = tv.rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(names, ["apple", "a", "b", "melon", "c"]);
+
+        tv.undo().unwrap();
+        assert_eq!(tv.rows.len(), 4);
+        assert_eq!(tv.rows[1][0], "orange");
+    }
+}
@@ -1,5 +1,10 @@
-#[derive(Debug, Clone, PartialEq)]
+use std::fmt;
+
+use super::keymap::{Action, Keymap, KeymapMode};
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum DisplayFocus {
+    #[default]
     TableSelector,
     TableView,
     FileView,
@@ -8,22 +13,17 @@ pub enum DisplayFocus {
     Exit(Box<DisplayFocus>),
 }
 
-impl Default for DisplayFocus {
-    fn default() -> Self {
-        DisplayFocus::TableSelector
-    }
-}
-
-impl ToString for DisplayFocus {
-    fn to_string(&self) -> String {
-        match self {
-            DisplayFocus::TableSelector => "TableList".to_string(),
-            DisplayFocus::TableView => "TableView".to_string(),
-            DisplayFocus::FileView => "FileView".to_string(),
-            DisplayFocus::Command(_) => "Command".to_string(),
-            DisplayFocus::Error(_) => "Error".to_string(),
-            DisplayFocus::Exit(_) => "Exit".to_string(),
-        }
+impl fmt::Display for DisplayFocus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DisplayFocus::TableSelector => "TableList",
+            DisplayFocus::TableView => "TableView",
+            DisplayFocus::FileView => "FileView",
+            DisplayFocus::Command(_) => "Command",
+            DisplayFocus::Error(_) => "Error",
+            DisplayFocus::Exit(_) => "Exit",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -37,29 +37,73 @@ impl DisplayFocus {
         }
     }
 
-    pub fn get_guide(&self) -> String {
-        match self {
-            DisplayFocus::TableSelector => {
-                "<a> Add new | <o> Open file browser | <J> Jump | <d> Delete table | <q> Quit app"
-                    .to_string()
-            }
-            DisplayFocus::TableView => {
-                "<r> Add new row | <e> Edit cell | <E> Edit header | <d> Delete cell | <J> Jump"
-                    .to_string()
-            }
-            DisplayFocus::FileView => {
-                "<Enter> Open file | <h/j/k/l> Navigate | <..> Go up | <q> Back"
-                    .to_string()
-            }
-            DisplayFocus::Command(_) => "<Enter> Submit | <Esc> Cancel".to_string(),
-            DisplayFocus::Error(_) => " <Enter> Exit".to_string(),
-            DisplayFocus::Exit(_) => " <Enter> Exit | <Esc> Cancel".to_string(),
-        }
+    /// Footer listing the main actions with the keys the keymap binds to them
+    pub fn get_guide(&self, keymap: &Keymap) -> String {
+        let entries: &[(&[Action], &str)] = match self {
+            DisplayFocus::TableSelector => &[
+                (&[Action::AddTable], "Add new"),
+                (&[Action::OpenFileView], "Open file browser"),
+                (&[Action::JumpCell], "Jump"),
+                (&[Action::RemoveTable], "Delete table"),
+                (&[Action::Undo], "Undo"),
+                (&[Action::CommandLine], "Command"),
+                (&[Action::Quit], "Quit app"),
+            ],
+            DisplayFocus::TableView => &[
+                (&[Action::AddRow], "Add new row"),
+                (&[Action::EditCell], "Edit cell"),
+                (&[Action::EditHeader], "Edit header"),
+                (&[Action::ClearCells], "Delete cell"),
+                (&[Action::Undo], "Undo"),
+                (&[Action::Search], "Search"),
+                (&[Action::JumpTable], "Jump"),
+                (&[Action::CommandLine], "Command"),
+            ],
+            DisplayFocus::FileView => &[
+                (&[Action::SelectFile], "Open file"),
+                (
+                    &[
+                        Action::MoveLeft,
+                        Action::MoveDown,
+                        Action::MoveUp,
+                        Action::MoveRight,
+                    ],
+                    "Navigate",
+                ),
+                (&[Action::Cancel], "Back"),
+            ],
+            DisplayFocus::Command(_) => &[
+                (&[Action::Submit], "Submit"),
+                (&[Action::Complete], "Complete"),
+                (&[Action::HistoryOlder, Action::HistoryNewer], "History"),
+                (&[Action::Cancel], "Cancel"),
+            ],
+            DisplayFocus::Error(_) => &[(&[Action::Cancel], "Exit")],
+            DisplayFocus::Exit(_) => &[
+                (&[Action::Exit], "Exit"),
+                (&[Action::SaveAllAndExit], "Save all and exit"),
+                (&[Action::DiscardAndExit], "Discard and exit"),
+                (&[Action::Cancel], "Cancel"),
+            ],
+        };
+
+        let mode = KeymapMode::from(self);
+        entries
+            .iter()
+            .filter_map(|(actions, label)| {
+                keymap
+                    .keys_label(mode, actions)
+                    .map(|keys| format!("<{}> {}", keys, label))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::app::keymap::Key;
+
     use super::*;
 
     #[test]
@@ -87,4 +131,28 @@ mod tests {
             DisplayFocus::TableSelector
         );
     }
+
+    #[test]
+    fn test_guide_follows_keymap() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            DisplayFocus::FileView.get_guide(&keymap),
+            "<Enter> Open file | <h/j/k/l> Navigate | <q> Back"
+        );
+
+        keymap.bind(
+            KeymapMode::FileView,
+            Key::parse_sequence("<C-o>").unwrap(),
+            Some(Action::SelectFile),
+        );
+        keymap.bind(
+            KeymapMode::FileView,
+            Key::parse_sequence("q").unwrap(),
+            None,
+        );
+        assert_eq!(
+            DisplayFocus::FileView.get_guide(&keymap),
+            "<C-o> Open file | <h/j/k/l> Navigate | <Esc> Back"
+        );
+    }
 }
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// Undo/redo stacks of reversible edits
+///
+/// The owner applies an edit and records the edit that reverts it. On undo, the owner
+/// takes the edit out with `take_undo`, applies it and hands the resulting inverse back
+/// with `push_undone` so that it can be redone (and vice versa for redo).
+#[derive(Debug, Clone, PartialEq)]
+pub struct History<T> {
+    undo_stack: VecDeque<T>,
+    redo_stack: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Record a new edit and discard everything that could have been redone
+    pub fn record(&mut self, edit: T) -> &mut Self {
+        self.redo_stack.clear();
+        self.push_redone(edit)
+    }
+
+    pub fn take_undo(&mut self) -> Option<T> {
+        self.undo_stack.pop_back()
+    }
+
+    pub fn take_redo(&mut self) -> Option<T> {
+        self.redo_stack.pop()
+    }
+
+    /// Hand back an edit that has just been reverted
+    pub fn push_undone(&mut self, edit: T) -> &mut Self {
+        self.redo_stack.push(edit);
+        self
+    }
+
+    /// Hand back an edit that has just been (re)applied
+    pub fn push_redone(&mut self, edit: T) -> &mut Self {
+        if self.undo_stack.len() >= HISTORY_LIMIT {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(edit);
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_undo() {
+        let mut history = History::new();
+        history.record(1).record(2);
+
+        let edit = history.take_undo().unwrap();
+        assert_eq!(edit, 2);
+        history.push_undone(edit);
+        assert!(history.can_undo());
+        assert!(history.can_redo());
+
+        let edit = history.take_redo().unwrap();
+        assert_eq!(edit, 2);
+        history.push_redone(edit);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = History::new();
+        history.record(1);
+        let edit = history.take_undo().unwrap();
+        history.push_undone(edit);
+
+        history.record(3);
+        assert!(!history.can_redo());
+        assert_eq!(history.take_undo(), Some(3));
+        assert_eq!(history.take_undo(), None);
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut history = History::new();
+        for i in 0..(HISTORY_LIMIT + 1) {
+            history.record(i);
+        }
+
+        let mut count = 0;
+        while history.take_undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, HISTORY_LIMIT);
+    }
+}
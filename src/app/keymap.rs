@@ -0,0 +1,560 @@
+mod action;
+mod key;
+
+use std::{collections::HashMap, fs, io, path::Path, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use eyre::Result;
+
+use crate::{error::StrataError, message::Message};
+
+use super::display_focus::DisplayFocus;
+
+pub use action::Action;
+pub use key::Key;
+
+/// Set of bindings that apply to a display focus, named like the sections of the
+/// keymap file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeymapMode {
+    TableSelector,
+    TableView,
+    FileView,
+    Command,
+    Error,
+    Exit,
+}
+
+impl KeymapMode {
+    pub const ALL: [KeymapMode; 6] = [
+        KeymapMode::TableSelector,
+        KeymapMode::TableView,
+        KeymapMode::FileView,
+        KeymapMode::Command,
+        KeymapMode::Error,
+        KeymapMode::Exit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeymapMode::TableSelector => "table_selector",
+            KeymapMode::TableView => "table_view",
+            KeymapMode::FileView => "file_view",
+            KeymapMode::Command => "command",
+            KeymapMode::Error => "error",
+            KeymapMode::Exit => "exit",
+        }
+    }
+}
+
+impl From<&DisplayFocus> for KeymapMode {
+    fn from(focus: &DisplayFocus) -> Self {
+        match focus {
+            DisplayFocus::TableSelector => KeymapMode::TableSelector,
+            DisplayFocus::TableView => KeymapMode::TableView,
+            DisplayFocus::FileView => KeymapMode::FileView,
+            DisplayFocus::Command(_) => KeymapMode::Command,
+            DisplayFocus::Error(_) => KeymapMode::Error,
+            DisplayFocus::Exit(_) => KeymapMode::Exit,
+        }
+    }
+}
+
+/// Keys bound by default, written as in the keymap file
+///
+/// The first keys of an action are the ones shown in the footer.
+const DEFAULT_BINDINGS: &[(KeymapMode, &str, Action)] = {
+    use Action::*;
+    use KeymapMode::{Command, Error, FileView, TableSelector, TableView};
+    &[
+        (TableSelector, "<Enter>", SelectTable),
+        (TableSelector, "<Esc>", Cancel),
+        (TableSelector, "q", Quit),
+        (TableSelector, "a", AddTable),
+        (TableSelector, "o", OpenFileView),
+        (TableSelector, "d", RemoveTable),
+        (TableSelector, "e", EditTableName),
+        (TableSelector, "J", JumpCell),
+        (TableSelector, "u", Undo),
+        (TableSelector, "<C-r>", Redo),
+        (TableSelector, "s", Save),
+        (TableSelector, "S", SaveAs),
+        (TableSelector, "W", SaveAll),
+        (TableSelector, ":", CommandLine),
+        (TableSelector, "j", MoveDown),
+        (TableSelector, "<Down>", MoveDown),
+        (TableSelector, "k", MoveUp),
+        (TableSelector, "<Up>", MoveUp),
+        (TableSelector, "l", MoveRight),
+        (TableSelector, "<Right>", MoveRight),
+        (TableSelector, "<Left>", MoveLeft),
+        (TableView, "<Esc>", Cancel),
+        (TableView, "q", Cancel),
+        (TableView, "r", AddRow),
+        (TableView, "R", DeleteRow),
+        (TableView, "c", AddColumn),
+        (TableView, "C", DeleteColumn),
+        (TableView, "e", EditCell),
+        (TableView, "a", EditCell),
+        (TableView, "<Enter>", EditCell),
+        (TableView, "E", EditHeader),
+        (TableView, "d", ClearCells),
+        (TableView, "u", Undo),
+        (TableView, "<C-r>", Redo),
+        (TableView, "/", Search),
+        (TableView, "?", SearchBackward),
+        (TableView, "n", NextMatch),
+        (TableView, "N", PreviousMatch),
+        (TableView, "F", Replace),
+        (TableView, "o", InsertRowBelow),
+        (TableView, "O", InsertRowAbove),
+        (TableView, "gi", InsertColumnLeft),
+        (TableView, "ga", InsertColumnRight),
+        (TableView, "D", DuplicateRow),
+        (TableView, "<A-k>", MoveRowUp),
+        (TableView, "<A-j>", MoveRowDown),
+        (TableView, "<A-h>", MoveColumnLeft),
+        (TableView, "<A-l>", MoveColumnRight),
+        (TableView, "gs", Sort),
+        (TableView, "gr", ToggleRawDisplay),
+        (TableView, "gc", ToggleColumnStats),
+        (TableView, "f", Filter),
+        (TableView, "v", VisualSelect),
+        (TableView, "V", VisualSelectRows),
+        (TableView, "<C-v>", VisualSelectColumns),
+        (TableView, "I", Fill),
+        (TableView, "U", Uppercase),
+        (TableView, "L", Lowercase),
+        (TableView, "T", Trim),
+        (TableView, "\"", SelectRegister),
+        (TableView, "y", Yank),
+        (TableView, "p", Paste),
+        (TableView, "=", Normalize),
+        (TableView, "J", JumpTable),
+        (TableView, ":", CommandLine),
+        (TableView, "s", Save),
+        (TableView, "S", SaveAs),
+        (TableView, "W", SaveAll),
+        (TableView, "gg", FirstRow),
+        (TableView, "G", LastRow),
+        (TableView, "0", FirstColumn),
+        (TableView, "$", LastColumn),
+        (TableView, "<C-d>", HalfPageDown),
+        (TableView, "<C-u>", HalfPageUp),
+        (TableView, "w", NextNonEmpty),
+        (TableView, "b", PreviousNonEmpty),
+        (TableView, "}", NextBlock),
+        (TableView, "{", PreviousBlock),
+        (TableView, "h", MoveLeft),
+        (TableView, "<Left>", MoveLeft),
+        (TableView, "j", MoveDown),
+        (TableView, "<Down>", MoveDown),
+        (TableView, "k", MoveUp),
+        (TableView, "<Up>", MoveUp),
+        (TableView, "l", MoveRight),
+        (TableView, "<Right>", MoveRight),
+        (TableView, "<Tab>", MoveRight),
+        (FileView, "<Enter>", SelectFile),
+        (FileView, "q", Cancel),
+        (FileView, "<Esc>", Cancel),
+        (FileView, "h", MoveLeft),
+        (FileView, "<Left>", MoveLeft),
+        (FileView, "j", MoveDown),
+        (FileView, "<Down>", MoveDown),
+        (FileView, "k", MoveUp),
+        (FileView, "<Up>", MoveUp),
+        (FileView, "l", MoveRight),
+        (FileView, "<Right>", MoveRight),
+        (Command, "<Enter>", Submit),
+        (Command, "<Esc>", Cancel),
+        (Command, "<Tab>", Complete),
+        (Command, "<S-Tab>", CompleteBackward),
+        (Command, "<Up>", HistoryOlder),
+        (Command, "<Down>", HistoryNewer),
+        (Command, "<Backspace>", Backspace),
+        (Command, "<Delete>", DeleteChar),
+        (Command, "<Left>", CursorLeft),
+        (Command, "<Right>", CursorRight),
+        (Command, "<C-Left>", CursorWordLeft),
+        (Command, "<C-Right>", CursorWordRight),
+        (Command, "<Home>", CursorHome),
+        (Command, "<C-a>", CursorHome),
+        (Command, "<End>", CursorEnd),
+        (Command, "<C-e>", CursorEnd),
+        (Command, "<C-w>", DeleteWord),
+        (Command, "<C-u>", DeleteToStart),
+        (Error, "<Enter>", Cancel),
+        (Error, "<Esc>", Cancel),
+        (KeymapMode::Exit, "<Enter>", Exit),
+        (KeymapMode::Exit, "q", Exit),
+        (KeymapMode::Exit, "s", SaveAllAndExit),
+        (KeymapMode::Exit, "d", DiscardAndExit),
+        (KeymapMode::Exit, "<Esc>", Cancel),
+    ]
+};
+
+/// Keys bound to an action
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<Key>,
+    pub action: Action,
+}
+
+/// Bindings of every mode, and the count and keys of a sequence being typed
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeymapMode, Vec<Binding>>,
+    pending: Vec<Key>,
+    pending_mode: Option<KeymapMode>,
+    count: Option<usize>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+            pending: Vec::new(),
+            pending_mode: None,
+            count: None,
+        };
+        for &(mode, keys, action) in DEFAULT_BINDINGS {
+            let keys = Key::parse_sequence(keys).expect("default keys are valid");
+            keymap
+                .bindings
+                .entry(mode)
+                .or_default()
+                .push(Binding { keys, action });
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// `$XDG_CONFIG_HOME/strata/keymap.toml`, or `~/.config/strata/keymap.toml`
+    pub fn default_file_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("strata").join("keymap.toml"))
+    }
+
+    /// Load the default bindings overridden by the ones of the file, if it exists
+    pub fn load(file_path: &Path) -> Result<Self> {
+        match fs::read_to_string(file_path) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Override the default bindings with a keymap file such as
+    ///
+    /// ```toml
+    /// [table_view]
+    /// dd = "delete_row"
+    /// d = "none"
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let invalid = |reason: String| StrataError::InvalidKeymap(reason);
+        let table: toml::Table = content.parse().map_err(|e| invalid(format!("{}", e)))?;
+
+        let mut keymap = Self::default();
+        for (section, bindings) in table {
+            let mode = KeymapMode::ALL
+                .into_iter()
+                .find(|mode| mode.name() == section)
+                .ok_or_else(|| invalid(format!("unknown section [{}]", section)))?;
+            let bindings = bindings
+                .as_table()
+                .ok_or_else(|| invalid(format!("[{}] is not a table", section)))?;
+            for (keys, action) in bindings {
+                let action = action
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("action of {} is not a string", keys)))?;
+                let action = match action {
+                    "none" => None,
+                    name => Some(
+                        Action::from_name(name)
+                            .ok_or_else(|| invalid(format!("unknown action {}", name)))?,
+                    ),
+                };
+                keymap.bind(mode, Key::parse_sequence(keys)?, action);
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn bindings(&self, mode: KeymapMode) -> &[Binding] {
+        self.bindings.get(&mode).map_or(&[], Vec::as_slice)
+    }
+
+    /// Bind the keys to the action in place of what they did, or unbind them
+    ///
+    /// The new keys come first when the keys of the action are shown.
+    pub fn bind(&mut self, mode: KeymapMode, keys: Vec<Key>, action: Option<Action>) -> &mut Self {
+        let bindings = self.bindings.entry(mode).or_default();
+        bindings.retain(|binding| binding.keys != keys);
+        if let Some(action) = action {
+            bindings.insert(0, Binding { keys, action });
+        }
+        self
+    }
+
+    /// Message for a key pressed with this focus
+    ///
+    /// A key starting a longer sequence waits for the next ones, and a key that breaks a
+    /// sequence is looked up on its own. A binding is found as soon as its keys are
+    /// typed, so a sequence cannot start with a key bound on its own. Unbound characters
+    /// are typed into the command input.
+    ///
+    /// In the table view, digits typed before the keys make a count, e.g. `20j`. `0`
+    /// only adds to a count that has been started.
+    pub fn message(&mut self, focus: &DisplayFocus, key: Key) -> Message {
+        let mode = KeymapMode::from(focus);
+        if self.pending_mode != Some(mode) {
+            self.pending.clear();
+            self.count = None;
+            self.pending_mode = Some(mode);
+        }
+        if let Some(count) = self.count_digit(mode, key) {
+            self.count = Some(count);
+            return Message::NoOp;
+        }
+        self.pending.push(key);
+
+        loop {
+            let bindings = self.bindings.get(&mode).map_or(&[][..], Vec::as_slice);
+            if let Some(binding) = bindings.iter().find(|b| b.keys == self.pending) {
+                self.pending.clear();
+                return binding.action.message(self.count.take());
+            }
+            if bindings.iter().any(|b| b.keys.starts_with(&self.pending)) {
+                return Message::NoOp;
+            }
+            if self.pending.len() == 1 {
+                break;
+            }
+            self.pending = vec![key];
+        }
+
+        self.pending.clear();
+        self.count = None;
+        match key.code {
+            KeyCode::Char(c)
+                if mode == KeymapMode::Command
+                    && !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                Message::Input(c)
+            }
+            _ => Message::NoOp,
+        }
+    }
+
+    /// Count with the digit added, when the key is a digit typed before a sequence
+    ///
+    /// A digit bound on its own is only read as part of a count that has been started.
+    fn count_digit(&self, mode: KeymapMode, key: Key) -> Option<usize> {
+        if mode != KeymapMode::TableView || !self.pending.is_empty() || !key.modifiers.is_empty() {
+            return None;
+        }
+        let digit = match key.code {
+            KeyCode::Char(c) => c.to_digit(10)? as usize,
+            _ => return None,
+        };
+        match self.count {
+            Some(count) => Some(count.saturating_mul(10).saturating_add(digit)),
+            None if self.bindings(mode).iter().any(|b| b.keys == [key]) => None,
+            None => Some(digit),
+        }
+    }
+
+    /// First keys of each action that is bound, e.g. `h/j/k/l`
+    pub fn keys_label(&self, mode: KeymapMode, actions: &[Action]) -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| {
+                self.bindings(mode)
+                    .iter()
+                    .find(|binding| binding.action == *action)
+            })
+            .map(|binding| binding.keys.iter().map(Key::to_string).collect())
+            .collect();
+        (!keys.is_empty()).then(|| keys.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::component::table_view::{Motion, SelectionMode},
+        message::MoveDirection,
+    };
+
+    use super::*;
+
+    fn find_action(keymap: &Keymap, mode: KeymapMode, keys: &str) -> Option<Action> {
+        let keys = Key::parse_sequence(keys).unwrap();
+        keymap
+            .bindings(mode)
+            .iter()
+            .find(|binding| binding.keys == keys)
+            .map(|binding| binding.action)
+    }
+
+    fn press(keymap: &mut Keymap, focus: &DisplayFocus, keys: &str) -> Vec<Message> {
+        Key::parse_sequence(keys)
+            .unwrap()
+            .into_iter()
+            .map(|key| keymap.message(focus, key))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let mut keymap = Keymap::default();
+        let messages = press(&mut keymap, &DisplayFocus::TableView, "<C-v>v<C-r>");
+        assert!(matches!(
+            messages.as_slice(),
+            [
+                Message::VisualSelect(SelectionMode::Columns),
+                Message::VisualSelect(SelectionMode::Cells),
+                Message::Redo,
+            ]
+        ));
+
+        let focus = DisplayFocus::Command(Box::new(DisplayFocus::TableView));
+        let messages = press(&mut keymap, &focus, "q<C-x><Enter>");
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::Input('q'), Message::NoOp, Message::ExecuteCommand]
+        ));
+    }
+
+    #[test]
+    fn test_key_sequence() {
+        let mut keymap = Keymap::parse(
+            r#"
+            [table_view]
+            d = "none"
+            dd = "delete_row"
+            "<C-d>" = "none"
+            "<C-d>x" = "clear_cells"
+            "#,
+        )
+        .unwrap();
+        let focus = DisplayFocus::TableView;
+
+        let messages = press(&mut keymap, &focus, "ddd");
+        assert!(matches!(
+            messages.as_slice(),
+            [Message::NoOp, Message::CollapseRow, Message::NoOp]
+        ));
+        // a key breaking the sequence is used on its own
+        let messages = press(&mut keymap, &focus, "u<C-d>xdu");
+        assert!(matches!(
+            messages.as_slice(),
+            [
+                Message::Undo,
+                Message::NoOp,
+                Message::DeleteCell,
+                Message::NoOp,
+                Message::Undo
+            ]
+        ));
+        // changing focus drops the pending keys
+        press(&mut keymap, &focus, "d");
+        let messages = press(&mut keymap, &DisplayFocus::TableSelector, "d");
+        assert!(matches!(messages.as_slice(), [Message::RemoveTable]));
+    }
+
+    #[test]
+    fn test_count() {
+        let mut keymap = Keymap::default();
+        let focus = DisplayFocus::TableView;
+
+        let messages = press(&mut keymap, &focus, "20j0");
+        assert!(matches!(
+            messages.as_slice(),
+            [
+                Message::NoOp,
+                Message::NoOp,
+                Message::Motion {
+                    motion: Motion::Down,
+                    count: Some(20)
+                },
+                Message::Motion {
+                    motion: Motion::FirstColumn,
+                    count: None
+                },
+            ]
+        ));
+        let messages = press(&mut keymap, &focus, "5gg");
+        assert!(matches!(
+            messages.as_slice(),
+            [
+                Message::NoOp,
+                Message::NoOp,
+                Message::Motion {
+                    motion: Motion::FirstRow,
+                    count: Some(5)
+                },
+            ]
+        ));
+        // an unbound key drops the count
+        let messages = press(&mut keymap, &focus, "3<C-x>j");
+        assert!(matches!(
+            messages.as_slice(),
+            [
+                Message::NoOp,
+                Message::NoOp,
+                Message::Move(MoveDirection::Down)
+            ]
+        ));
+        // digits are not counted out of the table view
+        let focus = DisplayFocus::Command(Box::new(DisplayFocus::TableView));
+        let messages = press(&mut keymap, &focus, "4");
+        assert!(matches!(messages.as_slice(), [Message::Input('4')]));
+    }
+
+    #[test]
+    fn test_parse_overrides() {
+        let keymap = Keymap::parse(
+            r#"
+            [table_selector]
+            x = "remove_table"
+            "#,
+        )
+        .unwrap();
+        let mode = KeymapMode::TableSelector;
+        assert_eq!(find_action(&keymap, mode, "x"), Some(Action::RemoveTable));
+        assert_eq!(find_action(&keymap, mode, "d"), Some(Action::RemoveTable));
+        assert_eq!(
+            keymap.keys_label(mode, &[Action::RemoveTable]),
+            Some("x".to_string())
+        );
+
+        for (content, error) in [
+            ("[table]\na = \"undo\"", "unknown section [table]"),
+            ("[exit]\na = \"fly\"", "unknown action fly"),
+            ("[exit]\na = 1", "action of a is not a string"),
+        ] {
+            assert_eq!(
+                Keymap::parse(content).unwrap_err().to_string(),
+                StrataError::InvalidKeymap(error.to_string()).to_string()
+            );
+        }
+        assert!(Keymap::parse("[exit]\n\"<Nope>\" = \"exit\"").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let keymap = Keymap::load(Path::new("no/such/keymap.toml")).unwrap();
+        assert_eq!(
+            keymap.bindings(KeymapMode::Exit),
+            Keymap::default().bindings(KeymapMode::Exit)
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{
+    app::{
+        base_component::line_editor::LineEdit,
+        component::table_view::{Motion, SelectionMode, Transform},
+    },
+    message::{Message, MoveDirection},
+};
+
+/// What a key does, named in the keymap file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    AddColumn,
+    AddRow,
+    AddTable,
+    Backspace,
+    Cancel,
+    ClearCells,
+    CommandLine,
+    Complete,
+    CompleteBackward,
+    CursorEnd,
+    CursorHome,
+    CursorLeft,
+    CursorRight,
+    CursorWordLeft,
+    CursorWordRight,
+    DeleteChar,
+    DeleteColumn,
+    DeleteRow,
+    DeleteToStart,
+    DeleteWord,
+    DiscardAndExit,
+    DuplicateRow,
+    EditCell,
+    EditHeader,
+    EditTableName,
+    Exit,
+    Fill,
+    Filter,
+    FirstColumn,
+    FirstRow,
+    HalfPageDown,
+    HalfPageUp,
+    HistoryNewer,
+    HistoryOlder,
+    InsertColumnLeft,
+    InsertColumnRight,
+    InsertRowAbove,
+    InsertRowBelow,
+    JumpCell,
+    JumpTable,
+    LastColumn,
+    LastRow,
+    Lowercase,
+    MoveColumnLeft,
+    MoveColumnRight,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveRowDown,
+    MoveRowUp,
+    MoveUp,
+    NextBlock,
+    NextMatch,
+    NextNonEmpty,
+    Normalize,
+    OpenFile,
+    OpenFileView,
+    Paste,
+    PreviousBlock,
+    PreviousMatch,
+    PreviousNonEmpty,
+    Quit,
+    Redo,
+    RemoveTable,
+    Replace,
+    Save,
+    SaveAll,
+    SaveAllAndExit,
+    SaveAs,
+    Search,
+    SearchBackward,
+    SelectFile,
+    SelectRegister,
+    SelectTable,
+    Sort,
+    Submit,
+    ToggleColumnStats,
+    ToggleRawDisplay,
+    Trim,
+    Undo,
+    Uppercase,
+    VisualSelect,
+    VisualSelectColumns,
+    VisualSelectRows,
+    Yank,
+}
+
+impl Action {
+    pub const ALL: [Action; 85] = [
+        Action::AddColumn,
+        Action::AddRow,
+        Action::AddTable,
+        Action::Backspace,
+        Action::Cancel,
+        Action::ClearCells,
+        Action::CommandLine,
+        Action::Complete,
+        Action::CompleteBackward,
+        Action::CursorEnd,
+        Action::CursorHome,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorWordLeft,
+        Action::CursorWordRight,
+        Action::DeleteChar,
+        Action::DeleteColumn,
+        Action::DeleteRow,
+        Action::DeleteToStart,
+        Action::DeleteWord,
+        Action::DiscardAndExit,
+        Action::DuplicateRow,
+        Action::EditCell,
+        Action::EditHeader,
+        Action::EditTableName,
+        Action::Exit,
+        Action::Fill,
+        Action::Filter,
+        Action::FirstColumn,
+        Action::FirstRow,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::HistoryNewer,
+        Action::HistoryOlder,
+        Action::InsertColumnLeft,
+        Action::InsertColumnRight,
+        Action::InsertRowAbove,
+        Action::InsertRowBelow,
+        Action::JumpCell,
+        Action::JumpTable,
+        Action::LastColumn,
+        Action::LastRow,
+        Action::Lowercase,
+        Action::MoveColumnLeft,
+        Action::MoveColumnRight,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveRowDown,
+        Action::MoveRowUp,
+        Action::MoveUp,
+        Action::NextBlock,
+        Action::NextMatch,
+        Action::NextNonEmpty,
+        Action::Normalize,
+        Action::OpenFile,
+        Action::OpenFileView,
+        Action::Paste,
+        Action::PreviousBlock,
+        Action::PreviousMatch,
+        Action::PreviousNonEmpty,
+        Action::Quit,
+        Action::Redo,
+        Action::RemoveTable,
+        Action::Replace,
+        Action::Save,
+        Action::SaveAll,
+        Action::SaveAllAndExit,
+        Action::SaveAs,
+        Action::Search,
+        Action::SearchBackward,
+        Action::SelectFile,
+        Action::SelectRegister,
+        Action::SelectTable,
+        Action::Sort,
+        Action::Submit,
+        Action::ToggleColumnStats,
+        Action::ToggleRawDisplay,
+        Action::Trim,
+        Action::Undo,
+        Action::Uppercase,
+        Action::VisualSelect,
+        Action::VisualSelectColumns,
+        Action::VisualSelectRows,
+        Action::Yank,
+    ];
+
+    /// Name used in the keymap file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::AddColumn => "add_column",
+            Action::AddRow => "add_row",
+            Action::AddTable => "add_table",
+            Action::Backspace => "backspace",
+            Action::Cancel => "cancel",
+            Action::ClearCells => "clear_cells",
+            Action::CommandLine => "command_line",
+            Action::Complete => "complete",
+            Action::CompleteBackward => "complete_backward",
+            Action::CursorEnd => "cursor_end",
+            Action::CursorHome => "cursor_home",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorWordLeft => "cursor_word_left",
+            Action::CursorWordRight => "cursor_word_right",
+            Action::DeleteChar => "delete_char",
+            Action::DeleteColumn => "delete_column",
+            Action::DeleteRow => "delete_row",
+            Action::DeleteToStart => "delete_to_start",
+            Action::DeleteWord => "delete_word",
+            Action::DiscardAndExit => "discard_and_exit",
+            Action::DuplicateRow => "duplicate_row",
+            Action::EditCell => "edit_cell",
+            Action::EditHeader => "edit_header",
+            Action::EditTableName => "edit_table_name",
+            Action::Exit => "exit",
+            Action::Fill => "fill",
+            Action::Filter => "filter",
+            Action::FirstColumn => "first_column",
+            Action::FirstRow => "first_row",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::HistoryNewer => "history_newer",
+            Action::HistoryOlder => "history_older",
+            Action::InsertColumnLeft => "insert_column_left",
+            Action::InsertColumnRight => "insert_column_right",
+            Action::InsertRowAbove => "insert_row_above",
+            Action::InsertRowBelow => "insert_row_below",
+            Action::JumpCell => "jump_cell",
+            Action::JumpTable => "jump_table",
+            Action::LastColumn => "last_column",
+            Action::LastRow => "last_row",
+            Action::Lowercase => "lowercase",
+            Action::MoveColumnLeft => "move_column_left",
+            Action::MoveColumnRight => "move_column_right",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveRowDown => "move_row_down",
+            Action::MoveRowUp => "move_row_up",
+            Action::MoveUp => "move_up",
+            Action::NextBlock => "next_block",
+            Action::NextMatch => "next_match",
+            Action::NextNonEmpty => "next_non_empty",
+            Action::Normalize => "normalize",
+            Action::OpenFile => "open_file",
+            Action::OpenFileView => "open_file_view",
+            Action::Paste => "paste",
+            Action::PreviousBlock => "previous_block",
+            Action::PreviousMatch => "previous_match",
+            Action::PreviousNonEmpty => "previous_non_empty",
+            Action::Quit => "quit",
+            Action::Redo => "redo",
+            Action::RemoveTable => "remove_table",
+            Action::Replace => "replace",
+            Action::Save => "save",
+            Action::SaveAll => "save_all",
+            Action::SaveAllAndExit => "save_all_and_exit",
+            Action::SaveAs => "save_as",
+            Action::Search => "search",
+            Action::SearchBackward => "search_backward",
+            Action::SelectFile => "select_file",
+            Action::SelectRegister => "select_register",
+            Action::SelectTable => "select_table",
+            Action::Sort => "sort",
+            Action::Submit => "submit",
+            Action::ToggleColumnStats => "toggle_column_stats",
+            Action::ToggleRawDisplay => "toggle_raw_display",
+            Action::Trim => "trim",
+            Action::Undo => "undo",
+            Action::Uppercase => "uppercase",
+            Action::VisualSelect => "visual_select",
+            Action::VisualSelectColumns => "visual_select_columns",
+            Action::VisualSelectRows => "visual_select_rows",
+            Action::Yank => "yank",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Message for the action, with the count typed before it for the motions of the
+    /// table view
+    pub fn message(self, count: Option<usize>) -> Message {
+        match self {
+            Action::AddColumn => Message::ExpandColumn,
+            Action::AddRow => Message::ExpandRow,
+            Action::AddTable => Message::AddTable,
+            Action::Backspace => Message::PopInput,
+            Action::Cancel => Message::Cancel,
+            Action::ClearCells => Message::DeleteCell,
+            Action::CommandLine => Message::CommandLine,
+            Action::Complete => Message::Complete,
+            Action::CompleteBackward => Message::CompleteBackward,
+            Action::CursorEnd => Message::EditCommand(LineEdit::End),
+            Action::CursorHome => Message::EditCommand(LineEdit::Home),
+            Action::CursorLeft => Message::EditCommand(LineEdit::Left),
+            Action::CursorRight => Message::EditCommand(LineEdit::Right),
+            Action::CursorWordLeft => Message::EditCommand(LineEdit::WordLeft),
+            Action::CursorWordRight => Message::EditCommand(LineEdit::WordRight),
+            Action::DeleteChar => Message::EditCommand(LineEdit::Delete),
+            Action::DeleteColumn => Message::CollapseColumn,
+            Action::DeleteRow => Message::CollapseRow,
+            Action::DeleteToStart => Message::EditCommand(LineEdit::DeleteToStart),
+            Action::DeleteWord => Message::EditCommand(LineEdit::DeleteWord),
+            Action::DiscardAndExit => Message::DiscardAndExit,
+            Action::DuplicateRow => Message::DuplicateRow,
+            Action::EditCell => Message::EditCell,
+            Action::EditHeader => Message::EditHeader,
+            Action::EditTableName => Message::EditTableName,
+            Action::Exit => Message::Exit,
+            Action::Fill => Message::Fill,
+            Action::Filter => Message::Filter,
+            Action::FirstColumn => Message::Motion {
+                motion: Motion::FirstColumn,
+                count,
+            },
+            Action::FirstRow => Message::Motion {
+                motion: Motion::FirstRow,
+                count,
+            },
+            Action::HalfPageDown => Message::Motion {
+                motion: Motion::HalfPageDown,
+                count,
+            },
+            Action::HalfPageUp => Message::Motion {
+                motion: Motion::HalfPageUp,
+                count,
+            },
+            Action::HistoryNewer => Message::RecallNewerInput,
+            Action::HistoryOlder => Message::RecallOlderInput,
+            Action::InsertColumnLeft => Message::InsertColumn { right: false },
+            Action::InsertColumnRight => Message::InsertColumn { right: true },
+            Action::InsertRowAbove => Message::InsertRow { below: false },
+            Action::InsertRowBelow => Message::InsertRow { below: true },
+            Action::JumpCell => Message::JumpCell,
+            Action::JumpTable => Message::JumpTable,
+            Action::LastColumn => Message::Motion {
+                motion: Motion::LastColumn,
+                count,
+            },
+            Action::LastRow => Message::Motion {
+                motion: Motion::LastRow,
+                count,
+            },
+            Action::Lowercase => Message::Transform(Transform::Lowercase),
+            Action::MoveColumnLeft => Message::MoveSelected(MoveDirection::Left),
+            Action::MoveColumnRight => Message::MoveSelected(MoveDirection::Right),
+            Action::MoveDown => Self::move_message(MoveDirection::Down, Motion::Down, count),
+            Action::MoveLeft => Self::move_message(MoveDirection::Left, Motion::Left, count),
+            Action::MoveRight => Self::move_message(MoveDirection::Right, Motion::Right, count),
+            Action::MoveRowDown => Message::MoveSelected(MoveDirection::Down),
+            Action::MoveRowUp => Message::MoveSelected(MoveDirection::Up),
+            Action::MoveUp => Self::move_message(MoveDirection::Up, Motion::Up, count),
+            Action::NextBlock => Message::Motion {
+                motion: Motion::NextBlock,
+                count,
+            },
+            Action::NextMatch => Message::NextMatch,
+            Action::NextNonEmpty => Message::Motion {
+                motion: Motion::NextNonEmpty,
+                count,
+            },
+            Action::Normalize => Message::Normalize,
+            Action::OpenFile => Message::Open,
+            Action::OpenFileView => Message::OpenFileView,
+            Action::Paste => Message::Paste,
+            Action::PreviousBlock => Message::Motion {
+                motion: Motion::PreviousBlock,
+                count,
+            },
+            Action::PreviousMatch => Message::PreviousMatch,
+            Action::PreviousNonEmpty => Message::Motion {
+                motion: Motion::PreviousNonEmpty,
+                count,
+            },
+            Action::Quit => Message::Exiting,
+            Action::Redo => Message::Redo,
+            Action::RemoveTable => Message::RemoveTable,
+            Action::Replace => Message::Replace,
+            Action::Save => Message::Save,
+            Action::SaveAll => Message::SaveAll,
+            Action::SaveAllAndExit => Message::SaveAllAndExit,
+            Action::SaveAs => Message::SaveAs,
+            Action::Search => Message::Search,
+            Action::SearchBackward => Message::SearchBackward,
+            Action::SelectFile => Message::SelectFile,
+            Action::SelectRegister => Message::SelectRegister,
+            Action::SelectTable => Message::SelectTable,
+            Action::Sort => Message::Sort,
+            Action::Submit => Message::ExecuteCommand,
+            Action::ToggleColumnStats => Message::ToggleColumnStats,
+            Action::ToggleRawDisplay => Message::ToggleRawDisplay,
+            Action::Trim => Message::Transform(Transform::Trim),
+            Action::Undo => Message::Undo,
+            Action::Uppercase => Message::Transform(Transform::Uppercase),
+            Action::VisualSelect => Message::VisualSelect(SelectionMode::Cells),
+            Action::VisualSelectColumns => Message::VisualSelect(SelectionMode::Columns),
+            Action::VisualSelectRows => Message::VisualSelect(SelectionMode::Rows),
+            Action::Yank => Message::Yank,
+        }
+    }
+
+    /// A count turns a move into a motion of the table view
+    fn move_message(direction: MoveDirection, motion: Motion, count: Option<usize>) -> Message {
+        match count {
+            Some(count) => Message::Motion {
+                motion,
+                count: Some(count),
+            },
+            None => Message::Move(direction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_unique() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::from_name("launch_rocket"), None);
+    }
+}
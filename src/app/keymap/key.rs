@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::{bail, Result};
+
+use crate::error::StrataError;
+
+/// Key press as it is bound in the keymap
+///
+/// Characters carry their case, so Shift is only kept for the other keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 16] = [
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Char(' ')),
+];
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(_) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                code
+            }
+            // terminals send Shift-Tab as BackTab
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                code
+            }
+            _ => code,
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Parse a sequence of keys written like `gg`, `<C-r>` or `<S-Tab>`
+    ///
+    /// `<lt>` stands for `<`.
+    pub fn parse_sequence(keys: &str) -> Result<Vec<Key>> {
+        let invalid = || StrataError::InvalidKey(keys.to_string());
+        let mut sequence = Vec::new();
+        let mut rest = keys;
+        while let Some(c) = rest.chars().next() {
+            if c != '<' {
+                sequence.push(Key::char(c));
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let end = rest.find('>').ok_or_else(invalid)?;
+            sequence.push(Self::parse_named(&rest[1..end]).ok_or_else(invalid)?);
+            rest = &rest[end + 1..];
+        }
+        if sequence.is_empty() {
+            bail!(invalid());
+        }
+        Ok(sequence)
+    }
+
+    /// Parse what is inside `<...>`, e.g. `C-r` or `Enter`
+    fn parse_named(name: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = name;
+        loop {
+            let modifier = match name.get(..2) {
+                Some("C-") => KeyModifiers::CONTROL,
+                Some("A-") => KeyModifiers::ALT,
+                Some("S-") => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            modifiers |= modifier;
+            name = &name[2..];
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ if name == "lt" => KeyCode::Char('<'),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(key_name, _)| *key_name == name)
+                .map(|(_, code)| *code)?,
+        };
+        Some(Key::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Key::new(event.code, event.modifiers)
+    }
+}
+
+/// Written as in the keymap file without the angle brackets, e.g. `a`, `C-r` or `Enter`
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "C-"),
+            (KeyModifiers::ALT, "A-"),
+            (KeyModifiers::SHIFT, "S-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", prefix)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char('<') => write!(f, "lt"),
+            KeyCode::Char(c) if c != ' ' => write!(f, "{}", c),
+            code => match NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() {
+        assert_eq!(
+            Key::parse_sequence("gg").unwrap(),
+            vec![Key::char('g'), Key::char('g')]
+        );
+        assert_eq!(
+            Key::parse_sequence("<C-r>").unwrap(),
+            vec![Key::new(KeyCode::Char('r'), KeyModifiers::CONTROL)]
+        );
+        assert_eq!(
+            Key::parse_sequence("<S-Tab><lt>").unwrap(),
+            vec![
+                Key::new(KeyCode::BackTab, KeyModifiers::NONE),
+                Key::char('<')
+            ]
+        );
+        assert_eq!(
+            Key::parse_sequence("<C-A-Left>").unwrap(),
+            vec![Key::new(
+                KeyCode::Left,
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )]
+        );
+        assert!(Key::parse_sequence("").is_err());
+        assert!(Key::parse_sequence("<Enter").is_err());
+        assert!(Key::parse_sequence("<Return>").is_err());
+    }
+
+    #[test]
+    fn test_key_event() {
+        // the case of a character already tells about Shift
+        let event = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(Key::from(event), Key::char('A'));
+        let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(
+            Key::from(event),
+            Key::parse_sequence("<BackTab>").unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        for keys in ["a", "C-r", "Enter", "S-Up", "lt", "Space"] {
+            let key = Key::parse_sequence(&format!("<{}>", keys)).unwrap()[0];
+            assert_eq!(key.to_string(), keys);
+        }
+    }
+}
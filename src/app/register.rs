@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use eyre::{bail, OptionExt, Result};
+
+use crate::error::StrataError;
+
+use super::component::table_view::SelectionMode;
+
+/// Register used when none is named
+pub const UNNAMED_REGISTER: char = '"';
+/// Register shared with the system clipboard
+pub const CLIPBOARD_REGISTER: char = '+';
+
+/// Block of values yanked from a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    pub values: Vec<Vec<String>>,
+    /// Whether whole rows or columns were yanked, which decides where they are pasted
+    pub mode: SelectionMode,
+}
+
+impl Register {
+    pub fn new(values: Vec<Vec<String>>, mode: SelectionMode) -> Self {
+        Self { values, mode }
+    }
+
+    /// Tab separated values, as spreadsheets copy them
+    pub fn to_tsv(&self) -> Result<String> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .flexible(true)
+            .from_writer(Vec::new());
+        for row in &self.values {
+            writer.write_record(row)?;
+        }
+        let mut tsv = String::from_utf8(writer.into_inner()?)?;
+        // a single line is pasted into one cell rather than inserting a new line
+        if tsv.ends_with('\n') {
+            tsv.pop();
+        }
+        Ok(tsv)
+    }
+
+    /// Read tab separated values, as spreadsheets copy them, as a block of cells
+    pub fn from_tsv(tsv: &str) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(tsv.as_bytes());
+        let values = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+            .collect::<Result<Vec<Vec<String>>>>()?;
+        Ok(Self::new(values, SelectionMode::Cells))
+    }
+}
+
+/// Registers named by a character, like in vim
+#[derive(Debug, Default)]
+pub struct Registers {
+    registers: HashMap<char, Register>,
+    /// Register named for the next yank or paste
+    pending: Option<char>,
+}
+
+impl Registers {
+    pub fn select(&mut self, name: char) -> Result<&mut Self> {
+        if !(name.is_ascii_alphanumeric() || name == UNNAMED_REGISTER || name == CLIPBOARD_REGISTER)
+        {
+            bail!(StrataError::InvalidRegister(name));
+        }
+        self.pending = Some(name);
+        Ok(self)
+    }
+
+    /// Register named for the next yank or paste, which is then forgotten
+    pub fn take_pending(&mut self) -> char {
+        self.pending.take().unwrap_or(UNNAMED_REGISTER)
+    }
+
+    /// Store the register, also in the unnamed register like a vim yank does
+    pub fn store(&mut self, name: char, register: Register) {
+        if name != UNNAMED_REGISTER {
+            self.registers.insert(UNNAMED_REGISTER, register.clone());
+        }
+        self.registers.insert(name, register);
+    }
+
+    pub fn get(&self, name: char) -> Result<&Register> {
+        self.registers
+            .get(&name)
+            .ok_or_eyre(StrataError::EmptyRegister(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_tsv_round_trip() {
+        let register = Register::new(
+            values(&[&["a", "b c"], &["multi\nline", "tab\there"]]),
+            SelectionMode::Cells,
+        );
+        let tsv = register.to_tsv().unwrap();
+        assert_eq!(tsv, "a\tb c\n\"multi\nline\"\t\"tab\there\"");
+        assert_eq!(Register::from_tsv(&tsv).unwrap(), register);
+
+        let register = Register::from_tsv("1\t2\r\n3\n").unwrap();
+        assert_eq!(register.values, values(&[&["1", "2"], &["3"]]));
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut registers = Registers::default();
+        assert!(registers.get(UNNAMED_REGISTER).is_err());
+        assert_eq!(registers.take_pending(), UNNAMED_REGISTER);
+
+        registers.select('a').unwrap();
+        let name = registers.take_pending();
+        assert_eq!(name, 'a');
+        let register = Register::new(values(&[&["x"]]), SelectionMode::Rows);
+        registers.store(name, register.clone());
+        assert_eq!(registers.get('a').unwrap(), &register);
+        assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), &register);
+        assert_eq!(registers.take_pending(), UNNAMED_REGISTER);
+
+        assert!(registers.select('!').is_err());
+    }
+}
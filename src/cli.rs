@@ -0,0 +1,292 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use eyre::Result;
+
+use crate::app::{
+    command_history::CommandHistory,
+    component::{
+        table_selector::{TableName, INITIAL_TABLE_NAME},
+        table_view::{parse_ascii_char, CsvDialect, TableView},
+    },
+    keymap::Keymap,
+    App,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// CSV files to open as tables
+    pub files: Vec<PathBuf>,
+
+    /// Treat the first line of each file as data instead of a header
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Field delimiter, a single ASCII character or `\t` (sniffed from the file by default)
+    #[arg(short, long, value_parser = parse_ascii_char)]
+    pub delimiter: Option<u8>,
+
+    /// Quote character
+    #[arg(long, value_parser = parse_ascii_char)]
+    pub quote: Option<u8>,
+
+    /// Escape character for quotes inside quoted fields, instead of doubling them
+    #[arg(long, value_parser = parse_ascii_char)]
+    pub escape: Option<u8>,
+
+    /// Skip lines starting with this character
+    #[arg(long, value_parser = parse_ascii_char)]
+    pub comment: Option<u8>,
+
+    /// Fail on rows whose number of fields differs from the header instead of padding them
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Table name for the file at the same position (defaults to the file stem)
+    #[arg(short = 'n', long = "table-name", value_name = "NAME")]
+    pub table_names: Vec<String>,
+
+    /// Open the tables without allowing any edit
+    #[arg(long)]
+    pub readonly: bool,
+
+    /// Keep the command history across sessions in `$XDG_STATE_HOME/strata/history`
+    #[arg(long)]
+    pub save_history: bool,
+
+    /// Keymap file overriding the default keys [default: `$XDG_CONFIG_HOME/strata/keymap.toml`]
+    #[arg(long, value_name = "FILE")]
+    pub keymap: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Build the app with every file opened as a table
+    ///
+    /// The first table is selected when any file was given.
+    pub fn build_app(&self) -> Result<App> {
+        let mut app = App::new();
+
+        for (index, file_path) in self.files.iter().enumerate() {
+            let mut table = TableView::from_csv_with_dialect(
+                file_path,
+                !self.no_header,
+                self.dialect(file_path)?,
+            )?;
+            table.readonly = self.readonly;
+
+            let table_name = match self.table_names.get(index) {
+                Some(name) => TableName::from(name)?,
+                None => {
+                    let stem = file_path
+                        .file_stem()
+                        .and_then(OsStr::to_str)
+                        .unwrap_or(INITIAL_TABLE_NAME);
+                    unique_table_name(&app, stem)?
+                }
+            };
+            app.load_table(table_name, table)?;
+        }
+
+        if !self.files.is_empty() {
+            app.table_selector_mut().select_index(0)?;
+        }
+        if let Some(file_path) = self.keymap.clone().or_else(Keymap::default_file_path) {
+            app.set_keymap(Keymap::load(&file_path)?);
+        }
+        if self.save_history {
+            if let Some(file_path) = CommandHistory::default_file_path() {
+                load_command_history(&mut app, &file_path);
+            }
+        }
+        Ok(app)
+    }
+
+    /// Dialect sniffed from the file, overridden by the given options
+    fn dialect(&self, file_path: &Path) -> Result<CsvDialect> {
+        let mut dialect = CsvDialect::sniff(file_path)?;
+        if let Some(delimiter) = self.delimiter {
+            dialect.delimiter = delimiter;
+        }
+        if let Some(quote) = self.quote {
+            dialect.quote = quote;
+        }
+        if let Some(escape) = self.escape {
+            dialect.escape = Some(escape);
+            dialect.double_quote = false;
+        }
+        if let Some(comment) = self.comment {
+            dialect.comment = Some(comment);
+        }
+        if self.strict {
+            dialect.flexible = false;
+        }
+        Ok(dialect)
+    }
+}
+
+/// Load the command history from the file
+///
+/// A file that cannot be read does not stop the app from starting: the error is shown and
+/// the history starts empty, replacing the file when it is saved.
+fn load_command_history(app: &mut App, file_path: &Path) {
+    let command_history = CommandHistory::load(file_path).unwrap_or_else(|e| {
+        app.error_popup_mut().push(format!(
+            "Could not load the command history from {}: {}",
+            file_path.display(),
+            e
+        ));
+        app.focus_error();
+        CommandHistory::persisted(file_path)
+    });
+    app.set_command_history(command_history);
+}
+
+/// Suffix the name with a number when a table with the same name is already open
+fn unique_table_name(app: &App, name: &str) -> Result<TableName> {
+    let mut table_name = TableName::from(name)?;
+    let mut suffix = 2;
+    while app
+        .table_selector()
+        .selected_index_by_name(&table_name)
+        .is_some()
+    {
+        table_name = TableName::from(format!("{}-{}", name, suffix))?;
+        suffix += 1;
+    }
+    Ok(table_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::display_focus::DisplayFocus;
+
+    #[test]
+    fn test_build_app() {
+        let cli = Cli::try_parse_from([
+            "strata",
+            "tests/data/fluits.csv",
+            "tests/data/fluits.csv",
+            "--readonly",
+        ])
+        .unwrap();
+        let app = cli.build_app().unwrap();
+
+        assert_eq!(app.table_selector().selected_index(), Some(0));
+        assert_eq!(
+            app.table_selector().table_name(1).map(TableName::as_str),
+            Some("fluits-2")
+        );
+        let tv = app.selected_table_view().unwrap();
+        assert!(tv.readonly);
+        assert_eq!(*tv.headers(), vec!["fluits", "price"]);
+    }
+
+    #[test]
+    fn test_build_app_without_history() {
+        let cli = Cli::try_parse_from(["strata", "tests/data/fluits.csv"]).unwrap();
+        let mut app = cli.build_app().unwrap();
+
+        assert!(app.undo_table_list().is_err());
+        assert_eq!(
+            app.table_selector()
+                .selected_table_name()
+                .map(TableName::as_str),
+            Some("fluits")
+        );
+        assert!(app.selected_table_view().is_ok());
+    }
+
+    #[test]
+    fn test_load_bad_command_history() {
+        let dir = std::env::temp_dir().join("strata_test_cli_history");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = App::new();
+
+        // a directory cannot be read as the history file
+        load_command_history(&mut app, &dir);
+        assert_eq!(app.error_popup().size(), 1);
+        assert!(matches!(app.display_focus(), DisplayFocus::Error(_)));
+        assert!(app.command_history().entries("Jump").is_none());
+
+        let file_path = dir.join("history");
+        std::fs::write(&file_path, "Jump\t1 1\nJump\t2 2\textra\n").unwrap();
+        load_command_history(&mut app, &file_path);
+        assert_eq!(app.error_popup().size(), 2);
+        assert!(app.command_history().entries("Jump").is_none());
+        assert!(app.command_history().save().is_ok());
+        assert!(CommandHistory::load(&file_path).is_ok());
+    }
+
+    #[test]
+    fn test_build_app_with_options() {
+        let cli = Cli::try_parse_from([
+            "strata",
+            "--no-header",
+            "--delimiter",
+            "\\t",
+            "--table-name",
+            "prices",
+            "tests/data/fluits.tsv",
+        ])
+        .unwrap();
+        let app = cli.build_app().unwrap();
+
+        assert_eq!(
+            app.table_selector()
+                .selected_table_name()
+                .map(TableName::as_str),
+            Some("prices")
+        );
+        let tv = app.selected_table_view().unwrap();
+        assert!(!tv.readonly);
+        assert_eq!(tv.cell_value(0, 0).unwrap(), "fluits");
+        assert_eq!(tv.cell_value(1, 1).unwrap(), "100");
+    }
+
+    #[test]
+    fn test_parse_ascii_char() {
+        assert_eq!(parse_ascii_char(";"), Ok(b';'));
+        assert_eq!(parse_ascii_char("tab"), Ok(b'\t'));
+        assert!(parse_ascii_char(";;").is_err());
+        assert!(parse_ascii_char("é").is_err());
+    }
+
+    #[test]
+    fn test_dialect_options() {
+        let cli = Cli::try_parse_from([
+            "strata",
+            "--delimiter",
+            ";",
+            "--escape",
+            "\\",
+            "--comment",
+            "#",
+        ])
+        .unwrap();
+        let dialect = cli.dialect(Path::new("tests/data/fluits.tsv")).unwrap();
+        assert_eq!(dialect.delimiter, b';');
+        assert_eq!(dialect.escape, Some(b'\\'));
+        assert!(!dialect.double_quote);
+        assert_eq!(dialect.comment, Some(b'#'));
+
+        let cli = Cli::try_parse_from(["strata"]).unwrap();
+        let dialect = cli.dialect(Path::new("tests/data/fluits.tsv")).unwrap();
+        assert_eq!(dialect.delimiter, b'\t');
+        assert!(dialect.flexible);
+
+        let cli = Cli::try_parse_from(["strata", "--strict"]).unwrap();
+        let dialect = cli.dialect(Path::new("tests/data/fluits.tsv")).unwrap();
+        assert!(!dialect.flexible);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let cli = Cli::try_parse_from(["strata", "tests/data/missing.csv"]).unwrap();
+        assert!(cli.build_app().is_err());
+    }
+}
@@ -2,42 +2,90 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum StrataError {
+    #[error("Circular reference in formula: cell:[{0}]")]
+    CircularReference(String),
+
     #[error("Command not found")]
     CommandNotFound,
 
+    #[error("Register is empty: register:[{0}]")]
+    EmptyRegister(char),
+
     #[error("Failed to get file name for path: {0}")]
     FailedToReadDir(String),
 
     #[error("Index out of bounds: max:[{max}], requested:[{requested}]")]
     IndexOutOfBounds { max: usize, requested: usize },
 
+    #[error("Invalid argument: command:[{command}], argument:[{argument}]")]
+    InvalidCommandArgument { command: String, argument: String },
+
+    #[error("Value does not match the column type: expected:[{expected}], value:[{value}]")]
+    InvalidCellValue { expected: String, value: String },
+
     #[error("Invalid column index: max:[{max}], requested:[{requested}]")]
     InvalidColumnIndex { max: usize, requested: usize },
 
+    #[error("Invalid formula: formula:[{formula}], error:[{error}]")]
+    InvalidFormula { formula: String, error: String },
+
+    #[error("Invalid key: keys:[{0}]")]
+    InvalidKey(String),
+
+    #[error("Invalid keymap: {0}")]
+    InvalidKeymap(String),
+
     #[error("Invalid operation was called:  operation:[{operation:?}], focus:[{focus:?}]")]
     InvalidOperationCall { operation: String, focus: String },
 
+    #[error("Invalid register: register:[{0}]")]
+    InvalidRegister(char),
+
     #[error("Invalid row index: max:[{max}], requested:[{requested}]")]
     InvalidRowIndex { max: usize, requested: usize },
 
+    #[error("Invalid search pattern: {0}")]
+    InvalidSearchPattern(String),
+
     #[error("Invalid table name")]
     InvalidTableName,
 
     #[error("Item not found: item_name:[{0}]")]
     ItemNotFound(String),
 
+    #[error("Missing argument: command:[{command}], usage:[{usage}]")]
+    MissingCommandArgument { command: String, usage: String },
+
     #[error("No cell selected")]
     NoCellSelected,
 
+    #[error("No file path for the table")]
+    NoFilePath,
+
     #[error("No item selected")]
     NoItemSelected,
 
+    #[error("No search")]
+    NoSearch,
+
     #[error("No table added")]
     NoTableAdded,
 
     #[error("No table selected")]
     NoTableSelected,
 
+    #[error("Nothing to redo")]
+    NothingToRedo,
+
+    #[error("Nothing to undo")]
+    NothingToUndo,
+
+    #[error("Table is readonly")]
+    ReadOnlyTable,
+
+    #[error("Pattern not found: {0}")]
+    SearchNotFound(String),
+
     #[error("String parse failed: {0}")]
     StringParseError(String),
 
@@ -49,4 +97,19 @@ pub enum StrataError {
 
     #[error("Table not found: table_name:[{0}]")]
     TableNotFound(String),
+
+    #[error(
+        "Row has a different number of fields: line:[{line}], expected:[{expected}], found:[{found}]"
+    )]
+    UnequalRowLength {
+        line: u64,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Unknown command: command:[{0}]")]
+    UnknownCommand(String),
+
+    #[error("Unsaved changes: table_name:[{0}]")]
+    UnsavedChanges(String),
 }
@@ -1,4 +1,6 @@
 pub mod app;
+pub mod cli;
+pub mod clipboard;
 pub mod error;
 pub mod message;
 pub mod update;
@@ -18,8 +20,8 @@ pub mod test_util {
         let table_name_1 = "table1";
         app.add_table(table_name_1).unwrap();
         app.selected_table_view_mut()
-            .and_then(|tv| Ok(tv.expand_row()))
-            .and_then(|tv| Ok(tv.expand_col()))
+            .and_then(|tv| tv.expand_row())
+            .and_then(|tv| tv.expand_col())
             .and_then(|tv| tv.update_cell(0, 0, "cell 0-0"))
             .and_then(|tv| tv.update_cell(0, 1, "cell 0-1"))
             .and_then(|tv| tv.update_cell(1, 0, "cell 1-0"))
@@ -29,8 +31,8 @@ pub mod test_util {
         let table_name_2 = "table2";
         app.add_table(table_name_2).unwrap();
         app.selected_table_view_mut()
-            .and_then(|tv| Ok(tv.expand_row()))
-            .and_then(|tv| Ok(tv.expand_col()))
+            .and_then(|tv| tv.expand_row())
+            .and_then(|tv| tv.expand_col())
             .and_then(|tv| tv.update_cell(0, 0, "cell 0-0"))
             .and_then(|tv| tv.update_cell(0, 1, "cell 0-1"))
             .and_then(|tv| tv.update_cell(1, 0, "cell 1-0"))
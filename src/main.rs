@@ -1,7 +1,14 @@
-use std::io;
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
 
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -11,163 +18,81 @@ use ratatui::{
     Terminal,
 };
 use strata::{
-    app::{display_focus::DisplayFocus, App},
-    message::{Message, MoveDirection},
+    app::{keymap::Key, App},
+    cli::Cli,
+    clipboard::osc52_copy_sequence,
+    message::Message,
     update::update,
     view::view,
 };
 
 fn main() -> Result<()> {
+    // load files before taking over the terminal so that errors are printed as usual
+    let mut app = Cli::parse().build_app()?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(&mut stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
     // run app
-    let mut app = App::new();
     let _ = run_app(&mut terminal, &mut app);
+    let saved_history = app.command_history().save();
 
     // cleanup
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
-    Ok(())
+    saved_history
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+fn run_app<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
     loop {
+        let reading_column_stats = app.update_column_stats();
         if let Err(e) = terminal.draw(|f| view(f, app)) {
             app.error_popup_mut().push(e.to_string());
             app.focus_error();
         }
-
-        if let Event::Key(key) = event::read()? {
-            let message = handle_key_event(key, app.display_focus());
-            if let Message::Exit = message {
-                break;
-            }
-            if let Err(e) = update(app, message) {
-                app.error_popup_mut().push(e.to_string());
-                app.focus_error();
-            }
+        // keep reading the rows for the column statistics until a key is pressed
+        if reading_column_stats && !event::poll(Duration::ZERO)? {
+            continue;
         }
-    }
 
-    Ok(())
-}
-
-fn handle_key_event(key: KeyEvent, focus: &DisplayFocus) -> Message {
-    match key.code {
-        // input charactor
-        KeyCode::Char(c) if matches!(focus, DisplayFocus::Command(_)) => Message::Input(c),
-        // special key
-        KeyCode::Esc => Message::Cancel,
-        KeyCode::Enter => match focus {
-            DisplayFocus::Command(_) => Message::ExecuteCommand,
-            DisplayFocus::TableSelector => Message::SelectTable,
-            DisplayFocus::TableView => Message::EditCell,
-            DisplayFocus::FileView => Message::SelectFile,
-            DisplayFocus::Exit(_) => Message::Exit,
-            _ => Message::NoOp,
-        },
-        KeyCode::Backspace => Message::PopInput,
-        // others
-        KeyCode::Char('q') => match focus {
-            DisplayFocus::Exit(_) => {
-                return Message::Exit;
+        let message = match event::read()? {
+            Event::Key(key) => {
+                let focus = app.display_focus().clone();
+                app.keymap_mut().message(&focus, Key::from(key))
             }
-            DisplayFocus::TableSelector => Message::Exiting,
-            DisplayFocus::TableView => Message::Cancel,
-            DisplayFocus::FileView => Message::Cancel,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('a') => match focus {
-            DisplayFocus::TableSelector => Message::AddTable,
-            DisplayFocus::TableView => Message::EditCell,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('d') => match focus {
-            DisplayFocus::TableSelector => Message::RemoveTable,
-            DisplayFocus::TableView => Message::DeleteCell,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('e') => match focus {
-            DisplayFocus::TableSelector => Message::EditTableName,
-            DisplayFocus::TableView => Message::EditCell,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('E') => match focus {
-            DisplayFocus::TableView => Message::EditHeader,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('o') => match focus {
-            DisplayFocus::TableSelector => Message::OpenFileView,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('s') => match focus {
-            DisplayFocus::TableSelector | DisplayFocus::TableView => Message::Save,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('r') => match focus {
-            DisplayFocus::TableView => Message::ExpandRow,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('R') => match focus {
-            DisplayFocus::TableView => Message::CollapseRow,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('c') => match focus {
-            DisplayFocus::TableView => Message::ExpandColumn,
-            _ => Message::NoOp,
-        },
-        KeyCode::Char('C') => match focus {
-            DisplayFocus::TableView => Message::CollapseColumn,
-            _ => Message::NoOp,
-        },
-        // move cursor
-        KeyCode::Up => Message::Move(MoveDirection::Up),
-        KeyCode::Down => Message::Move(MoveDirection::Down),
-        KeyCode::Right => Message::Move(MoveDirection::Right),
-        KeyCode::Left => Message::Move(MoveDirection::Left),
-        KeyCode::Tab if *focus == DisplayFocus::TableView => Message::Move(MoveDirection::Right),
-        KeyCode::Char('J') => match focus {
-            DisplayFocus::TableView => Message::JumpTable,
-            DisplayFocus::TableSelector => Message::JumpCell,
-            _ => Message::NoOp,
-        },
-
-        // vim keybindings
-        KeyCode::Char('h') if *focus == DisplayFocus::TableView => {
-            Message::Move(MoveDirection::Left)
-        }
-        KeyCode::Char('j')
-            if *focus == DisplayFocus::TableView || *focus == DisplayFocus::TableSelector || *focus == DisplayFocus::FileView =>
-        {
-            Message::Move(MoveDirection::Down)
-        }
-        KeyCode::Char('k')
-            if *focus == DisplayFocus::TableView || *focus == DisplayFocus::TableSelector || *focus == DisplayFocus::FileView =>
-        {
-            Message::Move(MoveDirection::Up)
+            Event::Paste(text) => Message::PasteText(text),
+            _ => continue,
+        };
+        if let Err(e) = update(app, message) {
+            app.error_popup_mut().push(e.to_string());
+            app.focus_error();
         }
-        KeyCode::Char('l')
-            if *focus == DisplayFocus::TableView || *focus == DisplayFocus::TableSelector || *focus == DisplayFocus::FileView =>
-        {
-            Message::Move(MoveDirection::Right)
+        if let Some(text) = app.take_clipboard_output() {
+            let backend = terminal.backend_mut();
+            backend.write_all(osc52_copy_sequence(&text).as_bytes())?;
+            Write::flush(backend)?;
         }
-        KeyCode::Char('h')
-            if *focus == DisplayFocus::FileView =>
-        {
-            Message::Move(MoveDirection::Left)
+        if app.should_quit() {
+            break;
         }
-        _ => Message::NoOp,
     }
+
+    Ok(())
 }
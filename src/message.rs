@@ -1,29 +1,127 @@
+mod command_line;
+
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use crate::app::{
+    base_component::line_editor::LineEdit,
+    component::table_view::{
+        ColumnType, DialectOption, Motion, NumberFormat, RowFilter, SelectionMode, SortKey,
+        Transform,
+    },
+};
+
+pub use command_line::parse_command_line;
+
 pub enum Message {
     AddTable,
     PopInput,
     Cancel,
     CollapseColumn,
     CollapseRow,
+    /// Open the `:` command line
+    CommandLine,
     RemoveTable,
     DeleteCell,
+    DeleteRows(RangeInclusive<usize>),
+    /// Append a column computed from each row, kept as formulas when `live`
+    DeriveColumn {
+        header: String,
+        expression: String,
+        live: bool,
+    },
+    DiscardAndExit,
+    /// Insert a copy of the selected row below it
+    DuplicateRow,
     EditTableName,
     EditCell,
+    /// Move the cursor or delete text in the command input
+    EditCommand(LineEdit),
     ExecuteCommand,
     Exit,
     Exiting,
+    /// Write the computed values of formulas to the path instead of the formulas
+    ExportValues(PathBuf),
     ExpandColumn,
     ExpandRow,
+    Fill,
+    Filter,
+    /// Select the cell, staying in the current column when `col` is not given
+    GoTo {
+        row: usize,
+        col: Option<usize>,
+    },
     EditHeader,
     Input(char),
+    /// Insert an empty column left or right of the selected one
+    InsertColumn {
+        right: bool,
+    },
+    /// Insert an empty row above or below the selected one
+    InsertRow {
+        below: bool,
+    },
     JumpTable,
     JumpCell,
     Move(MoveDirection),
+    /// Move the selected row up or down, or the selected column left or right
+    MoveSelected(MoveDirection),
+    /// Move the cursor of the table view, `count` times or to the row it gives
+    Motion {
+        motion: Motion,
+        count: Option<usize>,
+    },
+    NextMatch,
     NoOp,
+    Normalize,
     Open,
     OpenFileView,
+    OpenPath(PathBuf),
+    Paste,
+    /// Text pasted into the terminal
+    PasteText(String),
+    PreviousMatch,
+    /// Complete the command input, or show the next candidate
+    Complete,
+    /// Show the previous completion candidate
+    CompleteBackward,
+    /// Show the next entry of the command history
+    RecallNewerInput,
+    /// Show the previous entry of the command history
+    RecallOlderInput,
+    Redo,
+    Replace,
     Save,
+    SaveAll,
+    SaveAs,
+    SaveAllAndExit,
+    /// Save the selected table to the path, or to its file, with the dialect changed by
+    /// the options
+    SaveTo {
+        path: Option<PathBuf>,
+        options: Vec<DialectOption>,
+    },
+    Search,
+    SearchBackward,
     SelectFile,
+    SelectRegister,
     SelectTable,
+    SetFilter(Option<RowFilter>),
+    SetNumberFormat(NumberFormat),
+    /// Set the type of the selected column, or infer it again with `None`
+    SetColumnType(Option<ColumnType>),
+    Sort,
+    /// Show or hide the statistics of the selected column
+    ToggleColumnStats,
+    /// Show the numbers as typed or formatted
+    ToggleRawDisplay,
+    SortBy {
+        keys: Vec<SortKey>,
+        pin_first_row: bool,
+    },
+    Transform(Transform),
+    Undo,
+    VisualSelect(SelectionMode),
+    Yank,
 }
 
 pub enum MoveDirection {
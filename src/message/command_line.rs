@@ -0,0 +1,274 @@
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use eyre::{bail, Result};
+
+use crate::{
+    app::component::table_view::{
+        parse_sort_keys, ColumnType, DialectOption, NumberFormat, RowFilter,
+    },
+    error::StrataError,
+};
+
+use super::Message;
+
+/// Parse a command typed after `:`, e.g. `sort 2 desc` or `delrow 5-9`
+///
+/// A bare number goes to that row.
+pub fn parse_command_line(input: &str) -> Result<Message> {
+    let input = input.trim().trim_start_matches(':');
+    let (command, args) = input
+        .split_once(char::is_whitespace)
+        .map_or((input, ""), |(command, args)| (command, args.trim()));
+
+    let message = match command {
+        "w" | "write" if args.is_empty() => Message::Save,
+        "w" | "write" => {
+            let (path, options) = split_dialect_options(args)?;
+            Message::SaveTo {
+                path: (!path.is_empty()).then(|| PathBuf::from(path)),
+                options,
+            }
+        }
+        "wa" | "wall" => no_args(command, args, Message::SaveAll)?,
+        "wq" | "x" => no_args(command, args, Message::SaveAllAndExit)?,
+        "export" => Message::ExportValues(PathBuf::from(required(command, args, "export <path>")?)),
+        "q" | "quit" => no_args(command, args, Message::Exit)?,
+        "q!" | "quit!" => no_args(command, args, Message::DiscardAndExit)?,
+        "e" | "edit" => Message::OpenPath(PathBuf::from(required(command, args, "e <path>")?)),
+        "sort" => {
+            let (keys, pin_first_row) =
+                parse_sort_keys(required(command, args, "sort <col> [desc], ...")?)?;
+            Message::SortBy {
+                keys,
+                pin_first_row,
+            }
+        }
+        "filter" if args.is_empty() => Message::SetFilter(None),
+        "filter" => Message::SetFilter(Some(RowFilter::parse(args)?)),
+        "type" if args.is_empty() => Message::SetColumnType(None),
+        "type" => Message::SetColumnType(Some(ColumnType::parse(args)?)),
+        "format" => Message::SetNumberFormat(NumberFormat::parse(args)?),
+        "derive" => {
+            let usage = "derive [live] <name> = <expression>";
+            let (name, expression) = required(command, args, usage)?
+                .split_once('=')
+                .ok_or_else(|| invalid_argument(command, args))?;
+            let (name, live) = match name.trim().strip_prefix("live ") {
+                Some(name) => (name.trim(), true),
+                None => (name.trim(), false),
+            };
+            Message::DeriveColumn {
+                header: required(command, name, usage)?.to_string(),
+                expression: required(command, expression.trim(), usage)?.to_string(),
+                live,
+            }
+        }
+        "goto" => {
+            let mut indices = required(command, args, "goto <row> [col]")?.split_whitespace();
+            let row = parse_index(command, indices.next().unwrap_or_default())?;
+            let col = indices
+                .next()
+                .map(|col| parse_index(command, col))
+                .transpose()?;
+            if let Some(extra) = indices.next() {
+                bail!(invalid_argument(command, extra));
+            }
+            Message::GoTo { row, col }
+        }
+        "delrow" => Message::DeleteRows(parse_row_range(
+            command,
+            required(command, args, "delrow <row>[-<row>]")?,
+        )?),
+        "u" | "undo" => no_args(command, args, Message::Undo)?,
+        "redo" => no_args(command, args, Message::Redo)?,
+        row if row.parse::<usize>().is_ok() => no_args(
+            command,
+            args,
+            Message::GoTo {
+                row: parse_index(command, row)?,
+                col: None,
+            },
+        )?,
+        _ => bail!(StrataError::UnknownCommand(command.to_string())),
+    };
+    Ok(message)
+}
+
+fn no_args(command: &str, args: &str, message: Message) -> Result<Message> {
+    if !args.is_empty() {
+        bail!(invalid_argument(command, args));
+    }
+    Ok(message)
+}
+
+fn required<'a>(command: &str, args: &'a str, usage: &str) -> Result<&'a str> {
+    if args.is_empty() {
+        bail!(StrataError::MissingCommandArgument {
+            command: command.to_string(),
+            usage: usage.to_string(),
+        });
+    }
+    Ok(args)
+}
+
+/// Split the dialect options such as `delimiter=;` off the end of the arguments
+fn split_dialect_options(args: &str) -> Result<(&str, Vec<DialectOption>)> {
+    let mut rest = args;
+    let mut options = Vec::new();
+    loop {
+        let (head, word) = rest.rsplit_once(char::is_whitespace).unwrap_or(("", rest));
+        let is_option = word
+            .split_once('=')
+            .is_some_and(|(name, _)| DialectOption::NAMES.contains(&name));
+        if !is_option {
+            break;
+        }
+        options.push(DialectOption::parse(word)?);
+        rest = head.trim_end();
+    }
+    options.reverse();
+    Ok((rest, options))
+}
+
+fn parse_index(command: &str, value: &str) -> Result<usize> {
+    value
+        .parse::<usize>()
+        .map_err(|_| invalid_argument(command, value).into())
+}
+
+/// Parse `5` or `5-9`, in either order
+fn parse_row_range(command: &str, value: &str) -> Result<RangeInclusive<usize>> {
+    let (start, end) = match value.split_once('-') {
+        Some((start, end)) => (
+            parse_index(command, start.trim())?,
+            parse_index(command, end.trim())?,
+        ),
+        None => {
+            let row = parse_index(command, value)?;
+            (row, row)
+        }
+    };
+    Ok(start.min(end)..=start.max(end))
+}
+
+fn invalid_argument(command: &str, argument: &str) -> StrataError {
+    StrataError::InvalidCommandArgument {
+        command: command.to_string(),
+        argument: argument.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::component::table_view::{LineTerminator, SortKey};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_command_line() {
+        assert!(matches!(parse_command_line("w").unwrap(), Message::Save));
+        assert!(matches!(
+            parse_command_line(":w  out/a b.csv ").unwrap(),
+            Message::SaveTo { path: Some(path), options }
+                if path == PathBuf::from("out/a b.csv") && options.is_empty()
+        ));
+        assert!(matches!(
+            parse_command_line("w out.tsv delimiter=tab terminator=crlf").unwrap(),
+            Message::SaveTo { path: Some(path), options }
+                if path == PathBuf::from("out.tsv")
+                    && options == vec![
+                        DialectOption::Delimiter(b'\t'),
+                        DialectOption::Terminator(LineTerminator::CrLf),
+                    ]
+        ));
+        assert!(matches!(
+            parse_command_line("w quote='").unwrap(),
+            Message::SaveTo { path: None, options } if options == vec![DialectOption::Quote(b'\'')]
+        ));
+        assert!(parse_command_line("w out.csv delimiter=ab").is_err());
+        assert!(matches!(
+            parse_command_line("export values.csv").unwrap(),
+            Message::ExportValues(path) if path == PathBuf::from("values.csv")
+        ));
+        assert!(parse_command_line("export").is_err());
+        assert!(matches!(
+            parse_command_line("derive total = price * qty").unwrap(),
+            Message::DeriveColumn { header, expression, live: false }
+                if header == "total" && expression == "price * qty"
+        ));
+        assert!(matches!(
+            parse_command_line("derive live = 1").unwrap(),
+            Message::DeriveColumn { header, live: false, .. } if header == "live"
+        ));
+        assert!(matches!(
+            parse_command_line("derive live a b=a").unwrap(),
+            Message::DeriveColumn { header, live: true, .. } if header == "a b"
+        ));
+        for input in ["derive total", "derive = 1", "derive total ="] {
+            assert!(parse_command_line(input).is_err(), "{}", input);
+        }
+        assert!(matches!(
+            parse_command_line("q!").unwrap(),
+            Message::DiscardAndExit
+        ));
+        assert!(matches!(
+            parse_command_line("e tests/data/fluits.csv").unwrap(),
+            Message::OpenPath(path) if path == PathBuf::from("tests/data/fluits.csv")
+        ));
+        assert!(matches!(
+            parse_command_line("sort 2 desc, 0").unwrap(),
+            Message::SortBy { keys, pin_first_row: false }
+                if keys == vec![SortKey::descending(2), SortKey::ascending(0)]
+        ));
+        assert!(matches!(
+            parse_command_line("filter 1 > 3").unwrap(),
+            Message::SetFilter(Some(filter)) if filter.query() == "1 > 3"
+        ));
+        assert!(matches!(
+            parse_command_line("goto 10 3").unwrap(),
+            Message::GoTo {
+                row: 10,
+                col: Some(3)
+            }
+        ));
+        assert!(matches!(
+            parse_command_line("12").unwrap(),
+            Message::GoTo { row: 12, col: None }
+        ));
+        assert!(matches!(
+            parse_command_line("delrow 9-5").unwrap(),
+            Message::DeleteRows(rows) if rows == (5..=9)
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |input| parse_command_line(input).err().unwrap().to_string();
+        assert_eq!(
+            message("frobnicate 1"),
+            StrataError::UnknownCommand("frobnicate".to_string()).to_string()
+        );
+        assert_eq!(
+            message("e"),
+            StrataError::MissingCommandArgument {
+                command: "e".to_string(),
+                usage: "e <path>".to_string(),
+            }
+            .to_string()
+        );
+        assert_eq!(
+            message("goto 1 x"),
+            invalid_argument("goto", "x").to_string()
+        );
+        assert_eq!(
+            message("goto 1 2 3"),
+            invalid_argument("goto", "3").to_string()
+        );
+        assert_eq!(
+            message("delrow 1-"),
+            invalid_argument("delrow", "").to_string()
+        );
+        assert_eq!(message("q now"), invalid_argument("q", "now").to_string());
+        assert!(parse_command_line("sort up").is_err());
+    }
+}
@@ -1,21 +1,38 @@
 mod handler;
 
 use crate::{app::App, error::StrataError, message::Message};
-use eyre::{bail, OptionExt, Result};
+use eyre::{OptionExt, Result};
 use handler::{
     handle_add::handle_add_table,
     handle_cancel::handle_cancel,
     handle_change_table_size::{
-        handle_collapse_col, handle_collapse_row, handle_expand_col, handle_expand_row,
+        handle_collapse_col, handle_collapse_row, handle_delete_rows, handle_duplicate_row,
+        handle_expand_col, handle_expand_row, handle_insert_col, handle_insert_row,
+        handle_move_selected,
     },
+    handle_column_stats::handle_toggle_column_stats,
+    handle_column_type::handle_set_column_type,
+    handle_command_line::handle_command_line,
+    handle_derive_column::handle_derive_column,
     handle_edit_cell::handle_edit_cell,
     handle_edit_header::handle_edit_header,
     handle_edit_table_name::handle_edit_table_name,
-    handle_jump_cell::handle_jump_cell,
+    handle_exit::{handle_discard_and_exit, handle_exit, handle_save_all_and_exit},
+    handle_filter::{handle_filter, handle_set_filter},
+    handle_history::{handle_redo, handle_undo},
+    handle_jump_cell::{handle_goto, handle_jump_cell},
     handle_jump_table::handle_jump_table,
-    handle_move_cursor::handle_move_cursor,
-    handle_open::handle_open,
-    handle_save::handle_save,
+    handle_move_cursor::{handle_motion, handle_move_cursor},
+    handle_number_format::{handle_set_number_format, handle_toggle_raw_display},
+    handle_open::{handle_open, handle_open_path},
+    handle_register::{handle_paste, handle_paste_text, handle_select_register, handle_yank},
+    handle_replace::handle_replace,
+    handle_save::{
+        handle_export_values, handle_save, handle_save_all, handle_save_as, handle_save_to,
+    },
+    handle_search::{handle_next_match, handle_previous_match, handle_search},
+    handle_selection::{handle_delete_cells, handle_fill, handle_transform, handle_visual_select},
+    handle_sort::{handle_sort, handle_sort_by},
 };
 
 pub fn update(app: &mut App, message: Message) -> Result<&mut App> {
@@ -25,24 +42,26 @@ pub fn update(app: &mut App, message: Message) -> Result<&mut App> {
             app.command_mut()
                 .ok_or_eyre(StrataError::CommandNotFound)?
                 .pop();
-            Ok(app)
+            app.command_input_changed()
         }
         Message::Cancel => handle_cancel(app),
         Message::CollapseColumn => handle_collapse_col(app),
         Message::CollapseRow => handle_collapse_row(app),
+        Message::CommandLine => handle_command_line(app),
         Message::RemoveTable => app.remove_table(),
-        Message::DeleteCell => {
-            let tv = app.selected_table_view_mut()?;
-            let (row, col) = tv
-                .selected_index()
-                .ok_or_else(|| eyre::eyre!("No cell selected"))?;
-
-            tv.update_cell(row, col, "")?;
-            Ok(app)
-        }
+        Message::DeleteCell => handle_delete_cells(app),
+        Message::DeleteRows(rows) => handle_delete_rows(app, rows),
+        Message::DiscardAndExit => handle_discard_and_exit(app),
         Message::EditTableName => handle_edit_table_name(app),
         Message::EditCell => handle_edit_cell(app),
+        Message::EditCommand(edit) => {
+            app.command_mut()
+                .ok_or_eyre(StrataError::CommandNotFound)?
+                .edit(edit);
+            app.command_input_changed()
+        }
         Message::ExecuteCommand => app.execute_command(),
+        Message::Exit => handle_exit(app),
         Message::Exiting => Ok(app.focus_exit()),
         Message::ExpandColumn => handle_expand_col(app),
         Message::ExpandRow => handle_expand_row(app),
@@ -51,21 +70,52 @@ pub fn update(app: &mut App, message: Message) -> Result<&mut App> {
             app.command_mut()
                 .ok_or_eyre(StrataError::CommandNotFound)?
                 .input(c);
-            Ok(app)
+            app.command_input_changed()
         }
+        Message::DuplicateRow => handle_duplicate_row(app),
+        Message::Fill => handle_fill(app),
+        Message::Filter => handle_filter(app),
+        Message::GoTo { row, col } => handle_goto(app, row, col),
+        Message::InsertColumn { right } => handle_insert_col(app, right),
+        Message::InsertRow { below } => handle_insert_row(app, below),
         Message::JumpTable => handle_jump_table(app),
         Message::JumpCell => handle_jump_cell(app),
         Message::Move(direction) => handle_move_cursor(app, direction),
+        Message::MoveSelected(direction) => handle_move_selected(app, direction),
+        Message::Motion { motion, count } => handle_motion(app, motion, count),
         Message::NoOp => Ok(app),
+        Message::NextMatch => handle_next_match(app),
+        Message::Normalize => {
+            app.selected_table_view_mut()?.normalize()?;
+            Ok(app)
+        }
         Message::Open => handle_open(app),
         Message::OpenFileView => app.focus_file_view(),
+        Message::OpenPath(path) => handle_open_path(app, path),
+        Message::Paste => handle_paste(app),
+        Message::PasteText(text) => handle_paste_text(app, text),
+        Message::PreviousMatch => handle_previous_match(app),
+        Message::Complete => app.complete_command(false)?.command_input_changed(),
+        Message::CompleteBackward => app.complete_command(true)?.command_input_changed(),
+        Message::RecallNewerInput => app.recall_command_history(false)?.command_input_changed(),
+        Message::RecallOlderInput => app.recall_command_history(true)?.command_input_changed(),
+        Message::Redo => handle_redo(app),
+        Message::Replace => handle_replace(app),
         Message::Save => handle_save(app),
+        Message::SaveAll => handle_save_all(app),
+        Message::SaveAs => handle_save_as(app),
+        Message::SaveAllAndExit => handle_save_all_and_exit(app),
+        Message::SaveTo { path, options } => handle_save_to(app, path, options),
+        Message::ExportValues(path) => handle_export_values(app, path),
+        Message::Search => handle_search(app, false),
+        Message::SearchBackward => handle_search(app, true),
         Message::SelectFile => {
             if let Some(file_view) = app.file_view_mut() {
                 if let Some(selected_path) = file_view.selected_path() {
                     if selected_path.is_file() {
                         app.open_table(&selected_path, true)?;
-                        let table_name = selected_path.file_stem()
+                        let table_name = selected_path
+                            .file_stem()
                             .and_then(|s| s.to_str())
                             .unwrap_or("table");
                         app.focus_table_view_by_name(table_name)?;
@@ -76,7 +126,26 @@ pub fn update(app: &mut App, message: Message) -> Result<&mut App> {
             }
             Ok(app)
         }
+        Message::SelectRegister => handle_select_register(app),
         Message::SelectTable => app.focus_table_view(),
-        _ => bail!("Message handler not implemented"),
+        Message::SetFilter(filter) => handle_set_filter(app, filter),
+        Message::SetColumnType(column_type) => handle_set_column_type(app, column_type),
+        Message::DeriveColumn {
+            header,
+            expression,
+            live,
+        } => handle_derive_column(app, header, expression, live),
+        Message::SetNumberFormat(number_format) => handle_set_number_format(app, number_format),
+        Message::ToggleColumnStats => handle_toggle_column_stats(app),
+        Message::ToggleRawDisplay => handle_toggle_raw_display(app),
+        Message::Sort => handle_sort(app),
+        Message::SortBy {
+            keys,
+            pin_first_row,
+        } => handle_sort_by(app, keys, pin_first_row),
+        Message::Transform(transform) => handle_transform(app, transform),
+        Message::Undo => handle_undo(app),
+        Message::VisualSelect(mode) => handle_visual_select(app, mode),
+        Message::Yank => handle_yank(app),
     }
 }
@@ -1,11 +1,24 @@
 pub mod handle_add;
 pub mod handle_cancel;
 pub mod handle_change_table_size;
+pub mod handle_column_stats;
+pub mod handle_column_type;
+pub mod handle_command_line;
+pub mod handle_derive_column;
 pub mod handle_edit_cell;
 pub mod handle_edit_header;
 pub mod handle_edit_table_name;
+pub mod handle_exit;
+pub mod handle_filter;
+pub mod handle_history;
 pub mod handle_jump_cell;
 pub mod handle_jump_table;
 pub mod handle_move_cursor;
+pub mod handle_number_format;
 pub mod handle_open;
+pub mod handle_register;
+pub mod handle_replace;
 pub mod handle_save;
+pub mod handle_search;
+pub mod handle_selection;
+pub mod handle_sort;
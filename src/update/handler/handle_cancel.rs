@@ -6,14 +6,20 @@ pub(crate) fn handle_cancel(app: &mut App) -> Result<&mut App> {
     match app.display_focus() {
         DisplayFocus::Command(_) => {
             app.clear_command();
+            app.cancel_search_preview();
             app.focus_last()
         }
         DisplayFocus::Error(_) => {
             app.error_popup_mut().clear();
             app.focus_last()
         }
-        DisplayFocus::TableSelector | DisplayFocus::TableView | DisplayFocus::FileView | DisplayFocus::Exit(_) => {
-            app.focus_last()
+        DisplayFocus::TableView if app.selected_table_view()?.selection().is_some() => {
+            app.selected_table_view_mut()?.clear_selection();
+            Ok(app)
         }
+        DisplayFocus::TableSelector
+        | DisplayFocus::TableView
+        | DisplayFocus::FileView
+        | DisplayFocus::Exit(_) => app.focus_last(),
     }
 }
@@ -1,9 +1,11 @@
+use std::ops::RangeInclusive;
+
 use eyre::Result;
 
-use crate::{app::App, error::StrataError};
+use crate::{app::App, error::StrataError, message::MoveDirection};
 
 pub fn handle_expand_row(app: &mut App) -> Result<&mut App> {
-    app.selected_table_view_mut()?.expand_row();
+    app.selected_table_view_mut()?.expand_row()?;
     Ok(app)
 }
 
@@ -15,8 +17,29 @@ pub fn handle_collapse_row(app: &mut App) -> Result<&mut App> {
     Ok(app)
 }
 
+pub fn handle_insert_row(app: &mut App, below: bool) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (row, _) = tv.selected_index().ok_or(StrataError::NoCellSelected)?;
+
+    tv.insert_row(if below { row + 1 } else { row })?;
+    Ok(app)
+}
+
+pub fn handle_duplicate_row(app: &mut App) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (row, _) = tv.selected_index().ok_or(StrataError::NoCellSelected)?;
+
+    tv.duplicate_row(row)?;
+    Ok(app)
+}
+
+pub fn handle_delete_rows(app: &mut App, rows: RangeInclusive<usize>) -> Result<&mut App> {
+    app.selected_table_view_mut()?.remove_rows(rows)?;
+    Ok(app)
+}
+
 pub fn handle_expand_col(app: &mut App) -> Result<&mut App> {
-    app.selected_table_view_mut()?.expand_col();
+    app.selected_table_view_mut()?.expand_col()?;
     Ok(app)
 }
 
@@ -27,3 +50,24 @@ pub fn handle_collapse_col(app: &mut App) -> Result<&mut App> {
     tv.collapse_col(col)?;
     Ok(app)
 }
+
+pub fn handle_insert_col(app: &mut App, right: bool) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (_, col) = tv.selected_index().ok_or(StrataError::NoCellSelected)?;
+
+    tv.insert_col(if right { col + 1 } else { col })?;
+    Ok(app)
+}
+
+pub fn handle_move_selected(app: &mut App, direction: MoveDirection) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (row, col) = tv.selected_index().ok_or(StrataError::NoCellSelected)?;
+
+    match direction {
+        MoveDirection::Up => tv.move_row(row, -1)?,
+        MoveDirection::Down => tv.move_row(row, 1)?,
+        MoveDirection::Left => tv.move_col(col, -1)?,
+        MoveDirection::Right => tv.move_col(col, 1)?,
+    };
+    Ok(app)
+}
@@ -0,0 +1,31 @@
+use eyre::Result;
+
+use crate::app::App;
+
+pub(crate) fn handle_toggle_column_stats(app: &mut App) -> Result<&mut App> {
+    app.toggle_column_stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::setup_sample_app;
+
+    use super::*;
+
+    #[test]
+    fn test_toggle_column_stats() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+
+        handle_toggle_column_stats(&mut app).unwrap();
+        assert!(app.column_stats().unwrap().stats().is_none());
+        assert!(!app.update_column_stats());
+        let stats = app.column_stats().unwrap().stats().unwrap();
+        assert_eq!(stats.count, 11);
+        assert_eq!(stats.empty, 9);
+
+        handle_toggle_column_stats(&mut app).unwrap();
+        assert!(app.column_stats().is_none());
+        assert!(!app.update_column_stats());
+    }
+}
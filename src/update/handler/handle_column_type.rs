@@ -0,0 +1,42 @@
+use eyre::Result;
+
+use crate::{
+    app::{component::table_view::ColumnType, App},
+    error::StrataError,
+};
+
+/// Set the type of the selected column, or infer it again when there is none
+pub(crate) fn handle_set_column_type(
+    app: &mut App,
+    column_type: Option<ColumnType>,
+) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (_, col) = tv.selected_index().ok_or(StrataError::NoCellSelected)?;
+
+    tv.set_column_type(col, column_type)?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{message::parse_command_line, test_util::setup_sample_app, update::update};
+
+    use super::*;
+
+    #[test]
+    fn test_set_column_type() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+
+        let message = parse_command_line("type enum cell 0-0,cell 1-0").unwrap();
+        update(&mut app, message).unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.column_type(0).name(), "enum");
+        assert!(tv.check_value(0, 0, "cell 1-0").is_ok());
+        assert!(tv.check_value(0, 0, "cell 0-1").is_err());
+
+        update(&mut app, parse_command_line("type").unwrap()).unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.column_type(0), &ColumnType::Text);
+    }
+}
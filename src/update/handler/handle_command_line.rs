@@ -0,0 +1,92 @@
+use eyre::Result;
+
+use crate::{
+    app::{
+        component::command::{complete_path, CommandPopup},
+        App,
+    },
+    message::parse_command_line,
+    update::update,
+};
+
+/// Ask for a command such as `w path` or `sort 2 desc` and run it
+pub(crate) fn handle_command_line(app: &mut App) -> Result<&mut App> {
+    app.focus_command(
+        CommandPopup::new(
+            "Command [e.g. w path | e file | sort 2 desc | goto 10 3 | delrow 5-9 | q!]",
+            "",
+            Box::new(|input, app| {
+                let message = parse_command_line(input)?;
+                app.focus_last()?;
+                update(app, message)?;
+                Ok(())
+            }),
+        )
+        .with_history_key("Command Line")
+        .with_completion(complete_command_path),
+    );
+    Ok(app)
+}
+
+/// Complete the path given to the commands taking one
+fn complete_command_path(input: &str, app: &App) -> Vec<String> {
+    match input.split_once(' ') {
+        Some((command @ ("w" | "write" | "e" | "edit"), path)) => complete_path(path, app)
+            .into_iter()
+            .map(|path| format!("{command} {path}"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::display_focus::DisplayFocus,
+        test_util::{input_to_command, setup_sample_app},
+    };
+
+    use super::*;
+
+    fn run(app: &mut App, input: &str) -> Result<()> {
+        handle_command_line(app)?;
+        input_to_command(app, input);
+        app.execute_command()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_handle_command_line() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+
+        run(&mut app, "goto 1 1").unwrap();
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((1, 1))
+        );
+
+        run(&mut app, "delrow 0-1").unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert!(tv.rows[0].iter().all(String::is_empty));
+
+        run(&mut app, "u").unwrap();
+        assert_eq!(app.selected_table_view().unwrap().rows[0][0], "cell 0-0");
+
+        assert!(run(&mut app, "q").is_err());
+        assert!(!app.should_quit());
+        run(&mut app, "q!").unwrap();
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_complete_command_path() {
+        let app = setup_sample_app();
+        assert_eq!(
+            complete_command_path("e tests/da", &app),
+            vec!["e tests/data/".to_string()]
+        );
+        assert!(complete_command_path("sort 1", &app).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+use eyre::Result;
+
+use crate::app::App;
+
+/// Append a column computed from the other columns of each row
+pub(crate) fn handle_derive_column(
+    app: &mut App,
+    header: String,
+    expression: String,
+    live: bool,
+) -> Result<&mut App> {
+    app.selected_table_view_mut()?
+        .derive_col(&header, &expression, live)?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{message::parse_command_line, test_util::setup_sample_app, update::update};
+
+    use super::*;
+
+    #[test]
+    fn test_derive_column() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+
+        let message = parse_command_line("derive live first word = REGEX(A0, \"\\w+\")").unwrap();
+        update(&mut app, message).unwrap();
+        let tv = app.selected_table_view().unwrap();
+        let col = tv.headers().len() - 1;
+        assert_eq!(tv.headers()[col], "first word");
+        assert_eq!(tv.shown_value(1, col), "cell");
+
+        assert!(handle_derive_column(&mut app, "sum".into(), "1 +".into(), false).is_err());
+    }
+}
@@ -17,6 +17,7 @@ pub(crate) fn handle_edit_cell(app: &mut App) -> Result<&mut App> {
                 .selected_index()
                 .ok_or_else(|| eyre::eyre!("No cell selected"))?;
 
+            tv.check_value(row, col, input)?;
             tv.update_cell(row, col, input)?;
             app.focus_table_view()?;
             Ok(())
@@ -27,7 +28,10 @@ pub(crate) fn handle_edit_cell(app: &mut App) -> Result<&mut App> {
 
 #[cfg(test)]
 mod tests {
-    use crate::test_util::{input_to_command, setup_sample_app};
+    use crate::{
+        app::component::table_view::ColumnType,
+        test_util::{input_to_command, setup_sample_app},
+    };
 
     use super::*;
 
@@ -46,4 +50,55 @@ mod tests {
             "new cell value"
         );
     }
+
+    #[test]
+    fn test_edit_cell_rejects_invalid_value() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .set_column_type(0, Some(ColumnType::Integer))
+            .unwrap();
+
+        handle_edit_cell(&mut app).unwrap();
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "abc");
+        assert!(app.execute_command().is_err());
+        assert_eq!(
+            app.selected_table_view().unwrap().cell_value(0, 0).unwrap(),
+            "cell 0-0"
+        );
+
+        handle_edit_cell(&mut app).unwrap();
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "-12");
+        app.execute_command().unwrap();
+        assert_eq!(
+            app.selected_table_view().unwrap().cell_value(0, 0).unwrap(),
+            "-12"
+        );
+    }
+
+    #[test]
+    fn test_edit_cell_rejects_circular_formula() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        let tv = app.selected_table_view_mut().unwrap();
+        tv.update_cell(1, 0, "=A0").unwrap();
+
+        handle_edit_cell(&mut app).unwrap();
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "=A1&\"!\"");
+        assert!(app.execute_command().is_err());
+
+        let tv = app.selected_table_view_mut().unwrap();
+        tv.update_cell(1, 0, "value").unwrap();
+        handle_edit_cell(&mut app).unwrap();
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "=A1&\"!\"");
+        app.execute_command().unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.shown_value(0, 0), "value!");
+        assert_eq!(tv.cell_value(0, 0).unwrap(), "=A1&\"!\"");
+    }
 }
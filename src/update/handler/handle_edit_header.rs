@@ -1,6 +1,9 @@
 use eyre::Result;
 
-use crate::app::{component::command::CommandPopup, App};
+use crate::app::{
+    component::command::{complete_header, CommandPopup},
+    App,
+};
 
 pub(crate) fn handle_edit_header(app: &mut App) -> Result<&mut App> {
     let tv = app.selected_table_view()?;
@@ -14,19 +17,22 @@ pub(crate) fn handle_edit_header(app: &mut App) -> Result<&mut App> {
         .unwrap_or(&String::from(""))
         .clone();
 
-    app.focus_command(CommandPopup::new(
-        "Edit Header",
-        &initial_value,
-        Box::new(|input, app| {
-            let tv = app.selected_table_view_mut()?;
-            let (_, col) = tv
-                .selected_index()
-                .ok_or_else(|| eyre::eyre!("No column selected"))?;
+    app.focus_command(
+        CommandPopup::new(
+            "Edit Header",
+            &initial_value,
+            Box::new(|input, app| {
+                let tv = app.selected_table_view_mut()?;
+                let (_, col) = tv
+                    .selected_index()
+                    .ok_or_else(|| eyre::eyre!("No column selected"))?;
 
-            tv.update_header(col, input)?;
-            app.focus_last()?;
-            Ok(())
-        }),
-    ));
+                tv.update_header(col, input)?;
+                app.focus_last()?;
+                Ok(())
+            }),
+        )
+        .with_completion(complete_header),
+    );
     Ok(app)
 }
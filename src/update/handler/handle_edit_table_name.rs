@@ -1,11 +1,8 @@
-use eyre::{OptionExt, Result};
-
-use crate::{
-    app::{
-        component::{command::CommandPopup, table_selector::TableName},
-        App,
-    },
-    error::StrataError,
+use eyre::Result;
+
+use crate::app::{
+    component::{command::CommandPopup, table_selector::TableName},
+    App,
 };
 
 pub(crate) fn handle_edit_table_name(app: &mut App) -> Result<&mut App> {
@@ -14,13 +11,8 @@ pub(crate) fn handle_edit_table_name(app: &mut App) -> Result<&mut App> {
         "",
         Box::new(|input, app| {
             let table_name = TableName::from(input.to_string())?;
-            let selected_index = app
-                .table_selector()
-                .selected_index()
-                .ok_or_eyre(StrataError::NoTableSelected)?;
 
-            app.table_selector_mut()
-                .update_table(selected_index, table_name)?;
+            app.rename_selected_table(table_name)?;
             app.focus_table_selector();
             Ok(())
         }),
@@ -0,0 +1,69 @@
+use eyre::{bail, Result};
+
+use crate::{app::App, error::StrataError};
+
+use super::handle_save::save_all;
+
+/// Quit unless some table has unsaved changes
+pub(crate) fn handle_exit(app: &mut App) -> Result<&mut App> {
+    if app.has_unsaved_changes() {
+        let names: Vec<String> = app
+            .unsaved_table_names()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        bail!(StrataError::UnsavedChanges(names.join(", ")));
+    }
+    Ok(app.quit())
+}
+
+pub(crate) fn handle_discard_and_exit(app: &mut App) -> Result<&mut App> {
+    Ok(app.quit())
+}
+
+/// Save every modified table, then quit
+pub(crate) fn handle_save_all_and_exit(app: &mut App) -> Result<&mut App> {
+    save_all(app, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{input_to_command, setup_sample_app};
+
+    use super::*;
+
+    #[test]
+    fn test_handle_exit() {
+        let mut app = setup_sample_app();
+        app.focus_exit();
+        assert!(handle_exit(&mut app).is_err());
+        assert!(!app.should_quit());
+
+        handle_discard_and_exit(&mut app).unwrap();
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_handle_save_all_and_exit() {
+        let mut app = setup_sample_app();
+        app.focus_exit();
+        let dir = std::env::temp_dir();
+
+        handle_save_all_and_exit(&mut app).unwrap();
+        assert_eq!(app.command_name(), Some("Save File Path [table1]"));
+        let path1 = dir.join("strata_test_save_all_1.csv");
+        input_to_command(&mut app, path1.to_str().unwrap());
+        app.execute_command().unwrap();
+        assert!(!app.should_quit());
+
+        assert_eq!(app.command_name(), Some("Save File Path [table2]"));
+        let path2 = dir.join("strata_test_save_all_2.csv");
+        input_to_command(&mut app, path2.to_str().unwrap());
+        app.execute_command().unwrap();
+
+        assert!(app.should_quit());
+        assert!(!app.has_unsaved_changes());
+        std::fs::remove_file(path1).unwrap();
+        std::fs::remove_file(path2).unwrap();
+    }
+}
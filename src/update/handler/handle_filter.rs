@@ -0,0 +1,63 @@
+use eyre::Result;
+
+use crate::app::{
+    component::{command::CommandPopup, table_view::RowFilter},
+    App,
+};
+
+/// Ask for the conditions of the filter, starting from the current ones
+pub(crate) fn handle_filter(app: &mut App) -> Result<&mut App> {
+    let tv = app.selected_table_view()?;
+    let initial_input = match tv.filter() {
+        Some(filter) => filter.query().to_string(),
+        None => format!("{} ", tv.selected_index().map_or(0, |(_, col)| col)),
+    };
+
+    app.focus_command(CommandPopup::new(
+        "Filter [e.g. 1 >= 100; 0 ~ apple | = != ~ =~ < <= > >= empty !empty | empty to clear]",
+        &initial_input,
+        Box::new(|input, app| {
+            let filter = match input.trim() {
+                "" => None,
+                query => Some(RowFilter::parse(query)?),
+            };
+            app.focus_last()?;
+            handle_set_filter(app, filter)?;
+            Ok(())
+        }),
+    ));
+    Ok(app)
+}
+
+/// Show only the rows matching the filter, or all of them when there is none
+pub(crate) fn handle_set_filter(app: &mut App, filter: Option<RowFilter>) -> Result<&mut App> {
+    app.selected_table_view_mut()?.set_filter(filter)?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{input_to_command, setup_sample_app};
+
+    use super::*;
+
+    #[test]
+    fn test_handle_filter() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_filter(&mut app).unwrap();
+        input_to_command(&mut app, "~ 1-");
+        app.execute_command().unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.filter().map(RowFilter::query), Some("0 ~ 1-"));
+        assert_eq!(tv.visible_row_count(), 1);
+
+        // the prompt starts from the current filter and clears it when emptied
+        handle_filter(&mut app).unwrap();
+        assert_eq!(app.command().unwrap().value(), "0 ~ 1-");
+        app.command_mut().unwrap().clear();
+        app.execute_command().unwrap();
+        assert!(app.selected_table_view().unwrap().filter().is_none());
+    }
+}
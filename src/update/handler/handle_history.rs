@@ -0,0 +1,67 @@
+use eyre::Result;
+
+use crate::app::{display_focus::DisplayFocus, App};
+
+pub(crate) fn handle_undo(app: &mut App) -> Result<&mut App> {
+    match app.display_focus() {
+        DisplayFocus::TableSelector => app.undo_table_list(),
+        DisplayFocus::TableView => {
+            app.selected_table_view_mut()?.undo()?;
+            Ok(app)
+        }
+        _ => Ok(app),
+    }
+}
+
+pub(crate) fn handle_redo(app: &mut App) -> Result<&mut App> {
+    match app.display_focus() {
+        DisplayFocus::TableSelector => app.redo_table_list(),
+        DisplayFocus::TableView => {
+            app.selected_table_view_mut()?.redo()?;
+            Ok(app)
+        }
+        _ => Ok(app),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::setup_sample_app;
+
+    use super::*;
+
+    #[test]
+    fn test_undo_redo_cell() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .update_cell(0, 0, "new value")
+            .unwrap();
+
+        handle_undo(&mut app).unwrap();
+        assert_eq!(
+            app.selected_table_view().unwrap().cell_value(0, 0).unwrap(),
+            "cell 0-0"
+        );
+
+        handle_redo(&mut app).unwrap();
+        assert_eq!(
+            app.selected_table_view().unwrap().cell_value(0, 0).unwrap(),
+            "new value"
+        );
+    }
+
+    #[test]
+    fn test_undo_remove_table() {
+        let mut app = setup_sample_app();
+        app.remove_table().unwrap();
+
+        handle_undo(&mut app).unwrap();
+        assert!(app.selected_table_view().is_ok());
+        assert_eq!(
+            app.table_selector().selected_table_name().unwrap().as_str(),
+            "table1"
+        );
+    }
+}
@@ -16,14 +16,21 @@ pub(crate) fn handle_jump_cell(app: &mut App) -> Result<&mut App> {
                 .map(|(row, col)| (row.parse::<usize>(), col.parse::<usize>()))
                 .ok_or_eyre(StrataError::StringParseError(index_str))?;
 
-            app.selected_table_view_mut()?.select_cell(row?, col?)?;
-            app.focus_table_view()?;
+            handle_goto(app, row?, Some(col?))?;
             Ok(())
         }),
     ));
     Ok(app)
 }
 
+/// Select the cell, staying in the current column when none is given
+pub(crate) fn handle_goto(app: &mut App, row: usize, col: Option<usize>) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let col = col.unwrap_or(tv.selected_index().map_or(0, |(_, col)| col));
+    tv.select_cell(row, col)?;
+    app.focus_table_view()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -1,21 +1,27 @@
 use eyre::Result;
 
 use crate::app::{
-    component::{command::CommandPopup, table_selector::TableName},
+    component::{
+        command::{complete_table_name, CommandPopup},
+        table_selector::TableName,
+    },
     App,
 };
 
 pub(crate) fn handle_jump_table(app: &mut App) -> Result<&mut App> {
-    app.focus_command(CommandPopup::new(
-        "Jump [input table name e.g. table1]",
-        "",
-        Box::new(|input, app| {
-            let table_name = TableName::from(input)?;
-            app.table_selector_mut().select_by_name(&table_name)?;
-            app.focus_table_view()?;
-            Ok(())
-        }),
-    ));
+    app.focus_command(
+        CommandPopup::new(
+            "Jump [input table name e.g. table1]",
+            "",
+            Box::new(|input, app| {
+                let table_name = TableName::from(input)?;
+                app.table_selector_mut().select_by_name(&table_name)?;
+                app.focus_table_view()?;
+                Ok(())
+            }),
+        )
+        .with_completion(complete_table_name),
+    );
     Ok(app)
 }
 
@@ -42,4 +48,27 @@ mod tests {
         );
         assert_eq!(*app.display_focus(), DisplayFocus::TableView);
     }
+
+    #[test]
+    fn test_complete_table_name() {
+        let mut app = setup_sample_app();
+
+        handle_jump_table(&mut app).unwrap();
+        input_to_command(&mut app, "t");
+        app.complete_command(false).unwrap();
+        assert_eq!(app.command().unwrap().value(), "table");
+        assert_eq!(
+            app.command().unwrap().completion().unwrap().candidates(),
+            ["table1", "table2"]
+        );
+
+        app.complete_command(false).unwrap();
+        app.complete_command(false).unwrap();
+        assert_eq!(app.command().unwrap().value(), "table2");
+        app.execute_command().unwrap();
+        assert_eq!(
+            *app.table_selector().selected_table_name().unwrap(),
+            TableName::from("table2").unwrap()
+        );
+    }
 }
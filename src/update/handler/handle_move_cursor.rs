@@ -1,7 +1,7 @@
 use eyre::Result;
 
 use crate::{
-    app::{display_focus::DisplayFocus, App},
+    app::{component::table_view::Motion, display_focus::DisplayFocus, App},
     message::MoveDirection,
 };
 
@@ -38,8 +38,12 @@ pub(crate) fn handle_move_cursor(app: &mut App, direction: MoveDirection) -> Res
                 match direction {
                     MoveDirection::Up => file_view.select_prev(),
                     MoveDirection::Down => file_view.select_next(),
-                    MoveDirection::Left => { file_view.go_up_directory()?; }
-                    MoveDirection::Right => { file_view.expand_directory()?; }
+                    MoveDirection::Left => {
+                        file_view.go_up_directory()?;
+                    }
+                    MoveDirection::Right => {
+                        file_view.expand_directory()?;
+                    }
                 }
             }
             Ok(app)
@@ -47,3 +51,14 @@ pub(crate) fn handle_move_cursor(app: &mut App, direction: MoveDirection) -> Res
         _ => Ok(app),
     }
 }
+
+pub(crate) fn handle_motion(
+    app: &mut App,
+    motion: Motion,
+    count: Option<usize>,
+) -> Result<&mut App> {
+    if let DisplayFocus::TableView = app.display_focus() {
+        app.selected_table_view_mut()?.apply_motion(motion, count)?;
+    }
+    Ok(app)
+}
@@ -0,0 +1,42 @@
+use eyre::Result;
+
+use crate::app::{component::table_view::NumberFormat, App};
+
+pub(crate) fn handle_set_number_format(
+    app: &mut App,
+    number_format: NumberFormat,
+) -> Result<&mut App> {
+    app.selected_table_view_mut()?
+        .set_number_format(number_format);
+    Ok(app)
+}
+
+pub(crate) fn handle_toggle_raw_display(app: &mut App) -> Result<&mut App> {
+    app.selected_table_view_mut()?.toggle_raw_display();
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{message::parse_command_line, test_util::setup_sample_app, update::update};
+
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+
+        update(
+            &mut app,
+            parse_command_line("format sep=' places=1").unwrap(),
+        )
+        .unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.number_format().thousands_separator, Some('\''));
+        assert_eq!(tv.number_format().decimal_places, Some(1));
+
+        handle_toggle_raw_display(&mut app).unwrap();
+        assert!(app.selected_table_view().unwrap().is_raw_display());
+    }
+}
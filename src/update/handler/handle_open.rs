@@ -1,35 +1,47 @@
-use std::{ffi::OsStr, path::Path};
+use std::{ffi::OsStr, path::PathBuf};
 
 use eyre::{OptionExt, Result};
 
 use crate::{
-    app::{component::command::CommandPopup, display_focus::DisplayFocus, App},
+    app::{
+        component::command::{complete_path, CommandPopup},
+        display_focus::DisplayFocus,
+        App,
+    },
     error::StrataError,
 };
 
 pub(crate) fn handle_open(app: &mut App) -> Result<&mut App> {
     match app.display_focus() {
         DisplayFocus::TableSelector => {
-            app.focus_command(CommandPopup::new(
-                "Open File",
-                "",
-                Box::new(|input, app| {
-                    let path = Path::new(input);
-                    let table_name = path
-                        .file_stem()
-                        .and_then(OsStr::to_str)
-                        .ok_or_eyre(StrataError::InvalidTableName)?;
-                    app.open_table(&path, true)?;
-                    app.focus_table_view_by_name(&table_name)?;
-                    Ok(())
-                }),
-            ));
+            app.focus_command(
+                CommandPopup::new(
+                    "Open File",
+                    "",
+                    Box::new(|input, app| {
+                        handle_open_path(app, PathBuf::from(input))?;
+                        Ok(())
+                    }),
+                )
+                .with_completion(complete_path),
+            );
             Ok(app)
         }
         _ => Ok(app),
     }
 }
 
+/// Open the file as a new table and show it
+pub(crate) fn handle_open_path(app: &mut App, path: PathBuf) -> Result<&mut App> {
+    let table_name = path
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .ok_or_eyre(StrataError::InvalidTableName)?;
+    app.open_table(&path, true)?;
+    app.focus_table_view_by_name(table_name)?;
+    Ok(app)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,175 @@
+use eyre::{OptionExt, Result};
+
+use crate::{
+    app::{
+        component::{command::CommandPopup, table_view::SelectionMode},
+        display_focus::DisplayFocus,
+        register::{Register, CLIPBOARD_REGISTER},
+        App,
+    },
+    error::StrataError,
+};
+
+/// Ask for the register of the next yank or paste, without waiting for `Enter`
+pub(crate) fn handle_select_register(app: &mut App) -> Result<&mut App> {
+    app.focus_command(
+        CommandPopup::new(
+            "Register [a-z 0-9 | + clipboard]",
+            "",
+            Box::new(|_, app| {
+                app.focus_last()?;
+                Ok(())
+            }),
+        )
+        .without_history()
+        .on_change(|input, app| {
+            let Some(name) = input.chars().next() else {
+                return Ok(());
+            };
+            app.clear_command();
+            app.focus_last()?;
+            app.registers_mut().select(name)?;
+            Ok(())
+        }),
+    );
+    Ok(app)
+}
+
+/// Copy the visual selection, or the selected cell, to the register
+pub(crate) fn handle_yank(app: &mut App) -> Result<&mut App> {
+    let name = app.registers_mut().take_pending();
+    let tv = app.selected_table_view_mut()?;
+    let range = tv.target_range()?;
+    let mode = tv
+        .selection()
+        .map_or(SelectionMode::Cells, |selection| selection.mode);
+    let register = Register::new(tv.yank_range(&range), mode);
+    tv.clear_selection();
+
+    if name == CLIPBOARD_REGISTER {
+        app.copy_to_clipboard(register.to_tsv()?);
+    }
+    app.registers_mut().store(name, register);
+    Ok(app)
+}
+
+/// Paste the register at the selected cell
+///
+/// Yanked rows are pasted from the first column and yanked columns from the first row.
+pub(crate) fn handle_paste(app: &mut App) -> Result<&mut App> {
+    let name = app.registers_mut().take_pending();
+    let register = app.registers().get(name)?.clone();
+    paste(app, &register)
+}
+
+/// Paste text sent by the terminal, e.g. cells copied from a spreadsheet
+pub(crate) fn handle_paste_text(app: &mut App, text: String) -> Result<&mut App> {
+    match app.display_focus() {
+        DisplayFocus::Command(_) => {
+            let command = app.command_mut().ok_or_eyre(StrataError::CommandNotFound)?;
+            for c in text.chars().filter(|c| !c.is_control()) {
+                command.input(c);
+            }
+            app.command_input_changed()
+        }
+        DisplayFocus::TableView => {
+            let register = Register::from_tsv(&text)?;
+            app.registers_mut()
+                .store(CLIPBOARD_REGISTER, register.clone());
+            paste(app, &register)
+        }
+        _ => Ok(app),
+    }
+}
+
+fn paste<'a>(app: &'a mut App, register: &Register) -> Result<&'a mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let (row, col) = tv
+        .selected_index()
+        .ok_or_eyre(StrataError::NoCellSelected)?;
+    let at = match register.mode {
+        SelectionMode::Cells => (row, col),
+        SelectionMode::Rows => (row, 0),
+        SelectionMode::Columns => (0, col),
+    };
+    tv.paste_block(at, &register.values)?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::register::UNNAMED_REGISTER,
+        message::{Message, MoveDirection},
+        test_util::setup_sample_app,
+        update::update,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_yank_and_paste_rows() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        update(&mut app, Message::VisualSelect(SelectionMode::Rows)).unwrap();
+        update(&mut app, Message::Yank).unwrap();
+
+        let tv = app.selected_table_view_mut().unwrap();
+        assert_eq!(tv.selection(), None);
+        tv.select_cell(5, 3).unwrap();
+        update(&mut app, Message::Paste).unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[5][0], "cell 0-0");
+        assert_eq!(tv.rows[5][1], "cell 0-1");
+    }
+
+    #[test]
+    fn test_named_register() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        update(&mut app, Message::SelectRegister).unwrap();
+        update(&mut app, Message::Input('a')).unwrap();
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+        assert!(app.command().is_none());
+        update(&mut app, Message::Yank).unwrap();
+        assert_eq!(app.registers().get('a').unwrap().values[0][0], "cell 0-0");
+
+        update(&mut app, Message::Move(MoveDirection::Right)).unwrap();
+        update(&mut app, Message::Yank).unwrap();
+        assert_eq!(
+            app.registers().get(UNNAMED_REGISTER).unwrap().values[0][0],
+            "cell 0-1"
+        );
+
+        update(&mut app, Message::SelectRegister).unwrap();
+        update(&mut app, Message::Input('a')).unwrap();
+        update(&mut app, Message::Paste).unwrap();
+        assert_eq!(app.selected_table_view().unwrap().rows[0][1], "cell 0-0");
+
+        update(&mut app, Message::SelectRegister).unwrap();
+        assert!(update(&mut app, Message::Input('!')).is_err());
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+    }
+
+    #[test]
+    fn test_clipboard_register() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        update(&mut app, Message::VisualSelect(SelectionMode::Cells)).unwrap();
+        update(&mut app, Message::Move(MoveDirection::Right)).unwrap();
+        update(&mut app, Message::SelectRegister).unwrap();
+        update(&mut app, Message::Input('+')).unwrap();
+        update(&mut app, Message::Yank).unwrap();
+        assert_eq!(
+            app.take_clipboard_output(),
+            Some("cell 0-0\tcell 0-1".to_string())
+        );
+
+        update(&mut app, Message::PasteText("x\ty\nz".to_string())).unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[0][1], "x");
+        assert_eq!(tv.rows[0][2], "y");
+        assert_eq!(tv.rows[1][1], "z");
+    }
+}
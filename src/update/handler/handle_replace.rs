@@ -0,0 +1,391 @@
+use eyre::{bail, OptionExt, Result};
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    app::{
+        component::{
+            command::CommandPopup,
+            table_view::{split_flags, CellRange, Replacement, Search},
+        },
+        App,
+    },
+    error::StrataError,
+};
+
+/// Cells a find-and-replace looks at
+#[derive(Debug, Clone, PartialEq)]
+enum ReplaceScope {
+    /// Cells of the visual selection in the rows shown
+    Selection(CellRange),
+    Column(usize),
+    Table,
+    AllTables,
+}
+
+/// Parse the pattern typed in the find prompt
+///
+/// The pattern is a regular expression and may contain these flags anywhere:
+/// - `\c`: ignore case
+/// - `\l`: only replace in `current_col`
+/// - `\a`: replace in every table
+///
+/// Otherwise only the cells of the visual selection are replaced, if any.
+fn parse_find(
+    query: &str,
+    current_col: usize,
+    selection: Option<&CellRange>,
+) -> Result<(Regex, ReplaceScope)> {
+    let (pattern, flags) = split_flags(query, &['c', 'l', 'a']);
+    if pattern.is_empty() {
+        bail!(StrataError::InvalidSearchPattern(query.to_string()));
+    }
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(flags.contains(&'c'))
+        .build()
+        .map_err(|e| StrataError::InvalidSearchPattern(e.to_string()))?;
+    let scope = if flags.contains(&'a') {
+        ReplaceScope::AllTables
+    } else if flags.contains(&'l') {
+        ReplaceScope::Column(current_col)
+    } else {
+        selection
+            .cloned()
+            .map_or(ReplaceScope::Table, ReplaceScope::Selection)
+    };
+    Ok((regex, scope))
+}
+
+/// Replacements in the scope, with the index of their table
+fn find_replacements(
+    app: &App,
+    regex: &Regex,
+    replacement: &str,
+    scope: &ReplaceScope,
+) -> Result<Vec<(usize, Replacement)>> {
+    let selected = app
+        .table_selector()
+        .selected_index()
+        .ok_or_eyre(StrataError::NoTableSelected)?;
+    let indices = match scope {
+        ReplaceScope::AllTables => (0..app.table_views().len()).collect(),
+        _ => vec![selected],
+    };
+
+    Ok(indices
+        .into_iter()
+        .flat_map(|index| {
+            let tv = &app.table_views()[index];
+            let replacements = match scope {
+                ReplaceScope::Selection(range) => {
+                    tv.find_replacements_in(regex, replacement, range)
+                }
+                ReplaceScope::Column(col) => tv.find_replacements(regex, replacement, Some(*col)),
+                ReplaceScope::Table | ReplaceScope::AllTables => {
+                    tv.find_replacements(regex, replacement, None)
+                }
+            };
+            replacements
+                .into_iter()
+                .map(move |replacement| (index, replacement))
+        })
+        .collect())
+}
+
+pub(crate) fn handle_replace(app: &mut App) -> Result<&mut App> {
+    let tv = app.selected_table_view()?;
+    let current_col = tv.selected_index().map_or(0, |(_, col)| col);
+    let selection = tv.selected_range();
+    let title = match selection {
+        Some(_) => "Find in the selection [\\c ignore case | \\l this column | \\a all tables]",
+        None => "Find [\\c ignore case | \\l this column | \\a all tables]",
+    };
+    let preview_selection = selection.clone();
+
+    app.focus_command(
+        CommandPopup::new(
+            title,
+            "",
+            Box::new(move |input, app| {
+                let (regex, scope) = parse_find(input, current_col, selection.as_ref())?;
+                let count = find_replacements(app, &regex, "", &scope)?.len();
+                if count == 0 {
+                    bail!(StrataError::SearchNotFound(input.to_string()));
+                }
+                app.focus_last()?;
+                ask_replacement(app, regex, scope, count);
+                Ok(())
+            }),
+        )
+        .on_change(move |input, app| {
+            let search = parse_find(input, current_col, preview_selection.as_ref())
+                .ok()
+                .map(|(regex, scope)| {
+                    let column = match scope {
+                        ReplaceScope::Column(col) => Some(col),
+                        _ => None,
+                    };
+                    Search::from_regex(regex, column)
+                });
+            app.selected_table_view_mut()?.preview_search(search);
+            Ok(())
+        }),
+    );
+    Ok(app)
+}
+
+fn ask_replacement(app: &mut App, regex: Regex, scope: ReplaceScope, count: usize) {
+    app.focus_command(
+        CommandPopup::new(
+            &format!(
+                "Replace {} matching cells with [$1 for capture groups]",
+                count
+            ),
+            "",
+            Box::new(move |input, app| {
+                let replacements = find_replacements(app, &regex, input, &scope)?;
+                app.focus_last()?;
+                ask_confirmation(app, replacements);
+                Ok(())
+            }),
+        )
+        .with_history_key("Replace With"),
+    );
+}
+
+fn ask_confirmation(app: &mut App, replacements: Vec<(usize, Replacement)>) {
+    app.focus_command(
+        CommandPopup::new(
+            &format!(
+                "Apply {} replacements [a all | c confirm each]",
+                replacements.len()
+            ),
+            "a",
+            Box::new(move |input, app| {
+                match input {
+                    "a" => {
+                        app.focus_last()?;
+                        finish_replace(app, &replacements)?;
+                    }
+                    "c" => {
+                        app.focus_last()?;
+                        confirm_next(app, replacements, Vec::new())?;
+                    }
+                    _ => bail!(StrataError::StringParseError(input.to_string())),
+                }
+                Ok(())
+            }),
+        )
+        .without_history(),
+    );
+}
+
+/// Ask whether to apply the first pending replacement, then the following ones
+///
+/// Nothing is written until every replacement has been answered for.
+fn confirm_next(
+    app: &mut App,
+    mut pending: Vec<(usize, Replacement)>,
+    mut accepted: Vec<(usize, Replacement)>,
+) -> Result<&mut App> {
+    if pending.is_empty() {
+        return finish_replace(app, &accepted);
+    }
+    let (index, replacement) = &pending[0];
+    app.table_selector_mut().select_index(*index)?;
+    app.table_view_mut(*index)?
+        .select_cell(replacement.row, replacement.col)?;
+    let title = format!(
+        "Replace \"{}\" with \"{}\" [y yes | n no | a all remaining | q stop]",
+        replacement.old, replacement.new
+    );
+
+    app.focus_command(
+        CommandPopup::new(
+            &title,
+            "y",
+            Box::new(move |input, app| {
+                app.focus_last()?;
+                match input {
+                    "y" => {
+                        accepted.push(pending.remove(0));
+                        confirm_next(app, pending, accepted)?;
+                    }
+                    "n" => {
+                        pending.remove(0);
+                        confirm_next(app, pending, accepted)?;
+                    }
+                    "a" => {
+                        accepted.append(&mut pending);
+                        finish_replace(app, &accepted)?;
+                    }
+                    "q" => {
+                        finish_replace(app, &accepted)?;
+                    }
+                    _ => {
+                        // ask again for the same replacement
+                        confirm_next(app, pending, accepted)?;
+                        bail!(StrataError::StringParseError(input.to_string()));
+                    }
+                }
+                Ok(())
+            }),
+        )
+        .without_history(),
+    );
+    Ok(app)
+}
+
+fn finish_replace<'a>(
+    app: &'a mut App,
+    replacements: &[(usize, Replacement)],
+) -> Result<&'a mut App> {
+    for index in 0..app.table_views().len() {
+        app.table_view_mut(index)?.clear_search();
+    }
+    app.replace_in_tables(replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{
+            component::table_view::{RowFilter, SelectionMode},
+            display_focus::DisplayFocus,
+        },
+        test_util::{input_to_command, setup_sample_app},
+    };
+
+    use super::*;
+
+    fn answer(app: &mut App, input: &str) {
+        app.command_mut().unwrap().clear();
+        input_to_command(app, input);
+        app.execute_command().unwrap();
+    }
+
+    #[test]
+    fn test_parse_find() {
+        let (regex, scope) = parse_find("\\ccell (\\d)-\\l", 3, None).unwrap();
+        assert!(regex.is_match("CELL 1-"));
+        assert_eq!(scope, ReplaceScope::Column(3));
+
+        let (_, scope) = parse_find("a\\a", 3, None).unwrap();
+        assert_eq!(scope, ReplaceScope::AllTables);
+
+        let range = CellRange::new((0, 0), (1, 1));
+        let (_, scope) = parse_find("a", 3, Some(&range)).unwrap();
+        assert_eq!(scope, ReplaceScope::Selection(range.clone()));
+        let (_, scope) = parse_find("a\\l", 3, Some(&range)).unwrap();
+        assert_eq!(scope, ReplaceScope::Column(3));
+
+        assert!(parse_find("\\c", 0, None).is_err());
+        assert!(parse_find("(", 0, None).is_err());
+    }
+
+    #[test]
+    fn test_handle_replace_all() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_replace(&mut app).unwrap();
+        answer(&mut app, "cell (\\d)-(\\d)");
+        assert_eq!(
+            app.command_name(),
+            Some("Replace 4 matching cells with [$1 for capture groups]")
+        );
+        answer(&mut app, "$2/$1");
+        answer(&mut app, "a");
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[0][1], "1/0");
+        assert_eq!(tv.rows[1][0], "0/1");
+        assert!(tv.search().is_none());
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+        // the other table is left untouched
+        assert_eq!(app.table_views()[1].rows[0][1], "cell 0-1");
+
+        app.selected_table_view_mut().unwrap().undo().unwrap();
+        assert_eq!(app.selected_table_view().unwrap().rows[0][1], "cell 0-1");
+        assert_eq!(app.selected_table_view().unwrap().rows[1][0], "cell 1-0");
+    }
+
+    #[test]
+    fn test_handle_replace_confirm_each() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .select_cell(0, 1)
+            .unwrap();
+        handle_replace(&mut app).unwrap();
+        answer(&mut app, "cell( .-1)\\a");
+        answer(&mut app, "value$1");
+        answer(&mut app, "c");
+
+        // table1 0-1
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((0, 1))
+        );
+        answer(&mut app, "y");
+        // table1 1-1
+        answer(&mut app, "n");
+        // table2 0-1
+        assert_eq!(app.table_selector().selected_index(), Some(1));
+        answer(&mut app, "y");
+        // nothing is written before the last answer
+        assert_eq!(app.table_views()[0].rows[0][1], "cell 0-1");
+        // table2 1-1
+        answer(&mut app, "q");
+
+        assert_eq!(app.table_views()[0].rows[0][1], "value 0-1");
+        assert_eq!(app.table_views()[0].rows[1][1], "cell 1-1");
+        assert_eq!(app.table_views()[1].rows[0][1], "value 0-1");
+        assert_eq!(app.table_views()[1].rows[1][1], "cell 1-1");
+        assert!(app.command().is_none());
+    }
+
+    #[test]
+    fn test_handle_replace_in_selection() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        let tv = app.selected_table_view_mut().unwrap();
+        tv.set_filter(Some(RowFilter::parse("0 ~ 0-0").unwrap()))
+            .unwrap();
+        tv.select_cell(0, 0).unwrap();
+        tv.toggle_selection(SelectionMode::Columns).unwrap();
+
+        // the hidden row of the selected column is left out
+        handle_replace(&mut app).unwrap();
+        assert_eq!(
+            app.command_name(),
+            Some("Find in the selection [\\c ignore case | \\l this column | \\a all tables]")
+        );
+        answer(&mut app, "cell");
+        assert_eq!(
+            app.command_name(),
+            Some("Replace 1 matching cells with [$1 for capture groups]")
+        );
+        answer(&mut app, "value");
+        answer(&mut app, "a");
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[0][0], "value 0-0");
+        assert_eq!(tv.rows[0][1], "cell 0-1");
+        assert_eq!(tv.rows[1][0], "cell 1-0");
+    }
+
+    #[test]
+    fn test_handle_replace_readonly() {
+        let mut app = setup_sample_app();
+        app.table_view_mut(1).unwrap().readonly = true;
+        app.focus_table_view().unwrap();
+        handle_replace(&mut app).unwrap();
+        answer(&mut app, "cell\\a");
+        answer(&mut app, "value");
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "a");
+
+        assert!(app.execute_command().is_err());
+        assert_eq!(app.table_views()[0].rows[0][0], "cell 0-0");
+    }
+}
@@ -1,19 +1,259 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use eyre::Result;
+use eyre::{OptionExt, Result};
 
-use crate::app::{component::command::CommandPopup, App};
+use crate::{
+    app::{
+        component::{
+            command::{complete_path, CommandPopup},
+            table_view::DialectOption,
+        },
+        App,
+    },
+    error::StrataError,
+};
 
+/// Save the selected table in place, or ask for a path when it has none yet
 pub(crate) fn handle_save(app: &mut App) -> Result<&mut App> {
-    app.focus_command(CommandPopup::new(
-        "Save File Path",
-        "",
-        Box::new(|input, app| {
-            let path = Path::new(input);
-            app.selected_table_view()?.save_csv(path)?;
-            app.focus_last()?;
-            Ok(())
-        }),
-    ));
+    let tv = app.selected_table_view_mut()?;
+    if tv.file_path().is_some() {
+        tv.save()?;
+        return Ok(app);
+    }
+
+    app.focus_command(
+        CommandPopup::new(
+            "Save File Path",
+            "",
+            Box::new(|input, app| {
+                let path = Path::new(input);
+                app.selected_table_view_mut()?.save_csv(path)?;
+                app.focus_last()?;
+                Ok(())
+            }),
+        )
+        .with_completion(complete_path),
+    );
+    Ok(app)
+}
+
+pub(crate) fn handle_save_as(app: &mut App) -> Result<&mut App> {
+    let initial_value = app
+        .selected_table_view()?
+        .file_path()
+        .and_then(Path::to_str)
+        .unwrap_or_default()
+        .to_string();
+
+    app.focus_command(
+        CommandPopup::new(
+            "Save As",
+            &initial_value,
+            Box::new(|input, app| {
+                let path = Path::new(input);
+                app.selected_table_view_mut()?.save_csv(path)?;
+                app.focus_last()?;
+                Ok(())
+            }),
+        )
+        .with_completion(complete_path),
+    );
     Ok(app)
 }
+
+/// Save the selected table to the path, or to its file, with the dialect changed by the
+/// options
+///
+/// The path and the dialect are kept for the next saves.
+pub(crate) fn handle_save_to(
+    app: &mut App,
+    path: Option<PathBuf>,
+    options: Vec<DialectOption>,
+) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let path = match path {
+        Some(path) => path,
+        None => tv
+            .file_path()
+            .ok_or_eyre(StrataError::NoFilePath)?
+            .to_path_buf(),
+    };
+    let mut dialect = tv.dialect().clone();
+    for option in options {
+        dialect.apply(option);
+    }
+    tv.save_csv_with_dialect(&path, dialect)?;
+    Ok(app)
+}
+
+/// Write the computed values of the selected table to the path, keeping its formulas and
+/// its file path
+pub(crate) fn handle_export_values(app: &mut App, path: PathBuf) -> Result<&mut App> {
+    app.selected_table_view()?.export_values(&path)?;
+    Ok(app)
+}
+
+pub(crate) fn handle_save_all(app: &mut App) -> Result<&mut App> {
+    save_all(app, false)
+}
+
+/// Save every modified table
+///
+/// Tables that have a file path are written in place. For the others, a path is asked
+/// for one table at a time. When `quit` is set, the app quits once everything is saved.
+pub(crate) fn save_all(app: &mut App, quit: bool) -> Result<&mut App> {
+    let mut unsaved_without_path = None;
+    for index in app.unsaved_table_indices() {
+        let tv = app.table_view_mut(index)?;
+        if tv.file_path().is_some() {
+            tv.save()?;
+        } else if unsaved_without_path.is_none() {
+            unsaved_without_path = Some(index);
+        }
+    }
+
+    let Some(index) = unsaved_without_path else {
+        if quit {
+            app.quit();
+        }
+        return Ok(app);
+    };
+    let table_name = app
+        .table_selector()
+        .table_name(index)
+        .ok_or_eyre(StrataError::TableNotFound(index.to_string()))?
+        .to_string();
+    app.table_selector_mut().select_index(index)?;
+
+    app.focus_command(
+        CommandPopup::new(
+            &format!("Save File Path [{}]", table_name),
+            "",
+            Box::new(move |input, app| {
+                let path = Path::new(input);
+                app.selected_table_view_mut()?.save_csv(path)?;
+                app.focus_last()?;
+                save_all(app, quit)?;
+                Ok(())
+            }),
+        )
+        .with_history_key("Save File Path")
+        .with_completion(complete_path),
+    );
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        app::component::table_view::LineTerminator,
+        test_util::{input_to_command, setup_sample_app},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_handle_save_in_place() {
+        let path = std::env::temp_dir().join("strata_test_save_in_place.csv");
+        fs::copy("tests/data/fluits.csv", &path).unwrap();
+
+        let mut app = setup_sample_app();
+        app.open_table(&path, true).unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .update_cell(0, 0, "banana")
+            .unwrap();
+
+        handle_save(&mut app).unwrap();
+        assert!(app.command().is_none());
+        assert!(!app.selected_table_view().unwrap().is_modified());
+        assert!(fs::read_to_string(&path).unwrap().contains("banana,100"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_handle_save_as() {
+        let path = std::env::temp_dir().join("strata_test_save_as.csv");
+
+        let mut app = setup_sample_app();
+        app.open_table(Path::new("tests/data/fluits.csv"), true)
+            .unwrap();
+
+        handle_save_as(&mut app).unwrap();
+        let command = app.command_mut().unwrap();
+        assert_eq!(command.command_name(), "Save As");
+        command.clear();
+        input_to_command(&mut app, path.to_str().unwrap());
+        app.execute_command().unwrap();
+
+        assert_eq!(
+            app.selected_table_view().unwrap().file_path(),
+            Some(path.as_path())
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_handle_save_to_with_options() {
+        let path = std::env::temp_dir().join("strata_test_save_to_with_options.csv");
+
+        let mut app = setup_sample_app();
+        app.open_table(Path::new("tests/data/fluits.csv"), true)
+            .unwrap();
+        let options = vec![
+            DialectOption::Delimiter(b';'),
+            DialectOption::Terminator(LineTerminator::CrLf),
+        ];
+        handle_save_to(&mut app, Some(path.clone()), options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fluits;price\r\napple;100\r\norange;200\r\ngrape;150\r\n"
+        );
+
+        // the dialect is kept, and the file is reused without a path
+        handle_save_to(&mut app, None, vec![DialectOption::Delimiter(b'|')]).unwrap();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("fluits|price\r\n"));
+        assert_eq!(app.selected_table_view().unwrap().dialect().delimiter, b'|');
+
+        handle_save(&mut app).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("apple|100\r\n"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_handle_save_all() {
+        let dir = std::env::temp_dir();
+        let opened_path = dir.join("strata_test_save_all_opened.csv");
+        fs::copy("tests/data/fluits.csv", &opened_path).unwrap();
+
+        let mut app = setup_sample_app();
+        app.open_table(&opened_path, true).unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .update_cell(0, 0, "banana")
+            .unwrap();
+
+        handle_save_all(&mut app).unwrap();
+        assert!(fs::read_to_string(&opened_path)
+            .unwrap()
+            .contains("banana,100"));
+        assert_eq!(app.command_name(), Some("Save File Path [table1]"));
+
+        let path1 = dir.join("strata_test_save_all_table1.csv");
+        input_to_command(&mut app, path1.to_str().unwrap());
+        app.execute_command().unwrap();
+        let path2 = dir.join("strata_test_save_all_table2.csv");
+        input_to_command(&mut app, path2.to_str().unwrap());
+        app.execute_command().unwrap();
+
+        assert!(!app.has_unsaved_changes());
+        assert!(!app.should_quit());
+        for path in [opened_path, path1, path2] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
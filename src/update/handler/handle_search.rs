@@ -0,0 +1,112 @@
+use eyre::Result;
+
+use crate::app::{
+    component::{command::CommandPopup, table_view::Search},
+    App,
+};
+
+pub(crate) fn handle_search(app: &mut App, backward: bool) -> Result<&mut App> {
+    let title = if backward {
+        "Search Backward [\\c ignore case | \\r regex | \\l this column]"
+    } else {
+        "Search [\\c ignore case | \\r regex | \\l this column]"
+    };
+    let current_col = app
+        .selected_table_view()?
+        .selected_index()
+        .map_or(0, |(_, col)| col);
+
+    app.focus_command(
+        CommandPopup::new(
+            title,
+            "",
+            Box::new(move |input, app| {
+                let search = Search::parse(input, backward, current_col)?;
+                app.focus_table_view()?;
+                app.selected_table_view_mut()?.start_search(search)?;
+                Ok(())
+            }),
+        )
+        .with_history_key("Search")
+        .on_change(move |input, app| {
+            app.selected_table_view_mut()?
+                .preview_search(Search::parse(input, backward, current_col).ok());
+            Ok(())
+        }),
+    );
+    Ok(app)
+}
+
+pub(crate) fn handle_next_match(app: &mut App) -> Result<&mut App> {
+    app.selected_table_view_mut()?.next_match()?;
+    Ok(app)
+}
+
+pub(crate) fn handle_previous_match(app: &mut App) -> Result<&mut App> {
+    app.selected_table_view_mut()?.previous_match()?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::display_focus::DisplayFocus,
+        message::Message,
+        test_util::{input_to_command, setup_sample_app},
+        update::update,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_handle_search() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_search(&mut app, false).unwrap();
+        input_to_command(&mut app, "1-1");
+        app.execute_command().unwrap();
+
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((1, 1))
+        );
+
+        handle_next_match(&mut app).unwrap();
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((1, 1))
+        );
+        handle_previous_match(&mut app).unwrap();
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_handle_search_preview() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        update(&mut app, Message::Search).unwrap();
+        for c in "1-0".chars() {
+            update(&mut app, Message::Input(c)).unwrap();
+        }
+        assert_eq!(
+            app.selected_table_view().unwrap().selected_index(),
+            Some((1, 0))
+        );
+
+        update(&mut app, Message::Cancel).unwrap();
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.selected_index(), Some((0, 0)));
+        assert!(tv.search().is_none());
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+    }
+
+    #[test]
+    fn test_handle_next_match_without_search() {
+        let mut app = setup_sample_app();
+        assert!(handle_next_match(&mut app).is_err());
+    }
+}
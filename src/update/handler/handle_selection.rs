@@ -0,0 +1,118 @@
+use eyre::Result;
+
+use crate::app::{
+    component::{
+        command::CommandPopup,
+        table_view::{SelectionMode, Transform},
+    },
+    App,
+};
+
+pub(crate) fn handle_visual_select(app: &mut App, mode: SelectionMode) -> Result<&mut App> {
+    app.selected_table_view_mut()?.toggle_selection(mode)?;
+    Ok(app)
+}
+
+/// Clear the visual selection, or the selected cell
+pub(crate) fn handle_delete_cells(app: &mut App) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let range = tv.target_range()?;
+    tv.fill_range(&range, "")?;
+    Ok(app)
+}
+
+/// Ask for a value and write it to every cell of the visual selection
+pub(crate) fn handle_fill(app: &mut App) -> Result<&mut App> {
+    let initial_value = app
+        .selected_table_view()?
+        .selected_cell_value()?
+        .to_string();
+
+    app.focus_command(CommandPopup::new(
+        "Fill",
+        &initial_value,
+        Box::new(|input, app| {
+            let tv = app.selected_table_view_mut()?;
+            let range = tv.target_range()?;
+            tv.fill_range(&range, input)?;
+            app.focus_last()?;
+            Ok(())
+        }),
+    ));
+    Ok(app)
+}
+
+pub(crate) fn handle_transform(app: &mut App, transform: Transform) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    let range = tv.target_range()?;
+    tv.transform_range(&range, |value| transform.apply(value))?;
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::display_focus::DisplayFocus,
+        message::{Message, MoveDirection},
+        test_util::{input_to_command, setup_sample_app},
+        update::update,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_delete_selection() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        update(&mut app, Message::VisualSelect(SelectionMode::Rows)).unwrap();
+        update(&mut app, Message::Move(MoveDirection::Down)).unwrap();
+        update(&mut app, Message::DeleteCell).unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert!(tv.rows[0].iter().chain(&tv.rows[1]).all(String::is_empty));
+        assert_eq!(tv.selection(), None);
+    }
+
+    #[test]
+    fn test_handle_fill() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_visual_select(&mut app, SelectionMode::Columns).unwrap();
+        handle_fill(&mut app).unwrap();
+        app.command_mut().unwrap().clear();
+        input_to_command(&mut app, "x");
+        app.execute_command().unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert!(tv.rows.iter().all(|row| row[0] == "x"));
+        assert_eq!(tv.rows[0][1], "cell 0-1");
+    }
+
+    #[test]
+    fn test_handle_transform() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_visual_select(&mut app, SelectionMode::Cells).unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .move_selector(0, 1)
+            .unwrap();
+        handle_transform(&mut app, Transform::Uppercase).unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[0][0], "CELL 0-0");
+        assert_eq!(tv.rows[0][1], "CELL 0-1");
+        assert_eq!(tv.rows[1][0], "cell 1-0");
+    }
+
+    #[test]
+    fn test_cancel_selection() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        handle_visual_select(&mut app, SelectionMode::Cells).unwrap();
+        update(&mut app, Message::Cancel).unwrap();
+
+        assert_eq!(app.selected_table_view().unwrap().selection(), None);
+        assert_eq!(*app.display_focus(), DisplayFocus::TableView);
+    }
+}
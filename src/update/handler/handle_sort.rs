@@ -0,0 +1,76 @@
+use eyre::Result;
+
+use crate::app::{
+    component::{
+        command::CommandPopup,
+        table_view::{parse_sort_keys, SortKey},
+    },
+    App,
+};
+
+pub(crate) fn handle_sort(app: &mut App) -> Result<&mut App> {
+    let tv = app.selected_table_view()?;
+    let current_col = tv.selected_index().map_or(0, |(_, col)| col);
+    // a header loaded as data is likely meant to stay on top
+    let initial_input = if tv.has_header {
+        current_col.to_string()
+    } else {
+        format!("{} \\p", current_col)
+    };
+
+    app.focus_command(CommandPopup::new(
+        "Sort [e.g. 2 desc, 0 | \\p keep first row]",
+        &initial_input,
+        Box::new(|input, app| {
+            let (keys, pin_first_row) = parse_sort_keys(input)?;
+            app.focus_last()?;
+            handle_sort_by(app, keys, pin_first_row)?;
+            Ok(())
+        }),
+    ));
+    Ok(app)
+}
+
+/// Sort the rows of the visual selection, or the whole table
+pub(crate) fn handle_sort_by(
+    app: &mut App,
+    keys: Vec<SortKey>,
+    pin_first_row: bool,
+) -> Result<&mut App> {
+    let tv = app.selected_table_view_mut()?;
+    match tv.selected_range() {
+        // only the rows of the visual selection
+        Some(range) => {
+            tv.sort_rows_in(*range.rows.start()..*range.rows.end() + 1, &keys)?;
+            tv.clear_selection();
+        }
+        None => {
+            tv.sort_rows(&keys, pin_first_row)?;
+        }
+    }
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{input_to_command, setup_sample_app};
+
+    use super::*;
+
+    #[test]
+    fn test_handle_sort() {
+        let mut app = setup_sample_app();
+        app.focus_table_view().unwrap();
+        app.selected_table_view_mut()
+            .unwrap()
+            .select_cell(0, 1)
+            .unwrap();
+        handle_sort(&mut app).unwrap();
+        input_to_command(&mut app, " desc");
+        app.execute_command().unwrap();
+
+        let tv = app.selected_table_view().unwrap();
+        assert_eq!(tv.rows[0][1], "cell 1-1");
+        assert_eq!(tv.rows[1][1], "cell 0-1");
+    }
+}
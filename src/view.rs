@@ -14,6 +14,9 @@ use crate::app::{
     App,
 };
 
+/// Width of the panel with the statistics of the selected column
+const COLUMN_STATS_WIDTH: u16 = 32;
+
 pub fn view(frame: &mut Frame, app: &App) {
     // layout
     let [main_area, footer_area] =
@@ -24,12 +27,13 @@ pub fn view(frame: &mut Frame, app: &App) {
             .areas(main_area);
 
     // render
-    app.table_selector().render(
+    app.table_selector().render_with_modified(
         frame,
         table_selector_area,
         *app.display_focus() == DisplayFocus::TableSelector,
+        |index| app.is_table_modified(index),
     );
-    
+
     match app.display_focus() {
         DisplayFocus::FileView => {
             if let Some(file_view) = app.file_view() {
@@ -37,6 +41,18 @@ pub fn view(frame: &mut Frame, app: &App) {
             }
         }
         _ => {
+            let table_area = match app.column_stats() {
+                Some(column_stats) => {
+                    let [table_area, stats_area] = Layout::horizontal([
+                        Constraint::Min(0),
+                        Constraint::Length(COLUMN_STATS_WIDTH),
+                    ])
+                    .areas(table_area);
+                    column_stats.render(frame, stats_area, false);
+                    table_area
+                }
+                None => table_area,
+            };
             if let Ok(tv) = app.selected_table_view() {
                 tv.render(
                     frame,
@@ -46,7 +62,7 @@ pub fn view(frame: &mut Frame, app: &App) {
             }
         }
     }
-    render_footer(frame, footer_area, app.display_focus());
+    render_footer(frame, footer_area, app);
 
     // render overlay
     match app.display_focus() {
@@ -59,7 +75,7 @@ pub fn view(frame: &mut Frame, app: &App) {
             app.error_popup().render(frame);
         }
         DisplayFocus::Exit(_) => {
-            render_exit(frame);
+            render_exit(frame, &app.unsaved_table_names(), app.keymap());
         }
         _ => {}
     }
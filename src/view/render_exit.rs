@@ -1,24 +1,53 @@
 use ratatui::{
     layout::Rect,
     style::{Style, Stylize},
+    text::Text,
     Frame,
 };
 
-use crate::app::{base_component::popup::Popup, component::component_style};
+use crate::app::{
+    base_component::popup::Popup,
+    component::{component_style, table_selector::TableName},
+    keymap::{Action, Keymap, KeymapMode},
+};
 
-pub(crate) fn render_exit(frame: &mut Frame) {
+pub(crate) fn render_exit(frame: &mut Frame, unsaved_table_names: &[&TableName], keymap: &Keymap) {
+    let content: Text = if unsaved_table_names.is_empty() {
+        "Close this App?".into()
+    } else {
+        let mut lines = vec!["Unsaved changes in:".to_string()];
+        lines.extend(unsaved_table_names.iter().map(|name| format!("  {}", name)));
+        lines.push(String::new());
+        lines.push(
+            [
+                (Action::SaveAllAndExit, "Save all"),
+                (Action::DiscardAndExit, "Discard"),
+                (Action::Cancel, "Cancel"),
+            ]
+            .iter()
+            .filter_map(|(action, label)| {
+                keymap
+                    .keys_label(KeymapMode::Exit, &[*action])
+                    .map(|keys| format!("<{}> {}", keys, label))
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+        );
+        lines.join("\n").into()
+    };
     let popup_area = Rect {
         x: frame.area().width / 4,
         y: frame.area().height / 3,
         width: frame.area().width / 2,
-        height: 3,
+        height: content.height() as u16 + 2,
     };
     let popup = Popup {
         title: "Exit".into(),
-        content: "Close this App?".into(),
+        content,
         style: component_style(true),
         title_style: Style::new().white().bold(),
         border_style: Style::default(),
+        cursor: None,
     };
     frame.render_widget(popup, popup_area);
 }
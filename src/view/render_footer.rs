@@ -5,10 +5,10 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::display_focus::DisplayFocus;
+use crate::app::App;
 
-pub(super) fn render_footer(frame: &mut Frame, area: Rect, focus: &DisplayFocus) {
-    let footer =
-        Line::from(format!("{}", focus.get_guide())).style(Style::default().fg(Color::LightCyan));
+pub(super) fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let guide = app.display_focus().get_guide(app.keymap());
+    let footer = Line::from(guide).style(Style::default().fg(Color::LightCyan));
     frame.render_widget(footer, area);
 }